@@ -1,6 +1,45 @@
+use std::fmt;
+use std::str::FromStr;
+
 pub struct ActivationFunctions {}
 
-struct DerivativeFunctions {}
+//derivatives of the ActivationFunctions with the same name, taking the same input (not the output)
+pub struct DerivativeFunctions {}
+
+/* - Activation -
+An activation function by name, what Neat, the configs, backprop and the exporters use
+    function()/apply(x) - the function itself, from ActivationFunctions
+    derivative()/derivative_at(x) - its derivative, from DerivativeFunctions
+    name() - the name it's written as, Display/FromStr use the same names
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Activation {
+    Identity,
+    BinaryStep,
+    Sigmoid,
+    Relu,
+    Softplus,
+    Tanh,
+    Gaussian,
+    Sine,
+    Abs,
+    LeakyRelu,
+    Elu,
+    Selu,
+    Clamped,
+    Square,
+    Cube,
+    Inverse,
+    SteepenedSigmoid,
+}
+
+//slope of leaky relu below 0
+pub const LEAKY_RELU_SLOPE: f64 = 0.01;
+//from the selu paper
+pub const SELU_ALPHA: f64 = 1.673_263_242_354_377_3;
+pub const SELU_SCALE: f64 = 1.050_700_987_355_480_5;
+//from the original NEAT paper
+pub const STEEPENED_SIGMOID_SLOPE: f64 = 4.9;
 
 impl ActivationFunctions {
     pub fn identity(input: f64) -> f64 {
@@ -34,4 +73,289 @@ impl ActivationFunctions {
     }
 
     pub fn tanh(input: f64) -> f64 { (input).tanh() } //TODO
-}
\ No newline at end of file
+
+    //bell curve, 1 at 0
+    pub fn gaussian(input: f64) -> f64 {
+        (-input * input).exp()
+    }
+
+    pub fn sine(input: f64) -> f64 {
+        input.sin()
+    }
+
+    pub fn abs(input: f64) -> f64 {
+        input.abs()
+    }
+
+    pub fn leaky_relu(input: f64) -> f64 {
+        if input > 0.0 {
+            return input;
+        }
+
+        LEAKY_RELU_SLOPE * input
+    }
+
+    pub fn elu(input: f64) -> f64 {
+        if input > 0.0 {
+            return input;
+        }
+
+        input.exp() - 1.0
+    }
+
+    pub fn selu(input: f64) -> f64 {
+        if input > 0.0 {
+            return SELU_SCALE * input;
+        }
+
+        SELU_SCALE * SELU_ALPHA * (input.exp() - 1.0)
+    }
+
+    //identity between -1 and 1
+    pub fn clamped(input: f64) -> f64 {
+        input.clamp(-1.0, 1.0)
+    }
+
+    pub fn square(input: f64) -> f64 {
+        input * input
+    }
+
+    pub fn cube(input: f64) -> f64 {
+        input * input * input
+    }
+
+    //1/x, 0 at 0 so it stays finite
+    pub fn inverse(input: f64) -> f64 {
+        if input == 0.0 {
+            return 0.0;
+        }
+
+        1.0 / input
+    }
+
+    pub fn steepened_sigmoid(input: f64) -> f64 {
+        Self::scuffed_sigmoid(STEEPENED_SIGMOID_SLOPE * input)
+    }
+
+    //for picking an activation function from a config file
+    pub fn from_name(name: &str) -> Option<fn(f64) -> f64> {
+        Activation::from_name(name).map(|activation| activation.function())
+    }
+}
+
+impl DerivativeFunctions {
+    pub fn identity(_input: f64) -> f64 {
+        1.0
+    }
+
+    //0 everywhere (undefined at 0), so nothing can be learned through it
+    pub fn binary_step(_input: f64) -> f64 {
+        0.0
+    }
+
+    pub fn scuffed_sigmoid(input: f64) -> f64 {
+        let sigmoid = ActivationFunctions::scuffed_sigmoid(input);
+        sigmoid * (1.0 - sigmoid)
+    }
+
+    //0 at 0, same as the activation function's "greater than"
+    #[allow(non_snake_case)]
+    pub fn ReLU(input: f64) -> f64 {
+        if input > 0.0 {
+            return 1.0;
+        }
+
+        0.0
+    }
+
+    pub fn softplus(input: f64) -> f64 {
+        ActivationFunctions::scuffed_sigmoid(input)
+    }
+
+    pub fn tanh(input: f64) -> f64 {
+        1.0 - input.tanh().powi(2)
+    }
+
+    pub fn gaussian(input: f64) -> f64 {
+        -2.0 * input * ActivationFunctions::gaussian(input)
+    }
+
+    pub fn sine(input: f64) -> f64 {
+        input.cos()
+    }
+
+    //0 at 0
+    pub fn abs(input: f64) -> f64 {
+        if input > 0.0 {
+            1.0
+        } else if input < 0.0 {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn leaky_relu(input: f64) -> f64 {
+        if input > 0.0 {
+            return 1.0;
+        }
+
+        LEAKY_RELU_SLOPE
+    }
+
+    pub fn elu(input: f64) -> f64 {
+        if input > 0.0 {
+            return 1.0;
+        }
+
+        input.exp()
+    }
+
+    pub fn selu(input: f64) -> f64 {
+        if input > 0.0 {
+            return SELU_SCALE;
+        }
+
+        SELU_SCALE * SELU_ALPHA * input.exp()
+    }
+
+    pub fn clamped(input: f64) -> f64 {
+        if (-1.0..=1.0).contains(&input) {
+            return 1.0;
+        }
+
+        0.0
+    }
+
+    pub fn square(input: f64) -> f64 {
+        2.0 * input
+    }
+
+    pub fn cube(input: f64) -> f64 {
+        3.0 * input * input
+    }
+
+    //0 at 0, like the function
+    pub fn inverse(input: f64) -> f64 {
+        if input == 0.0 {
+            return 0.0;
+        }
+
+        -1.0 / (input * input)
+    }
+
+    pub fn steepened_sigmoid(input: f64) -> f64 {
+        STEEPENED_SIGMOID_SLOPE * Self::scuffed_sigmoid(STEEPENED_SIGMOID_SLOPE * input)
+    }
+
+    //same names as ActivationFunctions::from_name
+    pub fn from_name(name: &str) -> Option<fn(f64) -> f64> {
+        Activation::from_name(name).map(|activation| activation.derivative())
+    }
+}
+
+impl Activation {
+    pub const ALL: [Activation; 17] = [
+        Activation::Identity, Activation::BinaryStep, Activation::Sigmoid, Activation::Relu, Activation::Softplus,
+        Activation::Tanh, Activation::Gaussian, Activation::Sine, Activation::Abs, Activation::LeakyRelu,
+        Activation::Elu, Activation::Selu, Activation::Clamped, Activation::Square, Activation::Cube,
+        Activation::Inverse, Activation::SteepenedSigmoid,
+    ];
+
+    pub fn function(&self) -> fn(f64) -> f64 {
+        match self {
+            Activation::Identity => ActivationFunctions::identity,
+            Activation::BinaryStep => ActivationFunctions::binary_step,
+            Activation::Sigmoid => ActivationFunctions::scuffed_sigmoid,
+            Activation::Relu => ActivationFunctions::ReLU,
+            Activation::Softplus => ActivationFunctions::softplus,
+            Activation::Tanh => ActivationFunctions::tanh,
+            Activation::Gaussian => ActivationFunctions::gaussian,
+            Activation::Sine => ActivationFunctions::sine,
+            Activation::Abs => ActivationFunctions::abs,
+            Activation::LeakyRelu => ActivationFunctions::leaky_relu,
+            Activation::Elu => ActivationFunctions::elu,
+            Activation::Selu => ActivationFunctions::selu,
+            Activation::Clamped => ActivationFunctions::clamped,
+            Activation::Square => ActivationFunctions::square,
+            Activation::Cube => ActivationFunctions::cube,
+            Activation::Inverse => ActivationFunctions::inverse,
+            Activation::SteepenedSigmoid => ActivationFunctions::steepened_sigmoid,
+        }
+    }
+
+    pub fn derivative(&self) -> fn(f64) -> f64 {
+        match self {
+            Activation::Identity => DerivativeFunctions::identity,
+            Activation::BinaryStep => DerivativeFunctions::binary_step,
+            Activation::Sigmoid => DerivativeFunctions::scuffed_sigmoid,
+            Activation::Relu => DerivativeFunctions::ReLU,
+            Activation::Softplus => DerivativeFunctions::softplus,
+            Activation::Tanh => DerivativeFunctions::tanh,
+            Activation::Gaussian => DerivativeFunctions::gaussian,
+            Activation::Sine => DerivativeFunctions::sine,
+            Activation::Abs => DerivativeFunctions::abs,
+            Activation::LeakyRelu => DerivativeFunctions::leaky_relu,
+            Activation::Elu => DerivativeFunctions::elu,
+            Activation::Selu => DerivativeFunctions::selu,
+            Activation::Clamped => DerivativeFunctions::clamped,
+            Activation::Square => DerivativeFunctions::square,
+            Activation::Cube => DerivativeFunctions::cube,
+            Activation::Inverse => DerivativeFunctions::inverse,
+            Activation::SteepenedSigmoid => DerivativeFunctions::steepened_sigmoid,
+        }
+    }
+
+    pub fn apply(&self, input: f64) -> f64 {
+        (self.function())(input)
+    }
+
+    pub fn derivative_at(&self, input: f64) -> f64 {
+        (self.derivative())(input)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Activation::Identity => "identity",
+            Activation::BinaryStep => "binary_step",
+            Activation::Sigmoid => "sigmoid",
+            Activation::Relu => "relu",
+            Activation::Softplus => "softplus",
+            Activation::Tanh => "tanh",
+            Activation::Gaussian => "gaussian",
+            Activation::Sine => "sine",
+            Activation::Abs => "abs",
+            Activation::LeakyRelu => "leaky_relu",
+            Activation::Elu => "elu",
+            Activation::Selu => "selu",
+            Activation::Clamped => "clamped",
+            Activation::Square => "square",
+            Activation::Cube => "cube",
+            Activation::Inverse => "inverse",
+            Activation::SteepenedSigmoid => "steepened_sigmoid",
+        }
+    }
+
+    //name() plus the old names from ActivationFunctions
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "scuffed_sigmoid" => Some(Activation::Sigmoid),
+            "ReLU" => Some(Activation::Relu),
+            _ => Activation::ALL.iter().copied().find(|activation| activation.name() == name),
+        }
+    }
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Activation {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Activation::from_name(text.trim()).ok_or_else(|| format!("unknown activation function '{}'", text.trim()))
+    }
+}
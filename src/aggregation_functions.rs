@@ -0,0 +1,136 @@
+use std::fmt;
+use std::str::FromStr;
+
+/* - Aggregation -
+How a node combines its weighted inputs before the activation function, every NodeGene has one (sum by default)
+    apply(values) - the aggregated value, 0 for a node with no inputs
+    gradient(values) - d apply / d value for each value, for backprop
+    name() - the name it's written as, Display/FromStr use the same names
+max_abs is the value furthest from 0, keeping its sign (the positive one on a tie)
+median of an even number of values is the mean of the middle two
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Aggregation {
+    #[default]
+    Sum,
+    Product,
+    Mean,
+    Max,
+    Min,
+    Median,
+    MaxAbs,
+}
+
+impl Aggregation {
+    pub const ALL: [Aggregation; 7] = [
+        Aggregation::Sum, Aggregation::Product, Aggregation::Mean, Aggregation::Max,
+        Aggregation::Min, Aggregation::Median, Aggregation::MaxAbs,
+    ];
+
+    pub fn apply(&self, values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        match self {
+            Aggregation::Sum => values.iter().sum(),
+            Aggregation::Product => values.iter().product(),
+            Aggregation::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregation::Max => values[extreme_position(values, |a, b| a > b)],
+            Aggregation::Min => values[extreme_position(values, |a, b| a < b)],
+            Aggregation::Median => {
+                let (lower, upper) = median_positions(values);
+                (values[lower] + values[upper]) / 2.0
+            },
+            Aggregation::MaxAbs => values[max_abs_position(values)],
+        }
+    }
+
+    //the partial derivative for each value, max/min/median/max_abs only pass through the value(s) they picked
+    pub fn gradient(&self, values: &[f64]) -> Vec<f64> {
+        let mut gradient = vec![0.0; values.len()];
+        if values.is_empty() {
+            return gradient;
+        }
+
+        match self {
+            Aggregation::Sum => gradient.iter_mut().for_each(|partial| *partial = 1.0),
+            //product of the other values, not product / value so a 0 doesn't break it
+            Aggregation::Product => {
+                for (i, partial) in gradient.iter_mut().enumerate() {
+                    *partial = values.iter().enumerate().filter(|(j, _value)| *j != i).map(|(_j, value)| value).product();
+                }
+            },
+            Aggregation::Mean => gradient.iter_mut().for_each(|partial| *partial = 1.0 / values.len() as f64),
+            Aggregation::Max => gradient[extreme_position(values, |a, b| a > b)] = 1.0,
+            Aggregation::Min => gradient[extreme_position(values, |a, b| a < b)] = 1.0,
+            Aggregation::Median => {
+                let (lower, upper) = median_positions(values);
+                gradient[lower] += 0.5;
+                gradient[upper] += 0.5;
+            },
+            Aggregation::MaxAbs => gradient[max_abs_position(values)] = 1.0,
+        }
+
+        gradient
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregation::Sum => "sum",
+            Aggregation::Product => "product",
+            Aggregation::Mean => "mean",
+            Aggregation::Max => "max",
+            Aggregation::Min => "min",
+            Aggregation::Median => "median",
+            Aggregation::MaxAbs => "max_abs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Aggregation::ALL.iter().copied().find(|aggregation| aggregation.name() == name)
+    }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Aggregation {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Aggregation::from_name(text.trim()).ok_or_else(|| format!("unknown aggregation function '{}'", text.trim()))
+    }
+}
+
+//position of the first value no other value is_better than
+fn extreme_position<F>(values: &[f64], is_better: F) -> usize where
+    F: Fn(f64, f64) -> bool {
+    let mut best = 0;
+    for (i, value) in values.iter().enumerate().skip(1) {
+        if is_better(*value, values[best]) {
+            best = i;
+        }
+    }
+
+    best
+}
+
+//positions of the two middle values once sorted, the same position twice for an odd number of values
+fn median_positions(values: &[f64]) -> (usize, usize) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+
+    (order[(values.len() - 1) / 2], order[values.len() / 2])
+}
+
+//the max if it's at least as far from 0 as the min, otherwise the min
+fn max_abs_position(values: &[f64]) -> usize {
+    let max = extreme_position(values, |a, b| a > b);
+    let min = extreme_position(values, |a, b| a < b);
+
+    if values[max].abs() >= values[min].abs() { max } else { min }
+}
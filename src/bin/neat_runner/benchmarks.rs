@@ -0,0 +1,65 @@
+use std::f64::consts::PI;
+
+/* - Benchmark -
+a fixed set of (inputs, expected outputs) cases
+fitness is the number of output values minus the squared error, so a perfect network scores cases * outputs
+ */
+pub struct Benchmark {
+    pub name: &'static str,
+    pub input_size: usize,
+    pub output_size: usize,
+    pub cases: Vec<(Vec<f64>, Vec<f64>)>,
+}
+
+pub const BENCHMARK_NAMES: [&str; 4] = ["xor", "and", "parity3", "sine"];
+
+impl Benchmark {
+    pub fn from_name(name: &str) -> Option<Benchmark> {
+        match name {
+            "xor" => Some(Benchmark::from_truth_table("xor", 2, |bits| bits[0] ^ bits[1])),
+            "and" => Some(Benchmark::from_truth_table("and", 2, |bits| bits[0] & bits[1])),
+            "parity3" => Some(Benchmark::from_truth_table("parity3", 3, |bits| bits[0] ^ bits[1] ^ bits[2])),
+            "sine" => {
+                let cases = (0..8).map(|i| {
+                    let x = i as f64 / 7.0;
+                    (vec![x], vec![((2.0 * PI * x).sin() + 1.0) / 2.0])
+                }).collect();
+
+                Some(Benchmark {name: "sine", input_size: 1, output_size: 1, cases})
+            },
+            _ => None,
+        }
+    }
+
+    fn from_truth_table(name: &'static str, input_size: usize, function: fn(&[bool]) -> bool) -> Benchmark {
+        let mut cases = Vec::new();
+
+        for row in 0..(1 << input_size) {
+            let bits: Vec<bool> = (0..input_size).map(|bit| row & (1 << bit) != 0).collect();
+            let inputs = bits.iter().map(|bit| if *bit { 1.0 } else { 0.0 }).collect();
+            let output = if function(&bits) { 1.0 } else { 0.0 };
+            cases.push((inputs, vec![output]));
+        }
+
+        Benchmark {name, input_size, output_size: 1, cases}
+    }
+
+    pub fn max_fitness(&self) -> f64 {
+        (self.cases.len() * self.output_size) as f64
+    }
+
+    pub fn evaluate<F>(&self, mut network: F) -> f64 where
+        F: FnMut(&Vec<f64>) -> Vec<f64> {
+        let mut squared_error = 0.0;
+
+        for (inputs, expected) in &self.cases {
+            let outputs = network(inputs);
+            for (output, expected) in outputs.iter().zip(expected) {
+                squared_error += (output - expected).powi(2);
+            }
+        }
+
+        let fitness = self.max_fitness() - squared_error;
+        if fitness.is_finite() { fitness.max(0.0) } else { 0.0 }
+    }
+}
@@ -0,0 +1,341 @@
+use rust_machine_learning_library::export::c_source::save_genome_as_c;
+use rust_machine_learning_library::export::onnx::save_genome_as_onnx;
+use rust_machine_learning_library::export::rust_source::save_genome_as_rust;
+use rust_machine_learning_library::feed_forward::genome::Genome;
+use rust_machine_learning_library::feed_forward::mutation_rates::MutationRates;
+use rust_machine_learning_library::neat::Neat;
+use rust_machine_learning_library::neat::config::{NeatConfig, parse_config_line};
+use benchmarks::{Benchmark, BENCHMARK_NAMES};
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod benchmarks;
+
+const USAGE: &str = "usage:
+    neat_runner run <config> <benchmark> <output_dir>
+    neat_runner resume <output_dir> [checkpoint]
+    neat_runner evaluate <config> <genome> <benchmark>
+    neat_runner dot <genome> [output.dot]
+    neat_runner onnx <config> <genome> <output.onnx>
+    neat_runner rust <config> <genome> <output.rs>
+    neat_runner c <config> <genome> <output_dir> [name]";
+
+const RUN_CONFIG_FILE: &str = "run.cfg";
+const STATS_FILE: &str = "stats.csv";
+const MUTATION_RATES_FILE: &str = "mutation_rates.csv"; //the probabilities each generation was mutated with, see neat::adaptive_mutation
+const CHAMPION_FILE: &str = "champion.genome";
+const CHAMPION_NETWORK_FILE: &str = "champion.network"; //the champion with its activation function and bias, see Network
+const CHECKPOINT_DIR: &str = "checkpoints";
+
+/* - RunConfig -
+a NeatConfig plus the settings for the run itself
+ */
+struct RunConfig {
+    neat: NeatConfig,
+    benchmark: Option<String>,
+    population: usize,
+    generations: u64,
+    checkpoint_interval: u64, //0 to only checkpoint at the end
+}
+
+impl RunConfig {
+    fn load(path: &Path) -> Result<RunConfig, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("can't read {}: {}", path.display(), error))?;
+
+        let mut config = RunConfig {
+            neat: NeatConfig::default(),
+            benchmark: None,
+            population: 150,
+            generations: 100,
+            checkpoint_interval: 10,
+        };
+
+        for (line_i, line) in text.lines().enumerate() {
+            let (key, value) = match parse_config_line(line_i + 1, line).map_err(|error| format!("{}: {}", path.display(), error))? {
+                None => continue,
+                Some(key_value) => key_value,
+            };
+
+            let result = match key {
+                "benchmark" => {
+                    config.benchmark = Some(String::from(value));
+                    Ok(())
+                },
+                "population" => value.parse().map(|v| config.population = v).map_err(|_| format!("bad value '{}' for {}", value, key)),
+                "generations" => value.parse().map(|v| config.generations = v).map_err(|_| format!("bad value '{}' for {}", value, key)),
+                "checkpoint_interval" => value.parse().map(|v| config.checkpoint_interval = v).map_err(|_| format!("bad value '{}' for {}", value, key)),
+                _ => config.neat.set(key, value),
+            };
+
+            result.map_err(|message| format!("{}: line {}: {}", path.display(), line_i + 1, message))?;
+        }
+
+        Ok(config)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        if let Some(benchmark) = &self.benchmark {
+            text.push_str(&format!("benchmark = {}\n", benchmark));
+        }
+        text.push_str(&format!("population = {}\n", self.population));
+        text.push_str(&format!("generations = {}\n", self.generations));
+        text.push_str(&format!("checkpoint_interval = {}\n", self.checkpoint_interval));
+        text.push_str(&self.neat.to_text());
+
+        text
+    }
+
+    //the benchmark decides the network's shape
+    fn apply_benchmark(&mut self, benchmark: &Benchmark) {
+        self.benchmark = Some(String::from(benchmark.name));
+        self.neat.input_size = benchmark.input_size;
+        self.neat.output_size = benchmark.output_size;
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["run", config, benchmark, output_dir] => run(Path::new(config), benchmark, Path::new(output_dir)),
+        ["resume", output_dir] => resume(Path::new(output_dir), None),
+        ["resume", output_dir, checkpoint] => resume(Path::new(output_dir), Some(Path::new(checkpoint))),
+        ["evaluate", config, genome, benchmark] => evaluate(Path::new(config), Path::new(genome), benchmark),
+        ["dot", genome] => dot(Path::new(genome), None),
+        ["dot", genome, output] => dot(Path::new(genome), Some(Path::new(output))),
+        ["onnx", config, genome, output] => onnx(Path::new(config), Path::new(genome), Path::new(output)),
+        ["rust", config, genome, output] => rust(Path::new(config), Path::new(genome), Path::new(output)),
+        ["c", config, genome, output_dir] => c(Path::new(config), Path::new(genome), Path::new(output_dir), "network"),
+        ["c", config, genome, output_dir, name] => c(Path::new(config), Path::new(genome), Path::new(output_dir), name),
+        _ => Err(String::from(USAGE)),
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn get_benchmark(name: &str) -> Result<Benchmark, String> {
+    Benchmark::from_name(name).ok_or_else(|| format!("unknown benchmark '{}', expected one of: {}", name, BENCHMARK_NAMES.join(", ")))
+}
+
+fn run(config_path: &Path, benchmark_name: &str, output_dir: &Path) -> Result<(), String> {
+    let benchmark = get_benchmark(benchmark_name)?;
+    let mut config = RunConfig::load(config_path)?;
+    config.apply_benchmark(&benchmark);
+
+    fs::create_dir_all(output_dir.join(CHECKPOINT_DIR)).map_err(|error| format!("can't create {}: {}", output_dir.display(), error))?;
+    write_file(&output_dir.join(RUN_CONFIG_FILE), &config.to_text())?;
+    write_file(&output_dir.join(STATS_FILE), "generation,best_fitness,mean_fitness,species,clients,mean_complexity,phase\n")?;
+    write_file(&output_dir.join(MUTATION_RATES_FILE), &format!("generation,multiplier,{}\n", MutationRates::NAMES.join(",")))?;
+
+    let mut neat = Neat::from_config(&config.neat)?;
+    for _ in 0..config.population {
+        neat.new_client();
+    }
+
+    evolve(&mut neat, &config, &benchmark, output_dir, f64::NEG_INFINITY)
+}
+
+fn resume(output_dir: &Path, checkpoint: Option<&Path>) -> Result<(), String> {
+    let config = RunConfig::load(&output_dir.join(RUN_CONFIG_FILE))?;
+    let benchmark = match &config.benchmark {
+        None => return Err(format!("{} doesn't name a benchmark", output_dir.join(RUN_CONFIG_FILE).display())),
+        Some(name) => get_benchmark(name)?,
+    };
+
+    let checkpoint = match checkpoint {
+        Some(path) => PathBuf::from(path),
+        None => latest_checkpoint(&output_dir.join(CHECKPOINT_DIR))?,
+    };
+
+    let mut neat = Neat::load_checkpoint(&config.neat, &checkpoint).map_err(|error| format!("can't load {}: {}", checkpoint.display(), error))?;
+    println!("resuming from {} (generation {})", checkpoint.display(), neat.get_generation());
+
+    let best_so_far = best_fitness_in_stats(&output_dir.join(STATS_FILE));
+
+    //an older checkpoint than the last one redoes generations, their rows are written again
+    for file in [STATS_FILE, MUTATION_RATES_FILE] {
+        drop_rows_from_generation(&output_dir.join(file), neat.get_generation())?;
+    }
+
+    evolve(&mut neat, &config, &benchmark, output_dir, best_so_far)
+}
+
+fn evolve(neat: &mut Neat, config: &RunConfig, benchmark: &Benchmark, output_dir: &Path, mut best_so_far: f64) -> Result<(), String> {
+    let stats_path = output_dir.join(STATS_FILE);
+    let mutation_rates_path = output_dir.join(MUTATION_RATES_FILE);
+
+    while neat.get_generation() < config.generations {
+        let generation = neat.get_generation();
+        let mut total_fitness = 0.0;
+
+        for name in neat.get_client_names() {
+            let fitness = benchmark.evaluate(|inputs| neat.use_client(&name, inputs));
+            neat.score_client(&name, fitness);
+            total_fitness += fitness;
+        }
+        let best = neat.best_client().map(|client| (client.id(), client.score()));
+
+        let mean_fitness = total_fitness / neat.get_number_of_clients().max(1) as f64;
+        let best_fitness = best.as_ref().map_or(0.0, |(_name, fitness)| *fitness);
+
+        let phase = neat.get_search_phase().map_or(String::from("-"), |phase| phase.to_string());
+
+        append_file(&stats_path, &format!("{},{},{},{},{},{},{}\n", generation, best_fitness, mean_fitness,
+                                          neat.get_number_of_species(), neat.get_number_of_clients(),
+                                          neat.get_mean_complexity(), phase))?;
+        println!("generation {}: best {:.4} mean {:.4} species {} complexity {:.2} phase {}", generation, best_fitness, mean_fitness,
+                 neat.get_number_of_species(), neat.get_mean_complexity(), phase);
+
+        if let Some((name, fitness)) = best {
+            if fitness > best_so_far {
+                best_so_far = fitness;
+                neat.save_client_genome(&name, output_dir.join(CHAMPION_FILE)).map_err(|error| format!("can't save champion: {}", error))?;
+                neat.get_client_network(&name).save(output_dir.join(CHAMPION_NETWORK_FILE)).map_err(|error| format!("can't save champion: {}", error))?;
+            }
+        }
+
+        neat.update_clients();
+
+        if let Some(report) = neat.get_mutation_rate_report() {
+            let probabilities: Vec<String> = report.mean.to_array().iter().map(|probability| probability.to_string()).collect();
+            append_file(&mutation_rates_path, &format!("{},{},{}\n", report.generation, report.multiplier, probabilities.join(",")))?;
+        }
+
+        let is_last = neat.get_generation() >= config.generations;
+        let is_interval = config.checkpoint_interval > 0 && neat.get_generation().is_multiple_of(config.checkpoint_interval);
+        if is_last || is_interval {
+            let path = output_dir.join(CHECKPOINT_DIR).join(format!("generation_{}.checkpoint", neat.get_generation()));
+            neat.save_checkpoint(&path).map_err(|error| format!("can't write {}: {}", path.display(), error))?;
+        }
+    }
+
+    println!("best fitness {:.4} of {}, champion in {}", best_so_far, benchmark.max_fitness(), output_dir.join(CHAMPION_FILE).display());
+    Ok(())
+}
+
+fn evaluate(config_path: &Path, genome_path: &Path, benchmark_name: &str) -> Result<(), String> {
+    let benchmark = get_benchmark(benchmark_name)?;
+    let mut config = RunConfig::load(config_path)?;
+    config.apply_benchmark(&benchmark);
+
+    let neat = Neat::from_config(&config.neat)?;
+    let genome = Genome::load(genome_path).map_err(|error| format!("can't load {}: {}", genome_path.display(), error))?;
+
+    for (inputs, expected) in &benchmark.cases {
+        println!("{:?} -> {:?} (expected {:?})", inputs, neat.use_genome(&genome, inputs), expected);
+    }
+
+    let fitness = benchmark.evaluate(|inputs| neat.use_genome(&genome, inputs));
+    println!("fitness {:.4} of {}", fitness, benchmark.max_fitness());
+
+    Ok(())
+}
+
+fn dot(genome_path: &Path, output: Option<&Path>) -> Result<(), String> {
+    let genome = Genome::load(genome_path).map_err(|error| format!("can't load {}: {}", genome_path.display(), error))?;
+
+    match output {
+        None => {
+            print!("{}", genome.to_dot());
+            Ok(())
+        },
+        Some(path) => write_file(path, &genome.to_dot()),
+    }
+}
+
+//the config is only needed for the activation function
+fn onnx(config_path: &Path, genome_path: &Path, output: &Path) -> Result<(), String> {
+    let config = RunConfig::load(config_path)?;
+    let genome = Genome::load(genome_path).map_err(|error| format!("can't load {}: {}", genome_path.display(), error))?;
+
+    save_genome_as_onnx(&genome, config.neat.activation_function, output)
+        .map_err(|error| format!("can't write {}: {}", output.display(), error))
+}
+
+fn rust(config_path: &Path, genome_path: &Path, output: &Path) -> Result<(), String> {
+    let config = RunConfig::load(config_path)?;
+    let genome = Genome::load(genome_path).map_err(|error| format!("can't load {}: {}", genome_path.display(), error))?;
+
+    save_genome_as_rust(&genome, config.neat.activation_function, output)
+        .map_err(|error| format!("can't write {}: {}", output.display(), error))
+}
+
+//writes output_dir/name.h and output_dir/name.c, the genome came from a Neat so the bias is built in
+fn c(config_path: &Path, genome_path: &Path, output_dir: &Path, name: &str) -> Result<(), String> {
+    let config = RunConfig::load(config_path)?;
+    let genome = Genome::load(genome_path).map_err(|error| format!("can't load {}: {}", genome_path.display(), error))?;
+
+    fs::create_dir_all(output_dir).map_err(|error| format!("can't create {}: {}", output_dir.display(), error))?;
+    save_genome_as_c(&genome, config.neat.activation_function, name, true, output_dir)
+        .map_err(|error| format!("can't write {}: {}", output_dir.display(), error))
+}
+
+fn latest_checkpoint(checkpoint_dir: &Path) -> Result<PathBuf, String> {
+    let entries = fs::read_dir(checkpoint_dir).map_err(|error| format!("can't read {}: {}", checkpoint_dir.display(), error))?;
+
+    let mut latest: Option<(u64, PathBuf)> = None;
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let generation = file_name.to_str()
+            .and_then(|name| name.strip_prefix("generation_"))
+            .and_then(|name| name.strip_suffix(".checkpoint"))
+            .and_then(|number| number.parse::<u64>().ok());
+
+        if let Some(generation) = generation {
+            if latest.as_ref().is_none_or(|(latest_generation, _path)| generation > *latest_generation) {
+                latest = Some((generation, entry.path()));
+            }
+        }
+    }
+
+    latest.map(|(_generation, path)| path).ok_or_else(|| format!("no checkpoints in {}", checkpoint_dir.display()))
+}
+
+//the best fitness recorded so far, so a resumed run doesn't overwrite a better champion
+fn best_fitness_in_stats(stats_path: &Path) -> f64 {
+    let text = fs::read_to_string(stats_path).unwrap_or_default();
+
+    text.lines().skip(1)
+        .filter_map(|line| line.split(',').nth(1))
+        .filter_map(|value| value.parse::<f64>().ok())
+        .fold(f64::NEG_INFINITY, f64::max)
+}
+
+//keeps the header and the rows for generations before generation, a missing file is left missing
+fn drop_rows_from_generation(csv_path: &Path, generation: u64) -> Result<(), String> {
+    let text = match fs::read_to_string(csv_path) {
+        Err(_) => return Ok(()),
+        Ok(text) => text,
+    };
+
+    let mut kept = String::new();
+    for (line_i, line) in text.lines().enumerate() {
+        let row_generation = line.split(',').next().and_then(|value| value.parse::<u64>().ok());
+        if line_i == 0 || row_generation.is_some_and(|row_generation| row_generation < generation) {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+
+    write_file(csv_path, &kept)
+}
+
+fn write_file(path: &Path, text: &str) -> Result<(), String> {
+    fs::write(path, text).map_err(|error| format!("can't write {}: {}", path.display(), error))
+}
+
+fn append_file(path: &Path, text: &str) -> Result<(), String> {
+    let result: io::Result<()> = fs::OpenOptions::new().append(true).create(true).open(path)
+        .and_then(|mut file| file.write_all(text.as_bytes()));
+
+    result.map_err(|error| format!("can't write {}: {}", path.display(), error))
+}
@@ -0,0 +1,205 @@
+use crate::activation_functions::{Activation, LEAKY_RELU_SLOPE, SELU_ALPHA, SELU_SCALE, STEEPENED_SIGMOID_SLOPE};
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::layout::NetworkLayout;
+use crate::feed_forward::genome::Genome;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/* - C source export -
+Writes a genome as a C99 header/source pair, the source only needs <math.h> (link with -lm)
+    #define NAME_INPUTS n
+    #define NAME_OUTPUTS m
+    void name(const double inputs[NAME_INPUTS], double outputs[NAME_OUTPUTS]);
+outputs are in the same order as Calculator::run (innovation number order)
+with_bias - the first input node is the bias, it's fixed at 1.0 and left out of inputs like Neat::use_client does,
+    use it for genomes from a Neat
+without it every input node is in inputs, same as Calculator::run
+max/min/median/max_abs aggregations call a static helper (only written if it's used) on a compound literal array
+ */
+
+//the return expression of `static double activation(double x)`, same as the ActivationFunctions
+fn activation_body(activation: Activation) -> String {
+    match activation {
+        Activation::Identity => String::from("x"),
+        Activation::BinaryStep => String::from("x >= 0.0 ? 1.0 : 0.0"),
+        Activation::Sigmoid => String::from("1.0 / (1.0 + exp(-x))"),
+        Activation::Relu => String::from("x > 0.0 ? x : 0.0"),
+        Activation::Softplus => String::from("log(1.0 + exp(x))"),
+        Activation::Tanh => String::from("tanh(x)"),
+        Activation::Gaussian => String::from("exp(-x * x)"),
+        Activation::Sine => String::from("sin(x)"),
+        Activation::Abs => String::from("fabs(x)"),
+        Activation::LeakyRelu => format!("x > 0.0 ? x : {} * x", float_literal(LEAKY_RELU_SLOPE)),
+        Activation::Elu => String::from("x > 0.0 ? x : exp(x) - 1.0"),
+        Activation::Selu => format!("x > 0.0 ? {} * x : {} * (exp(x) - 1.0)", float_literal(SELU_SCALE), float_literal(SELU_SCALE * SELU_ALPHA)),
+        Activation::Clamped => String::from("x < -1.0 ? -1.0 : (x > 1.0 ? 1.0 : x)"),
+        Activation::Square => String::from("x * x"),
+        Activation::Cube => String::from("x * x * x"),
+        Activation::Inverse => String::from("x == 0.0 ? 0.0 : 1.0 / x"),
+        Activation::SteepenedSigmoid => format!("1.0 / (1.0 + exp(-{} * x))", float_literal(STEEPENED_SIGMOID_SLOPE)),
+    }
+}
+
+//a static helper for the aggregations that aren't written inline, same as Aggregation::apply
+fn aggregation_helper(aggregation: Aggregation) -> Option<&'static str> {
+    match aggregation {
+        Aggregation::Sum | Aggregation::Product | Aggregation::Mean => None,
+        Aggregation::Max => Some("static double aggregate_max(const double values[], int count) {
+    double max = values[0];
+    int i;
+    for (i = 1; i < count; i++) if (values[i] > max) max = values[i];
+    return max;
+}
+"),
+        Aggregation::Min => Some("static double aggregate_min(const double values[], int count) {
+    double min = values[0];
+    int i;
+    for (i = 1; i < count; i++) if (values[i] < min) min = values[i];
+    return min;
+}
+"),
+        Aggregation::Median => Some("static double aggregate_median(double values[], int count) {
+    int i, j;
+    for (i = 1; i < count; i++) { /* insertion sort, there's only a few values */
+        double value = values[i];
+        for (j = i; j > 0 && values[j - 1] > value; j--) values[j] = values[j - 1];
+        values[j] = value;
+    }
+    return (values[(count - 1) / 2] + values[count / 2]) / 2.0;
+}
+"),
+        Aggregation::MaxAbs => Some("static double aggregate_max_abs(const double values[], int count) {
+    double max = values[0], min = values[0];
+    int i;
+    for (i = 1; i < count; i++) {
+        if (values[i] > max) max = values[i];
+        if (values[i] < min) min = values[i];
+    }
+    return fabs(max) >= fabs(min) ? max : min;
+}
+"),
+    }
+}
+
+//the expression combining a node's weighted inputs
+fn aggregate(aggregation: Aggregation, terms: &[String]) -> String {
+    match (aggregation, terms.len()) {
+        (_, 0) => String::from("0.0"), //no connections aggregates to 0, same as the calculator
+        (_, 1) => terms[0].clone(),
+        (Aggregation::Sum, _) => terms.join(" + "),
+        (Aggregation::Product, _) => terms.join(" * "),
+        (Aggregation::Mean, _) => format!("({}) / {}", terms.join(" + "), float_literal(terms.len() as f64)),
+        (Aggregation::Median, _) => format!("aggregate_median((double[]){{{}}}, {})", terms.join(", "), terms.len()),
+        (aggregation, _) => format!("aggregate_{}((const double[]){{{}}}, {})", aggregation.name(), terms.join(", "), terms.len()),
+    }
+}
+
+//name is the function name, the header guard and defines are the upper case version of it
+//returns (header, source), the source includes the header as "name.h"
+pub fn genome_to_c(genome: &Genome, activation: Activation, name: &str, with_bias: bool) -> Result<(String, String), String> {
+    if !is_c_identifier(name) {
+        return Err(format!("'{}' isn't a valid C function name", name));
+    }
+    let layout = NetworkLayout::new(genome)?;
+    if layout.outputs.is_empty() {
+        return Err(String::from("genome has no output nodes"));
+    }
+    if with_bias && layout.inputs.len() < 2 {
+        return Err(String::from("genome needs a bias node and at least one other input node"));
+    }
+
+    let needed = layout.needed_nodes();
+    let upper_name = name.to_uppercase();
+    let bias_node = if with_bias { layout.inputs.first().copied() } else { None };
+    let input_nodes: Vec<usize> = layout.inputs.iter().copied().filter(|node| Some(*node) != bias_node).collect();
+
+    let comment = format!("/* generated by rust_machine_learning_library {} from a genome with {} nodes and {} connections\n * inputs in node order {:?}{}, outputs in node order {:?} */\n",
+                          env!("CARGO_PKG_VERSION"), genome.nodes.len(), genome.connections.len(), input_nodes,
+                          match bias_node { Some(node) => format!(" (node {} is the bias)", node), None => String::new() },
+                          layout.outputs);
+    let signature = format!("void {}(const double inputs[{}_INPUTS], double outputs[{}_OUTPUTS])", name, upper_name, upper_name);
+
+    let mut header = comment.clone();
+    header.push_str(&format!("#ifndef {}_H\n#define {}_H\n\n", upper_name, upper_name));
+    header.push_str(&format!("#define {}_INPUTS {}\n", upper_name, input_nodes.len()));
+    header.push_str(&format!("#define {}_OUTPUTS {}\n\n", upper_name, layout.outputs.len()));
+    header.push_str(&format!("{};\n\n#endif\n", signature));
+
+    let mut source = comment;
+    source.push_str(&format!("#include <math.h>\n#include \"{}.h\"\n\n", name));
+    source.push_str(&format!("static double activation(double x) {{\n    return {};\n}}\n\n", activation_body(activation)));
+
+    let mut body = String::new();
+    let mut helpers_used = Vec::new();
+
+    if !input_nodes.iter().any(|input| needed.contains(input)) {
+        body.push_str("    (void)inputs; /* no enabled connection reaches an output from an input */\n");
+    }
+
+    for node in layout.calculated_nodes() {
+        if !needed.contains(node) { continue; }
+
+        let terms: Vec<String> = layout.get_incoming(*node).iter().map(|(_con_num, from, weight)| {
+            if Some(*from) == bias_node {
+                return float_literal(*weight);
+            }
+            let value = match input_nodes.iter().position(|input| input == from) {
+                Some(position) => format!("inputs[{}]", position),
+                None => format!("node_{}", from),
+            };
+            format!("{} * {}", float_literal(*weight), value)
+        }).collect();
+
+        let aggregation = layout.get_aggregation(*node);
+        if terms.len() > 1 && !helpers_used.contains(&aggregation) {
+            helpers_used.push(aggregation);
+        }
+        body.push_str(&format!("    const double node_{} = activation({});\n", node, aggregate(aggregation, &terms)));
+    }
+
+    for (position, node) in layout.outputs.iter().enumerate() {
+        body.push_str(&format!("    outputs[{}] = node_{};\n", position, node));
+    }
+
+    //helpers go before the function using them
+    for aggregation in Aggregation::ALL.iter() {
+        if let (true, Some(helper)) = (helpers_used.contains(aggregation), aggregation_helper(*aggregation)) {
+            source.push_str(helper);
+            source.push('\n');
+        }
+    }
+    source.push_str(&format!("{} {{\n{}}}\n", signature, body));
+
+    Ok((header, source))
+}
+
+//writes directory/name.h and directory/name.c
+pub fn save_genome_as_c<P: AsRef<Path>>(genome: &Genome, activation: Activation, name: &str, with_bias: bool, directory: P) -> io::Result<()> {
+    let (header, source) = genome_to_c(genome, activation, name, with_bias)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+
+    fs::write(directory.as_ref().join(format!("{}.h", name)), header)?;
+    fs::write(directory.as_ref().join(format!("{}.c", name)), source)
+}
+
+fn is_c_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+//{:e} gives the shortest literal that parses back to the same double, eg. 1.25e0
+fn float_literal(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NAN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "INFINITY" } else { "(-INFINITY)" })
+    } else if value < 0.0 {
+        format!("({:e})", value)
+    } else {
+        format!("{:e}", value)
+    }
+}
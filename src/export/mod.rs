@@ -0,0 +1,11 @@
+#[cfg(test)]
+mod tests;
+
+mod protobuf;
+pub mod onnx;
+pub mod rust_source;
+pub mod c_source;
+
+/* - export -
+Turning a genome into something that runs without this crate
+ */
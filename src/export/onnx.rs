@@ -0,0 +1,262 @@
+use crate::activation_functions::{Activation, STEEPENED_SIGMOID_SLOPE};
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::layout::NetworkLayout;
+use super::protobuf::ProtoMessage;
+use crate::feed_forward::genome::Genome;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/* - ONNX export -
+Writes a genome as an onnx model (ir version 7, opset 13), float32
+    input "input" - 1d tensor, one value per input node in innovation number order (same as Calculator::run,
+        so for a Neat genome the first value is the bias and should be 1.0)
+    output "output" - 1d tensor, one value per output node in innovation number order
+every node becomes a Gather (inputs) or Mul/aggregation/activation (hidden/output), disabled connections are left out
+median sorts with TopK, the other aggregations are elementwise ops
+ */
+
+const IR_VERSION: i64 = 7;
+const OPSET_VERSION: i64 = 13;
+
+//onnx.proto enums
+const TENSOR_FLOAT: i64 = 1;
+const TENSOR_INT64: i64 = 7;
+const ATTRIBUTE_INT: i64 = 2;
+
+//constants the activation functions need besides "zero", (name, value)
+fn activation_constants(activation: Activation) -> Vec<(&'static str, f32)> {
+    match activation {
+        Activation::Clamped => vec![("one", 1.0), ("minus_one", -1.0)],
+        Activation::SteepenedSigmoid => vec![("steepness", STEEPENED_SIGMOID_SLOPE as f32)],
+        _ => Vec::new(),
+    }
+}
+
+//the op(s) for an activation function, total_name is the weighted sum and output_name is the node's value
+//onnx's LeakyRelu, Elu and Selu defaults match ActivationFunctions (up to f32 precision)
+fn add_activation(graph: &mut ProtoMessage, activation: Activation, node: usize, total_name: &str, output_name: &str) {
+    let step = |name: &str| format!("{}_{}", name, node);
+
+    let single_op = match activation {
+        Activation::Identity => Some("Identity"),
+        Activation::Sigmoid => Some("Sigmoid"),
+        Activation::Relu => Some("Relu"),
+        Activation::Softplus => Some("Softplus"),
+        Activation::Tanh => Some("Tanh"),
+        Activation::Sine => Some("Sin"),
+        Activation::Abs => Some("Abs"),
+        Activation::LeakyRelu => Some("LeakyRelu"),
+        Activation::Elu => Some("Elu"),
+        Activation::Selu => Some("Selu"),
+        _ => None,
+    };
+    if let Some(op_type) = single_op {
+        graph.message(1, &node_proto(op_type, &[total_name], output_name, &[]));
+        return;
+    }
+
+    match activation {
+        Activation::BinaryStep => { //there's no single op
+            graph.message(1, &node_proto("GreaterOrEqual", &[total_name, "zero"], &step("step"), &[]));
+            graph.message(1, &node_proto("Cast", &[&step("step")], output_name, &[("to", TENSOR_FLOAT)]));
+        },
+        Activation::Gaussian => {
+            graph.message(1, &node_proto("Mul", &[total_name, total_name], &step("square"), &[]));
+            graph.message(1, &node_proto("Neg", &[&step("square")], &step("negative"), &[]));
+            graph.message(1, &node_proto("Exp", &[&step("negative")], output_name, &[]));
+        },
+        Activation::Clamped => { //Clip wants scalar min/max, Min/Max work with the [1] constants
+            graph.message(1, &node_proto("Min", &[total_name, "one"], &step("below_one"), &[]));
+            graph.message(1, &node_proto("Max", &[&step("below_one"), "minus_one"], output_name, &[]));
+        },
+        Activation::Square => {
+            graph.message(1, &node_proto("Mul", &[total_name, total_name], output_name, &[]));
+        },
+        Activation::Cube => {
+            graph.message(1, &node_proto("Mul", &[total_name, total_name], &step("square"), &[]));
+            graph.message(1, &node_proto("Mul", &[&step("square"), total_name], output_name, &[]));
+        },
+        Activation::Inverse => { //0 at 0 like ActivationFunctions::inverse
+            graph.message(1, &node_proto("Reciprocal", &[total_name], &step("reciprocal"), &[]));
+            graph.message(1, &node_proto("Equal", &[total_name, "zero"], &step("is_zero"), &[]));
+            graph.message(1, &node_proto("Where", &[&step("is_zero"), "zero", &step("reciprocal")], output_name, &[]));
+        },
+        Activation::SteepenedSigmoid => {
+            graph.message(1, &node_proto("Mul", &[total_name, "steepness"], &step("steepened"), &[]));
+            graph.message(1, &node_proto("Sigmoid", &[&step("steepened")], output_name, &[]));
+        },
+        _ => unreachable!("{} is a single op", activation),
+    }
+}
+
+//the op(s) combining a node's weighted inputs (at least one) into total_name
+fn add_aggregation(graph: &mut ProtoMessage, aggregation: Aggregation, node: usize, products: &[String], total_name: &str) {
+    let step = |name: &str| format!("{}_{}", name, node);
+    let products: Vec<&str> = products.iter().map(String::as_str).collect();
+
+    match aggregation {
+        Aggregation::Sum => { graph.message(1, &node_proto("Sum", &products, total_name, &[])); },
+        Aggregation::Mean => { graph.message(1, &node_proto("Mean", &products, total_name, &[])); },
+        Aggregation::Max => { graph.message(1, &node_proto("Max", &products, total_name, &[])); },
+        Aggregation::Min => { graph.message(1, &node_proto("Min", &products, total_name, &[])); },
+        Aggregation::Product => { //Mul only takes two inputs
+            if products.len() == 1 {
+                graph.message(1, &node_proto("Identity", &products, total_name, &[]));
+                return;
+            }
+
+            let mut partial = String::from(products[0]);
+            for (i, product) in products.iter().enumerate().skip(1) {
+                let output = if i + 1 == products.len() { String::from(total_name) } else { step(&format!("partial_{}", i)) };
+                graph.message(1, &node_proto("Mul", &[&partial, product], &output, &[]));
+                partial = output;
+            }
+        },
+        Aggregation::Median => { //sort descending with TopK, then the mean of the middle one/two
+            let count = products.len();
+            graph.message(5, &int64_tensor(&step("count"), &[count as i64]));
+            graph.message(5, &int64_tensor(&step("middle"), &[((count - 1) / 2) as i64, (count / 2) as i64]));
+
+            graph.message(1, &node_proto("Concat", &products, &step("values"), &[("axis", 0)]));
+            let mut top_k = node_proto("TopK", &[&step("values"), &step("count")], &step("sorted"), &[("axis", 0)]);
+            top_k.string(2, &step("sorted_indices")); //TopK always outputs the indices too
+            graph.message(1, &top_k);
+            graph.message(1, &node_proto("Gather", &[&step("sorted"), &step("middle")], &step("middle_values"), &[("axis", 0)]));
+            graph.message(1, &node_proto("ReduceMean", &[&step("middle_values")], total_name, &[]));
+        },
+        Aggregation::MaxAbs => { //the max unless the min is further from 0, same as Aggregation::apply
+            graph.message(1, &node_proto("Max", &products, &step("max"), &[]));
+            graph.message(1, &node_proto("Min", &products, &step("min"), &[]));
+            graph.message(1, &node_proto("Abs", &[&step("max")], &step("abs_max"), &[]));
+            graph.message(1, &node_proto("Abs", &[&step("min")], &step("abs_min"), &[]));
+            graph.message(1, &node_proto("GreaterOrEqual", &[&step("abs_max"), &step("abs_min")], &step("max_is_further"), &[]));
+            graph.message(1, &node_proto("Where", &[&step("max_is_further"), &step("max"), &step("min")], total_name, &[]));
+        },
+    }
+}
+
+pub fn genome_to_onnx(genome: &Genome, activation: Activation) -> Result<Vec<u8>, String> {
+    let layout = NetworkLayout::new(genome)?;
+    if layout.outputs.is_empty() {
+        return Err(String::from("genome has no output nodes"));
+    }
+
+    let mut graph = ProtoMessage::new();
+    graph.string(2, "genome");
+
+    graph.message(5, &float_tensor("zero", 0.0));
+    for (name, value) in activation_constants(activation) {
+        graph.message(5, &float_tensor(name, value));
+    }
+
+    for (position, node) in layout.inputs.iter().enumerate() {
+        let index_name = format!("index_{}", position);
+        graph.message(5, &int64_tensor(&index_name, &[position as i64]));
+        graph.message(1, &node_proto("Gather", &["input", &index_name], &node_name(*node), &[("axis", 0)]));
+    }
+
+    for node in layout.calculated_nodes() {
+        let mut products = Vec::new();
+
+        for (con_num, from, weight) in layout.get_incoming(*node) {
+            let weight_name = format!("weight_{}", con_num);
+            let product_name = format!("product_{}", con_num);
+            graph.message(5, &float_tensor(&weight_name, *weight as f32));
+            graph.message(1, &node_proto("Mul", &[&node_name(*from), &weight_name], &product_name, &[]));
+            products.push(product_name);
+        }
+
+        //no connections aggregates to 0, same as the calculator
+        let total_name = if products.is_empty() {
+            String::from("zero")
+        } else {
+            let total_name = format!("total_{}", node);
+            add_aggregation(&mut graph, layout.get_aggregation(*node), *node, &products, &total_name);
+            total_name
+        };
+
+        add_activation(&mut graph, activation, *node, &total_name, &node_name(*node));
+    }
+
+    let output_names: Vec<String> = layout.outputs.iter().map(|node| node_name(*node)).collect();
+    let output_refs: Vec<&str> = output_names.iter().map(String::as_str).collect();
+    graph.message(1, &node_proto("Concat", &output_refs, "output", &[("axis", 0)]));
+
+    graph.message(11, &value_info("input", layout.inputs.len()));
+    graph.message(12, &value_info("output", layout.outputs.len()));
+
+    let mut opset = ProtoMessage::new();
+    opset.string(1, "").int(2, OPSET_VERSION);
+
+    let mut model = ProtoMessage::new();
+    model.int(1, IR_VERSION)
+        .string(2, "rust_machine_learning_library")
+        .string(3, env!("CARGO_PKG_VERSION"))
+        .message(7, &graph)
+        .message(8, &opset);
+
+    Ok(model.into_bytes())
+}
+
+pub fn save_genome_as_onnx<P: AsRef<Path>>(genome: &Genome, activation: Activation, path: P) -> io::Result<()> {
+    let bytes = genome_to_onnx(genome, activation).map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+    fs::write(path, bytes)
+}
+
+fn node_name(node: usize) -> String {
+    format!("node_{}", node)
+}
+
+//NodeProto, attributes are all ints
+fn node_proto(op_type: &str, inputs: &[&str], output: &str, attributes: &[(&str, i64)]) -> ProtoMessage {
+    let mut node = ProtoMessage::new();
+
+    for input in inputs {
+        node.string(1, input);
+    }
+    node.string(2, output);
+    node.string(3, output); //name the node after what it makes
+    node.string(4, op_type);
+
+    for (name, value) in attributes {
+        let mut attribute = ProtoMessage::new();
+        attribute.string(1, name).int(3, *value).int(20, ATTRIBUTE_INT);
+        node.message(5, &attribute);
+    }
+
+    node
+}
+
+//TensorProto holding a single value, shape [1]
+fn float_tensor(name: &str, value: f32) -> ProtoMessage {
+    let mut tensor = ProtoMessage::new();
+    tensor.int(1, 1).int(2, TENSOR_FLOAT).packed_floats(4, &[value]).string(8, name);
+    tensor
+}
+
+//1d TensorProto of int64s
+fn int64_tensor(name: &str, values: &[i64]) -> ProtoMessage {
+    let mut tensor = ProtoMessage::new();
+    tensor.int(1, values.len() as i64).int(2, TENSOR_INT64).packed_ints(7, values).string(8, name);
+    tensor
+}
+
+//ValueInfoProto for a 1d float tensor
+fn value_info(name: &str, size: usize) -> ProtoMessage {
+    let mut dimension = ProtoMessage::new();
+    dimension.int(1, size as i64);
+
+    let mut shape = ProtoMessage::new();
+    shape.message(1, &dimension);
+
+    let mut tensor_type = ProtoMessage::new();
+    tensor_type.int(1, TENSOR_FLOAT).message(2, &shape);
+
+    let mut type_proto = ProtoMessage::new();
+    type_proto.message(1, &tensor_type);
+
+    let mut value_info = ProtoMessage::new();
+    value_info.string(1, name).message(2, &type_proto);
+    value_info
+}
@@ -0,0 +1,98 @@
+/* - ProtoMessage -
+Just enough protobuf encoding to write an onnx model, no dependencies
+    https://developers.google.com/protocol-buffers/docs/encoding
+ */
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_LENGTH_DELIMITED: u64 = 2;
+
+#[derive(Debug, Default)]
+pub(crate) struct ProtoMessage {
+    bytes: Vec<u8>,
+}
+
+impl ProtoMessage {
+    pub(crate) fn new() -> Self {
+        ProtoMessage {bytes: Vec::new()}
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub(crate) fn varint(&mut self, field: u64, value: u64) -> &mut Self {
+        self.key(field, WIRE_VARINT);
+        write_varint(&mut self.bytes, value);
+        self
+    }
+
+    //int64/int32 fields, negative numbers take 10 bytes like protobuf does
+    pub(crate) fn int(&mut self, field: u64, value: i64) -> &mut Self {
+        self.varint(field, value as u64)
+    }
+
+    pub(crate) fn bytes(&mut self, field: u64, data: &[u8]) -> &mut Self {
+        self.key(field, WIRE_LENGTH_DELIMITED);
+        write_varint(&mut self.bytes, data.len() as u64);
+        self.bytes.extend_from_slice(data);
+        self
+    }
+
+    pub(crate) fn string(&mut self, field: u64, text: &str) -> &mut Self {
+        self.bytes(field, text.as_bytes())
+    }
+
+    pub(crate) fn message(&mut self, field: u64, message: &ProtoMessage) -> &mut Self {
+        self.bytes(field, &message.bytes)
+    }
+
+    pub(crate) fn packed_floats(&mut self, field: u64, values: &[f32]) -> &mut Self {
+        let data: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+        self.bytes(field, &data)
+    }
+
+    pub(crate) fn packed_ints(&mut self, field: u64, values: &[i64]) -> &mut Self {
+        let mut data = Vec::new();
+        for value in values {
+            write_varint(&mut data, *value as u64);
+        }
+        self.bytes(field, &data)
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        write_varint(&mut self.bytes, (field << 3) | wire_type);
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_like_protobuf() {
+        //examples from the protobuf encoding docs
+        let mut message = ProtoMessage::new();
+        message.varint(1, 150);
+        assert_eq!(message.into_bytes(), vec![0x08, 0x96, 0x01]);
+
+        let mut message = ProtoMessage::new();
+        message.string(2, "testing");
+        assert_eq!(message.into_bytes(), vec![0x12, 0x07, 0x74, 0x65, 0x73, 0x74, 0x69, 0x6e, 0x67]);
+
+        let mut message = ProtoMessage::new();
+        message.packed_ints(4, &[3, 270, 86942]);
+        assert_eq!(message.into_bytes(), vec![0x22, 0x06, 0x03, 0x8E, 0x02, 0x9E, 0xA7, 0x05]);
+
+        let mut message = ProtoMessage::new();
+        message.int(1, -1);
+        assert_eq!(message.into_bytes().len(), 11, "negative ints are 10 byte varints");
+    }
+}
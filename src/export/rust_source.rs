@@ -0,0 +1,135 @@
+use crate::feed_forward::layout::NetworkLayout;
+use crate::activation_functions::{Activation, LEAKY_RELU_SLOPE, SELU_ALPHA, SELU_SCALE, STEEPENED_SIGMOID_SLOPE};
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::genome::Genome;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/* - Rust source export -
+Writes a genome as a self contained rust function, no dependency on this crate
+    pub fn network(inputs: &[f64; N]) -> [f64; M]
+inputs/outputs are in the same order as Calculator::run (innovation number order, so for a Neat genome
+the first input is the bias and should be 1.0)
+every weighted sum is unrolled, disabled connections and hidden nodes that can't reach an output are left out
+sum/product/mean aggregations are unrolled too, the others call a helper function that's only written if it's used
+most activation functions use f64 methods from std (exp, ln, sin...), the piecewise linear ones work in no_std
+ */
+
+//the body of `fn activation(x: f64) -> f64`, same as the ActivationFunctions
+fn activation_body(activation: Activation) -> String {
+    match activation {
+        Activation::Identity => String::from("x"),
+        Activation::BinaryStep => String::from("if x >= 0.0 { 1.0 } else { 0.0 }"),
+        Activation::Sigmoid => String::from("1.0 / (1.0 + (-x).exp())"),
+        Activation::Relu => String::from("if x > 0.0 { x } else { 0.0 }"),
+        Activation::Softplus => String::from("(1.0 + x.exp()).ln()"),
+        Activation::Tanh => String::from("x.tanh()"),
+        Activation::Gaussian => String::from("(-x * x).exp()"),
+        Activation::Sine => String::from("x.sin()"),
+        Activation::Abs => String::from("x.abs()"),
+        Activation::LeakyRelu => format!("if x > 0.0 {{ x }} else {{ {:?} * x }}", LEAKY_RELU_SLOPE),
+        Activation::Elu => String::from("if x > 0.0 { x } else { x.exp() - 1.0 }"),
+        Activation::Selu => format!("if x > 0.0 {{ {:?} * x }} else {{ {:?} * (x.exp() - 1.0) }}", SELU_SCALE, SELU_SCALE * SELU_ALPHA),
+        Activation::Clamped => String::from("x.clamp(-1.0, 1.0)"),
+        Activation::Square => String::from("x * x"),
+        Activation::Cube => String::from("x * x * x"),
+        Activation::Inverse => String::from("if x == 0.0 { 0.0 } else { 1.0 / x }"),
+        Activation::SteepenedSigmoid => format!("1.0 / (1.0 + (-{:?} * x).exp())", STEEPENED_SIGMOID_SLOPE),
+    }
+}
+
+//a helper function for the aggregations that aren't written inline, same as Aggregation::apply
+fn aggregation_helper(aggregation: Aggregation) -> Option<&'static str> {
+    match aggregation {
+        Aggregation::Sum | Aggregation::Product | Aggregation::Mean => None,
+        Aggregation::Max => Some("fn aggregate_max(values: &[f64]) -> f64 {\n    values.iter().copied().fold(values[0], |max, value| if value > max { value } else { max })\n}\n"),
+        Aggregation::Min => Some("fn aggregate_min(values: &[f64]) -> f64 {\n    values.iter().copied().fold(values[0], |min, value| if value < min { value } else { min })\n}\n"),
+        Aggregation::Median => Some("fn aggregate_median(values: &mut [f64]) -> f64 {\n    values.sort_unstable_by(|a, b| a.total_cmp(b));\n    (values[(values.len() - 1) / 2] + values[values.len() / 2]) / 2.0\n}\n"),
+        Aggregation::MaxAbs => Some("fn aggregate_max_abs(values: &[f64]) -> f64 {\n    let max = values.iter().copied().fold(values[0], |max, value| if value > max { value } else { max });\n    let min = values.iter().copied().fold(values[0], |min, value| if value < min { value } else { min });\n    if max.abs() >= min.abs() { max } else { min }\n}\n"),
+    }
+}
+
+//the expression combining a node's weighted inputs
+fn aggregate(aggregation: Aggregation, terms: &[String]) -> String {
+    match (aggregation, terms.len()) {
+        (_, 0) => String::from("0.0"), //no connections aggregates to 0, same as the calculator
+        (_, 1) => terms[0].clone(),
+        (Aggregation::Sum, _) => terms.join(" + "),
+        (Aggregation::Product, _) => terms.join(" * "),
+        (Aggregation::Mean, _) => format!("({}) / {:?}", terms.join(" + "), terms.len() as f64),
+        (Aggregation::Median, _) => format!("aggregate_median(&mut [{}])", terms.join(", ")),
+        (aggregation, _) => format!("aggregate_{}(&[{}])", aggregation.name(), terms.join(", ")),
+    }
+}
+
+pub fn genome_to_rust(genome: &Genome, activation: Activation) -> Result<String, String> {
+    let layout = NetworkLayout::new(genome)?;
+
+    let input_position = |node: usize| layout.inputs.iter().position(|input| *input == node);
+
+    let needed = layout.needed_nodes();
+    let mut helpers_used = Vec::new();
+
+    let mut source = String::new();
+    source.push_str(&format!("// generated by rust_machine_learning_library {} from a genome with {} nodes and {} connections\n",
+                             env!("CARGO_PKG_VERSION"), genome.nodes.len(), genome.connections.len()));
+    source.push_str(&format!("// inputs in node order {:?}, outputs in node order {:?}\n", layout.inputs, layout.outputs));
+    source.push_str(&format!("pub fn network(inputs: &[f64; {}]) -> [f64; {}] {{\n", layout.inputs.len(), layout.outputs.len()));
+
+    if !layout.inputs.iter().any(|input| needed.contains(input)) {
+        source.push_str("    let _ = inputs; //no enabled connection reaches an output from an input\n");
+    }
+
+    for node in layout.calculated_nodes() {
+        if !needed.contains(node) { continue; }
+
+        let terms: Vec<String> = layout.get_incoming(*node).iter().map(|(_con_num, from, weight)| {
+            let value = match input_position(*from) {
+                Some(position) => format!("inputs[{}]", position),
+                None => format!("node_{}", from),
+            };
+            format!("{} * {}", float_literal(*weight), value)
+        }).collect();
+
+        let aggregation = layout.get_aggregation(*node);
+        if terms.len() > 1 && !helpers_used.contains(&aggregation) {
+            helpers_used.push(aggregation);
+        }
+        source.push_str(&format!("    let node_{} = activation({});\n", node, aggregate(aggregation, &terms)));
+    }
+
+    let outputs: Vec<String> = layout.outputs.iter().map(|node| format!("node_{}", node)).collect();
+    source.push_str(&format!("    [{}]\n", outputs.join(", ")));
+    source.push_str("}\n\n");
+
+    source.push_str("#[inline]\n");
+    source.push_str(&format!("fn activation(x: f64) -> f64 {{\n    {}\n}}\n", activation_body(activation)));
+
+    for aggregation in Aggregation::ALL.iter() {
+        if let (true, Some(helper)) = (helpers_used.contains(aggregation), aggregation_helper(*aggregation)) {
+            source.push('\n');
+            source.push_str(helper);
+        }
+    }
+
+    Ok(source)
+}
+
+pub fn save_genome_as_rust<P: AsRef<Path>>(genome: &Genome, activation: Activation, path: P) -> io::Result<()> {
+    let source = genome_to_rust(genome, activation).map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+    fs::write(path, source)
+}
+
+//debug formatting gives the shortest literal that parses back to the same f64
+fn float_literal(value: f64) -> String {
+    if value.is_nan() {
+        String::from("f64::NAN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "f64::INFINITY" } else { "f64::NEG_INFINITY" })
+    } else if value < 0.0 {
+        format!("({:?})", value)
+    } else {
+        format!("{:?}", value)
+    }
+}
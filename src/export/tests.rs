@@ -0,0 +1,446 @@
+use super::onnx::genome_to_onnx;
+use super::rust_source::genome_to_rust;
+use super::c_source::genome_to_c;
+use crate::activation_functions::Activation;
+use crate::feed_forward::calculator::Calculator;
+use crate::feed_forward::connection_gene::ConnectionGene;
+use crate::feed_forward::genome::Genome;
+use crate::feed_forward::node_gene::NodeGene;
+use crate::feed_forward::tests::{get_testing_genome_0, get_testing_genome_2, get_aggregation_testing_genome};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::rc::Rc;
+
+//testing genome 0 plus a disabled connection and a hidden node nothing connects into
+pub(crate) fn get_export_testing_genome() -> Genome {
+    let mut genome = get_testing_genome_0();
+    genome.add_node(Rc::new(NodeGene::new(7, 0.3, 0.5)));
+
+    let mut disabled = ConnectionGene::new(6, Rc::clone(&genome.nodes[&1]), Rc::clone(&genome.nodes[&5]));
+    disabled.weight = 100.0;
+    disabled.enabled = false;
+    genome.add_connection(disabled);
+
+    let mut from_unconnected = ConnectionGene::new(7, Rc::clone(&genome.nodes[&7]), Rc::clone(&genome.nodes[&6]));
+    from_unconnected.weight = -0.3;
+    genome.add_connection(from_unconnected);
+
+    genome.connections.get_mut(&1).unwrap().weight = -1.25;
+
+    genome
+}
+
+pub(crate) const EXPORT_TEST_INPUTS: [[f64; 2]; 4] = [[1.0, 1.0], [0.0, 0.5], [-2.0, 0.25], [3.5, -1.0]];
+
+//fresh scratch directory for compiling generated code
+pub(crate) fn get_build_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rust_machine_learning_library_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+//checks a compiled program's output lines, space separated outputs per set of inputs
+//calculator_inputs are what Calculator::run gets for each line
+pub(crate) fn assert_outputs_match(name: &str, lines: &[&str], genome: &Genome, activation: Activation,
+                                   calculator_inputs: &[Vec<f64>], tolerance: f64) {
+    let calculator = Calculator::new(genome, activation.function());
+    assert_eq!(lines.len(), calculator_inputs.len());
+
+    for (line, inputs) in lines.iter().zip(calculator_inputs) {
+        let expected = calculator.run(inputs);
+        let output: Vec<f64> = line.split_whitespace().map(|value| value.parse().unwrap()).collect();
+
+        assert_eq!(output.len(), expected.len());
+        for (output, expected) in output.iter().zip(&expected) {
+            assert!((output - expected).abs() <= tolerance * expected.abs().max(1.0),
+                    "{} with inputs {:?}: generated code gave {}, calculator gave {}", name, inputs, output, expected);
+        }
+    }
+}
+
+/* - protobuf decoding -
+just enough to read back what the exporter writes
+ */
+
+#[derive(Debug, Clone)]
+enum ProtoValue {
+    Varint(u64),
+    Bytes(Vec<u8>),
+    Fixed32, //the exporter never writes these, skipped
+}
+
+struct ProtoFields(Vec<(u64, ProtoValue)>);
+
+impl ProtoFields {
+    fn parse(bytes: &[u8]) -> ProtoFields {
+        let mut fields = Vec::new();
+        let mut position = 0;
+
+        while position < bytes.len() {
+            let key = read_varint(bytes, &mut position);
+            let value = match key & 7 {
+                0 => ProtoValue::Varint(read_varint(bytes, &mut position)),
+                2 => {
+                    let length = read_varint(bytes, &mut position) as usize;
+                    position += length;
+                    ProtoValue::Bytes(bytes[position - length..position].to_vec())
+                },
+                5 => {
+                    position += 4;
+                    ProtoValue::Fixed32
+                },
+                wire_type => panic!("unexpected wire type {}", wire_type),
+            };
+            fields.push((key >> 3, value));
+        }
+
+        ProtoFields(fields)
+    }
+
+    fn all(&self, field: u64) -> Vec<&ProtoValue> {
+        self.0.iter().filter(|(number, _value)| *number == field).map(|(_number, value)| value).collect()
+    }
+
+    fn int(&self, field: u64) -> Option<i64> {
+        self.all(field).first().map(|value| match value {
+            ProtoValue::Varint(value) => *value as i64,
+            _ => panic!("field {} isn't a varint", field),
+        })
+    }
+
+    fn string(&self, field: u64) -> Option<String> {
+        self.strings(field).into_iter().next()
+    }
+
+    fn strings(&self, field: u64) -> Vec<String> {
+        self.all(field).into_iter().map(|value| match value {
+            ProtoValue::Bytes(bytes) => String::from_utf8(bytes.clone()).unwrap(),
+            _ => panic!("field {} isn't a string", field),
+        }).collect()
+    }
+
+    fn messages(&self, field: u64) -> Vec<ProtoFields> {
+        self.all(field).into_iter().map(|value| match value {
+            ProtoValue::Bytes(bytes) => ProtoFields::parse(bytes),
+            _ => panic!("field {} isn't a message", field),
+        }).collect()
+    }
+
+    fn message(&self, field: u64) -> ProtoFields {
+        self.messages(field).into_iter().next().unwrap_or_else(|| panic!("missing field {}", field))
+    }
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*position];
+        *position += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 { return value; }
+        shift += 7;
+    }
+}
+
+//TensorProto -> values, everything becomes f64
+fn tensor_values(tensor: &ProtoFields) -> Vec<f64> {
+    match tensor.int(2) {
+        Some(1) => { //float, packed
+            let bytes = match tensor.all(4).first() {
+                Some(ProtoValue::Bytes(bytes)) => bytes.clone(),
+                other => panic!("expected packed float_data, got {:?}", other),
+            };
+            bytes.chunks(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64).collect()
+        },
+        Some(7) => { //int64, packed
+            let bytes = match tensor.all(7).first() {
+                Some(ProtoValue::Bytes(bytes)) => bytes.clone(),
+                other => panic!("expected packed int64_data, got {:?}", other),
+            };
+            let mut position = 0;
+            let mut values = Vec::new();
+            while position < bytes.len() {
+                values.push(read_varint(&bytes, &mut position) as i64 as f64);
+            }
+            values
+        },
+        data_type => panic!("unexpected tensor type {:?}", data_type),
+    }
+}
+
+//runs an onnx model with 1d tensors, only the ops the exporter uses
+fn run_onnx(model_bytes: &[u8], inputs: &[f64]) -> Vec<f64> {
+    let model = ProtoFields::parse(model_bytes);
+    assert_eq!(model.int(1), Some(7), "ir version");
+    assert_eq!(model.message(8).int(2), Some(13), "opset version");
+
+    let graph = model.message(7);
+    let mut values: HashMap<String, Vec<f64>> = HashMap::new();
+
+    for tensor in graph.messages(5) {
+        let name = tensor.string(8).unwrap();
+        assert_eq!(tensor.all(1).len(), 1, "initializers are 1d");
+        assert!(values.insert(name, tensor_values(&tensor)).is_none(), "initializer names are unique");
+    }
+
+    let graph_input = graph.message(11);
+    assert_eq!(graph_input.string(1).unwrap(), "input");
+    let input_size = graph_input.message(2).message(1).message(2).message(1).int(1).unwrap();
+    assert_eq!(input_size as usize, inputs.len());
+    values.insert(String::from("input"), inputs.iter().map(|input| *input as f32 as f64).collect());
+
+    for node in graph.messages(1) {
+        let op_type = node.string(4).unwrap();
+        let output = node.string(2).unwrap();
+        let attributes: HashMap<String, i64> = node.messages(5).iter()
+            .map(|attribute| (attribute.string(1).unwrap(), attribute.int(3).unwrap())).collect();
+        //nodes have to be in topological order, every input already exists
+        let node_inputs: Vec<&Vec<f64>> = node.strings(1).iter()
+            .map(|name| values.get(name).unwrap_or_else(|| panic!("{} used before it's made", name))).collect();
+
+        let single = |function: fn(f64) -> f64| -> Vec<f64> {
+            node_inputs[0].iter().map(|value| function(*value) as f32 as f64).collect()
+        };
+
+        let result = match op_type.as_str() {
+            "Gather" => {
+                assert_eq!(attributes["axis"], 0);
+                node_inputs[1].iter().map(|index| node_inputs[0][*index as usize]).collect()
+            },
+            "Mul" => vec![(node_inputs[0][0] as f32 * node_inputs[1][0] as f32) as f64],
+            "Sum" => vec![node_inputs.iter().fold(0.0_f32, |total, value| total + value[0] as f32) as f64],
+            "Identity" => node_inputs[0].clone(),
+            //formulas from the onnx operator docs, not ActivationFunctions, so this checks the mapping
+            "Sigmoid" => single(|x| 1.0 / (1.0 + (-x).exp())),
+            "Tanh" => single(|x| (x.exp() - (-x).exp()) / (x.exp() + (-x).exp())),
+            "Relu" => single(|x| x.max(0.0)),
+            "Softplus" => single(|x| x.exp().ln_1p()),
+            "Sin" => single(f64::sin),
+            "Abs" => single(f64::abs),
+            "LeakyRelu" => single(|x| if x >= 0.0 { x } else { 0.01 * x }),
+            "Elu" => single(|x| if x >= 0.0 { x } else { x.exp() - 1.0 }),
+            "Selu" => single(|x| if x > 0.0 { 1.050_701_022_148_132_3 * x } else { 1.050_701_022_148_132_3 * (1.673_263_192_176_818_8 * x.exp() - 1.673_263_192_176_818_8) }),
+            "Neg" => single(|x| -x),
+            "Exp" => single(f64::exp),
+            "Reciprocal" => single(|x| 1.0 / x),
+            "Min" => vec![node_inputs.iter().map(|value| value[0]).fold(f64::INFINITY, f64::min)],
+            "Max" => vec![node_inputs.iter().map(|value| value[0]).fold(f64::NEG_INFINITY, f64::max)],
+            "Mean" => vec![(node_inputs.iter().fold(0.0_f32, |total, value| total + value[0] as f32) / node_inputs.len() as f32) as f64],
+            "TopK" => { //largest and sorted are on by default
+                assert_eq!(attributes["axis"], 0);
+                let mut sorted = node_inputs[0].clone();
+                sorted.sort_by(|a, b| b.partial_cmp(a).unwrap());
+                sorted.truncate(node_inputs[1][0] as usize);
+                sorted
+            },
+            "ReduceMean" => vec![(node_inputs[0].iter().map(|value| *value as f32).sum::<f32>() / node_inputs[0].len() as f32) as f64],
+            "Equal" => vec![if node_inputs[0][0] == node_inputs[1][0] { 1.0 } else { 0.0 }],
+            "Where" => vec![if node_inputs[0][0] != 0.0 { node_inputs[1][0] } else { node_inputs[2][0] }],
+            "GreaterOrEqual" => vec![if node_inputs[0][0] >= node_inputs[1][0] { 1.0 } else { 0.0 }],
+            "Cast" => {
+                assert_eq!(attributes["to"], 1, "cast to float");
+                node_inputs[0].clone()
+            },
+            "Concat" => {
+                assert_eq!(attributes["axis"], 0);
+                node_inputs.iter().flat_map(|value| value.iter().copied()).collect()
+            },
+            op_type => panic!("unexpected op {}", op_type),
+        };
+
+        assert!(values.insert(output.clone(), result).is_none(), "{} made twice", output);
+    }
+
+    assert_eq!(graph.message(12).string(1).unwrap(), "output");
+    values.remove("output").expect("graph makes its output")
+}
+
+#[test]
+fn test_onnx_matches_calculator() {
+    for genome in [get_export_testing_genome(), get_testing_genome_2(), get_aggregation_testing_genome()].iter() {
+        for activation in Activation::ALL.iter() {
+            let model = genome_to_onnx(genome, *activation).unwrap();
+            let calculator = Calculator::new(genome, activation.function());
+
+            for inputs in EXPORT_TEST_INPUTS.iter() {
+                let expected = calculator.run(&inputs.to_vec());
+                let output = run_onnx(&model, inputs);
+
+                assert_eq!(output.len(), expected.len());
+                for (output, expected) in output.iter().zip(&expected) {
+                    assert!((output - expected).abs() < 1e-4 * expected.abs().max(1.0),
+                            "{} with inputs {:?}: onnx gave {}, calculator gave {}", activation, inputs, output, expected);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_onnx_skips_disabled_connections() {
+    let model = ProtoFields::parse(&genome_to_onnx(&get_export_testing_genome(), Activation::Identity).unwrap());
+    let initializer_names: Vec<String> = model.message(7).messages(5).iter().map(|tensor| tensor.string(8).unwrap()).collect();
+
+    assert!(initializer_names.contains(&String::from("weight_0")));
+    assert!(!initializer_names.contains(&String::from("weight_6")), "connection 6 is disabled");
+}
+
+#[test]
+fn test_onnx_rejects_invalid_genomes() {
+    let mut genome = get_testing_genome_0();
+    let backwards = ConnectionGene::new(6, Rc::clone(&genome.nodes[&2]), Rc::clone(&genome.nodes[&6]));
+    genome.add_connection(backwards);
+    assert!(genome_to_onnx(&genome, Activation::Identity).is_err(), "invalid genome");
+}
+
+//the genomes the generated code tests compile, every connection disabled in the third one
+fn get_compiled_testing_genomes() -> Vec<Genome> {
+    let mut all_disabled = get_testing_genome_0();
+    for connection in all_disabled.connections.values_mut() {
+        connection.enabled = false;
+    }
+
+    vec![get_export_testing_genome(), get_testing_genome_2(), all_disabled, get_aggregation_testing_genome()]
+}
+
+#[test]
+fn test_rust_source_signature() {
+    let genome = get_export_testing_genome();
+    let source = genome_to_rust(&genome, Activation::Sigmoid).unwrap();
+
+    assert!(source.contains("pub fn network(inputs: &[f64; 2]) -> [f64; 4] {"), "{}", source);
+    assert!(source.contains("(-1.25) * inputs[1]"), "{}", source);
+    assert!(!source.contains("100.0"), "connection 6 is disabled:\n{}", source);
+    assert!(!source.contains("use "), "generated code shouldn't depend on anything:\n{}", source);
+}
+
+#[test]
+fn test_rust_source_compiles_and_matches_calculator() {
+    let dir = get_build_dir("rust_source");
+
+    for (genome_i, genome) in get_compiled_testing_genomes().iter().enumerate() {
+        //one program per genome, every activation function's network in its own module
+        let mut program = String::new();
+        let mut main = String::from("fn main() {\n");
+        for activation in Activation::ALL.iter() {
+            program.push_str(&format!("mod {} {{\n{}}}\n\n", activation.name(), genome_to_rust(genome, *activation).unwrap()));
+            for inputs in EXPORT_TEST_INPUTS.iter() {
+                main.push_str(&format!("    let outputs = {}::network(&{:?});\n", activation.name(), inputs));
+                main.push_str("    println!(\"{}\", outputs.iter().map(|output| format!(\"{:?}\", output)).collect::<Vec<_>>().join(\" \"));\n");
+            }
+        }
+        program.push_str(&main);
+        program.push_str("}\n");
+
+        let source_path = dir.join(format!("networks_{}.rs", genome_i));
+        let binary_path = dir.join(format!("networks_{}", genome_i));
+        fs::write(&source_path, &program).unwrap();
+
+        //-D warnings so unused nodes or needless parentheses in the generated code fail the test
+        let compiled = Command::new("rustc").arg("-D").arg("warnings").arg("-o").arg(&binary_path).arg(&source_path)
+            .output().expect("rustc should be available when running tests");
+        assert!(compiled.status.success(), "generated code didn't compile:\n{}\n{}", program, String::from_utf8_lossy(&compiled.stderr));
+
+        let run = Command::new(&binary_path).output().unwrap();
+        assert!(run.status.success());
+        let output = String::from_utf8(run.stdout).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), Activation::ALL.len() * EXPORT_TEST_INPUTS.len());
+
+        let calculator_inputs: Vec<Vec<f64>> = EXPORT_TEST_INPUTS.iter().map(|inputs| inputs.to_vec()).collect();
+        for (activation, lines) in Activation::ALL.iter().zip(lines.chunks(EXPORT_TEST_INPUTS.len())) {
+            assert_outputs_match(activation.name(), lines, genome, *activation, &calculator_inputs, 1e-9);
+        }
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_c_source_header() {
+    let genome = get_export_testing_genome();
+
+    let (header, source) = genome_to_c(&genome, Activation::Tanh, "controller", true).unwrap();
+    assert!(header.contains("#define CONTROLLER_INPUTS 1\n"), "bias isn't an input:\n{}", header);
+    assert!(header.contains("#define CONTROLLER_OUTPUTS 4\n"), "{}", header);
+    assert!(header.contains("void controller(const double inputs[CONTROLLER_INPUTS], double outputs[CONTROLLER_OUTPUTS]);"), "{}", header);
+    assert!(source.contains("#include \"controller.h\""), "{}", source);
+    assert!(!source.contains("1e2"), "connection 6 is disabled:\n{}", source);
+
+    let (header, _source) = genome_to_c(&genome, Activation::Tanh, "controller", false).unwrap();
+    assert!(header.contains("#define CONTROLLER_INPUTS 2\n"), "{}", header);
+
+    assert!(genome_to_c(&genome, Activation::Tanh, "2controller", true).is_err());
+    assert!(genome_to_c(&genome, Activation::Tanh, "control-ler", true).is_err());
+}
+
+#[test]
+fn test_c_source_compiles_and_matches_calculator() {
+    let dir = get_build_dir("c_source");
+    let compiler = std::env::var("CC").unwrap_or_else(|_| String::from("cc"));
+
+    for (genome_i, genome) in get_compiled_testing_genomes().iter().enumerate() {
+        for with_bias in [false, true].iter() {
+            //with a bias the program leaves out the first input and the calculator gets 1.0 there instead
+            let first_input = if *with_bias { 1 } else { 0 };
+            let calculator_inputs: Vec<Vec<f64>> = EXPORT_TEST_INPUTS.iter().map(|inputs| {
+                let mut inputs = inputs.to_vec();
+                if *with_bias { inputs[0] = 1.0; }
+                inputs
+            }).collect();
+
+            //one program per genome, a .c/.h pair per activation function
+            let program_name = format!("networks_{}_{}", genome_i, if *with_bias { "bias" } else { "no_bias" });
+            let mut includes = String::from("#include <stdio.h>\n");
+            let mut main = String::from("int main(void) {\n    int i;\n");
+            let mut sources = Vec::new();
+
+            for activation in Activation::ALL.iter() {
+                let network_name = format!("{}_{}", program_name, activation.name());
+                let (header, source) = genome_to_c(genome, *activation, &network_name, *with_bias).unwrap();
+                fs::write(dir.join(format!("{}.h", network_name)), &header).unwrap();
+                fs::write(dir.join(format!("{}.c", network_name)), &source).unwrap();
+                sources.push(dir.join(format!("{}.c", network_name)));
+
+                includes.push_str(&format!("#include \"{}.h\"\n", network_name));
+                main.push_str(&format!("    {{\n        double outputs[{}_OUTPUTS];\n", network_name.to_uppercase()));
+                for inputs in EXPORT_TEST_INPUTS.iter() {
+                    let inputs: Vec<String> = inputs[first_input..].iter().map(|input| format!("{:e}", input)).collect();
+                    main.push_str("        {\n");
+                    main.push_str(&format!("            const double inputs[] = {{{}}};\n", inputs.join(", ")));
+                    main.push_str(&format!("            {}(inputs, outputs);\n", network_name));
+                    main.push_str(&format!("            for (i = 0; i < {}_OUTPUTS; i++) printf(\"%.17g \", outputs[i]);\n", network_name.to_uppercase()));
+                    main.push_str("            printf(\"\\n\");\n        }\n");
+                }
+                main.push_str("    }\n");
+            }
+            main.push_str("    return 0;\n}\n");
+
+            let main_path = dir.join(format!("{}_main.c", program_name));
+            fs::write(&main_path, format!("{}\n{}", includes, main)).unwrap();
+
+            let binary_path = dir.join(&program_name);
+            let compiled = Command::new(&compiler)
+                .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-pedantic", "-o"]).arg(&binary_path)
+                .args(&sources).arg(&main_path).arg("-lm")
+                .output().unwrap_or_else(|error| panic!("couldn't run the C compiler '{}' (set CC to pick another): {}", compiler, error));
+            assert!(compiled.status.success(), "generated code didn't compile:\n{}", String::from_utf8_lossy(&compiled.stderr));
+
+            let run = Command::new(&binary_path).output().unwrap();
+            assert!(run.status.success());
+            let output = String::from_utf8(run.stdout).unwrap();
+            let lines: Vec<&str> = output.lines().collect();
+            assert_eq!(lines.len(), Activation::ALL.len() * EXPORT_TEST_INPUTS.len());
+
+            for (activation, lines) in Activation::ALL.iter().zip(lines.chunks(EXPORT_TEST_INPUTS.len())) {
+                assert_outputs_match(&format!("{} {}", program_name, activation), lines, genome, *activation, &calculator_inputs, 1e-12);
+            }
+        }
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,317 @@
+use super::genome::Genome;
+use super::layout::NetworkLayout;
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+/* - Backprop -
+Gradient descent on the weights of a genome, the topology stays fixed
+    Backprop::new(&genome, activation, with_bias) - a copy of the genome's enabled connections
+    train(dataset, settings) - fine tunes the copy's weights, returns the loss after every epoch
+    write_weights(&mut genome) - puts the tuned weights back into the genome (lamarckian evolution)
+inputs/outputs work like Calculator::run, with_bias works like the C export - the first input node is fixed at 1.0
+and left out of the inputs, same as Neat::use_client
+a dataset is a list of (inputs, targets)
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loss {
+    MeanSquaredError,
+    BinaryCrossEntropy, //targets and outputs should be in 0..1, eg. sigmoid outputs
+}
+
+impl Loss {
+    //outputs are clamped this far from 0 and 1 for cross entropy so the log doesn't blow up
+    const EPSILON: f64 = 1e-12;
+
+    pub fn value(&self, outputs: &[f64], targets: &[f64]) -> f64 {
+        let total: f64 = outputs.iter().zip(targets).map(|(output, target)| match self {
+            Loss::MeanSquaredError => (output - target).powi(2),
+            Loss::BinaryCrossEntropy => {
+                let output = output.clamp(Self::EPSILON, 1.0 - Self::EPSILON);
+                -(target * output.ln() + (1.0 - target) * (1.0 - output).ln())
+            },
+        }).sum();
+
+        total / outputs.len() as f64
+    }
+
+    //d value / d output
+    fn derivative(&self, output: f64, target: f64, number_of_outputs: usize) -> f64 {
+        let derivative = match self {
+            Loss::MeanSquaredError => 2.0 * (output - target),
+            Loss::BinaryCrossEntropy => {
+                let output = output.clamp(Self::EPSILON, 1.0 - Self::EPSILON);
+                (output - target) / (output * (1.0 - output))
+            },
+        };
+
+        derivative / number_of_outputs as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Optimizer {
+    Sgd {learning_rate: f64, momentum: f64},
+    Adam {learning_rate: f64, beta1: f64, beta2: f64, epsilon: f64},
+}
+
+impl Optimizer {
+    pub fn sgd(learning_rate: f64) -> Self {
+        Optimizer::Sgd {learning_rate, momentum: 0.0}
+    }
+
+    //with the defaults from the adam paper
+    pub fn adam(learning_rate: f64) -> Self {
+        Optimizer::Adam {learning_rate, beta1: 0.9, beta2: 0.999, epsilon: 1e-8}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FineTuneSettings {
+    pub loss: Loss,
+    pub optimizer: Optimizer,
+    pub epochs: usize,
+    pub batch_size: usize, //0 for the whole dataset in one batch
+    pub shuffle: bool, //shuffle the dataset every epoch, only matters with batches
+}
+
+impl Default for FineTuneSettings {
+    fn default() -> Self {
+        FineTuneSettings {
+            loss: Loss::MeanSquaredError,
+            optimizer: Optimizer::adam(0.01),
+            epochs: 100,
+            batch_size: 0,
+            shuffle: true,
+        }
+    }
+}
+
+//momentum for sgd, first/second moments for adam
+struct OptimizerState {
+    first_moment: Vec<f64>,
+    second_moment: Vec<f64>,
+    steps: i32,
+}
+
+pub struct Backprop {
+    input_positions: Vec<Option<usize>>, //per input node, where its value is in the inputs (None for the bias)
+    calculated: Vec<Vec<usize>>, //per hidden/output node in evaluation order, the connections coming into it
+    aggregations: Vec<Aggregation>, //per hidden/output node, same order as calculated
+    output_positions: Vec<usize>, //node index of every output, in output order
+    number_of_inputs: usize,
+
+    connection_numbers: Vec<usize>,
+    connection_nodes: Vec<(usize, usize)>, //(from, to) node indexes, inputs first then calculated
+    weights: Vec<f64>,
+
+    activation_function: fn(f64) -> f64,
+    derivative_function: fn(f64) -> f64,
+}
+
+impl Backprop {
+    pub fn new(genome: &Genome, activation: Activation, with_bias: bool) -> Result<Self, String> {
+        let layout = NetworkLayout::new(genome)?;
+        if with_bias && layout.inputs.is_empty() {
+            return Err(String::from("genome needs a bias node"));
+        }
+
+        let input_positions: Vec<Option<usize>> = (0..layout.inputs.len()).map(|position| {
+            if !with_bias { Some(position) } else if position == 0 { None } else { Some(position - 1) }
+        }).collect();
+        let number_of_inputs = input_positions.iter().flatten().count();
+
+        let mut node_indexes: HashMap<usize, usize> = HashMap::new();
+        for node in layout.inputs.iter().chain(layout.calculated_nodes()) {
+            node_indexes.insert(*node, node_indexes.len());
+        }
+
+        let mut backprop = Backprop {
+            input_positions,
+            calculated: Vec::new(),
+            aggregations: Vec::new(),
+            output_positions: layout.outputs.iter().map(|node| node_indexes[node]).collect(),
+            number_of_inputs,
+            connection_numbers: Vec::new(),
+            connection_nodes: Vec::new(),
+            weights: Vec::new(),
+            activation_function: activation.function(),
+            derivative_function: activation.derivative(),
+        };
+
+        for node in layout.calculated_nodes() {
+            let mut incoming = Vec::new();
+            for (con_num, from, weight) in layout.get_incoming(*node) {
+                incoming.push(backprop.weights.len());
+                backprop.connection_numbers.push(*con_num);
+                backprop.connection_nodes.push((node_indexes[from], node_indexes[node]));
+                backprop.weights.push(*weight);
+            }
+            backprop.calculated.push(incoming);
+            backprop.aggregations.push(layout.get_aggregation(*node));
+        }
+
+        Ok(backprop)
+    }
+
+    pub fn run(&self, inputs: &[f64]) -> Vec<f64> {
+        let (_totals, values) = self.forward(inputs);
+        self.output_positions.iter().map(|node| values[*node]).collect()
+    }
+
+    //mean loss over the dataset
+    pub fn get_loss(&self, dataset: &[(Vec<f64>, Vec<f64>)], loss: Loss) -> f64 {
+        if dataset.is_empty() { return 0.0; }
+
+        let total: f64 = dataset.iter().map(|(inputs, targets)| loss.value(&self.run(inputs), targets)).sum();
+        total / dataset.len() as f64
+    }
+
+    //gradient of the mean loss over the batch, connection innovation number -> d loss / d weight
+    pub fn get_gradients(&self, batch: &[(Vec<f64>, Vec<f64>)], loss: Loss) -> HashMap<usize, f64> {
+        self.connection_numbers.iter().copied().zip(self.batch_gradients(batch, loss)).collect()
+    }
+
+    pub fn get_weights(&self) -> HashMap<usize, f64> {
+        self.connection_numbers.iter().copied().zip(self.weights.iter().copied()).collect()
+    }
+
+    pub fn clamp_weights(&mut self, min: f64, max: f64) {
+        for weight in self.weights.iter_mut() {
+            *weight = weight.max(min).min(max);
+        }
+    }
+
+    //lamarckian write back, only touches the connections this was made from
+    pub fn write_weights(&self, genome: &mut Genome) {
+        for (con_num, weight) in self.connection_numbers.iter().zip(&self.weights) {
+            match genome.connections.get_mut(con_num) {
+                None => panic!("connection {} isn't in the genome, write weights to the genome the Backprop was made from", con_num),
+                Some(connection) => connection.weight = *weight,
+            }
+        }
+    }
+
+    //returns the loss over the whole dataset after every epoch
+    pub fn train(&mut self, dataset: &[(Vec<f64>, Vec<f64>)], settings: &FineTuneSettings) -> Vec<f64> {
+        let mut state = OptimizerState {
+            first_moment: vec![0.0; self.weights.len()],
+            second_moment: vec![0.0; self.weights.len()],
+            steps: 0,
+        };
+        let batch_size = if settings.batch_size == 0 { dataset.len().max(1) } else { settings.batch_size };
+        let mut order: Vec<usize> = (0..dataset.len()).collect();
+        let mut losses = Vec::with_capacity(settings.epochs);
+
+        for _epoch in 0..settings.epochs {
+            if settings.shuffle {
+                order.shuffle(&mut rand::thread_rng());
+            }
+
+            for batch_order in order.chunks(batch_size) {
+                let batch: Vec<(Vec<f64>, Vec<f64>)> = batch_order.iter().map(|i| dataset[*i].clone()).collect();
+                let gradients = self.batch_gradients(&batch, settings.loss);
+                self.step(&gradients, &settings.optimizer, &mut state);
+            }
+
+            losses.push(self.get_loss(dataset, settings.loss));
+        }
+
+        losses
+    }
+
+    //(aggregated total, activated value) for every node index, inputs are just copied in
+    fn forward(&self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        if inputs.len() != self.number_of_inputs {
+            panic!("expected {} inputs, got {}", self.number_of_inputs, inputs.len());
+        }
+
+        let number_of_nodes = self.input_positions.len() + self.calculated.len();
+        let mut totals = vec![0.0; number_of_nodes];
+        let mut values = vec![0.0; number_of_nodes];
+
+        for (node, position) in self.input_positions.iter().enumerate() {
+            values[node] = match position {
+                None => 1.0,
+                Some(position) => inputs[*position],
+            };
+            totals[node] = values[node];
+        }
+
+        for (calculated_i, incoming) in self.calculated.iter().enumerate() {
+            let node = self.input_positions.len() + calculated_i;
+            totals[node] = self.aggregations[calculated_i].apply(&self.weighted_values(incoming, &values));
+            values[node] = (self.activation_function)(totals[node]);
+        }
+
+        (totals, values)
+    }
+
+    //weight * value of the from node, for each of the connections
+    fn weighted_values(&self, connections: &[usize], values: &[f64]) -> Vec<f64> {
+        connections.iter().map(|connection| self.weights[*connection] * values[self.connection_nodes[*connection].0]).collect()
+    }
+
+    fn batch_gradients(&self, batch: &[(Vec<f64>, Vec<f64>)], loss: Loss) -> Vec<f64> {
+        let mut gradients = vec![0.0; self.weights.len()];
+
+        for (inputs, targets) in batch {
+            if targets.len() != self.output_positions.len() {
+                panic!("expected {} targets, got {}", self.output_positions.len(), targets.len());
+            }
+            let (totals, values) = self.forward(inputs);
+
+            //d loss / d value, then back through the nodes in reverse evaluation order
+            let mut value_gradients = vec![0.0; values.len()];
+            for (node, target) in self.output_positions.iter().zip(targets) {
+                value_gradients[*node] += loss.derivative(values[*node], *target, targets.len());
+            }
+
+            for (calculated_i, incoming) in self.calculated.iter().enumerate().rev() {
+                let node = self.input_positions.len() + calculated_i;
+                let total_gradient = value_gradients[node] * (self.derivative_function)(totals[node]);
+                if total_gradient == 0.0 { continue; }
+
+                let partials = self.aggregations[calculated_i].gradient(&self.weighted_values(incoming, &values));
+                for (connection, partial) in incoming.iter().zip(partials) {
+                    let from = self.connection_nodes[*connection].0;
+                    gradients[*connection] += total_gradient * partial * values[from];
+                    value_gradients[from] += total_gradient * partial * self.weights[*connection];
+                }
+            }
+        }
+
+        for gradient in gradients.iter_mut() {
+            *gradient /= batch.len().max(1) as f64;
+        }
+        gradients
+    }
+
+    fn step(&mut self, gradients: &[f64], optimizer: &Optimizer, state: &mut OptimizerState) {
+        state.steps += 1;
+
+        match *optimizer {
+            Optimizer::Sgd {learning_rate, momentum} => {
+                for (i, gradient) in gradients.iter().enumerate() {
+                    state.first_moment[i] = momentum * state.first_moment[i] + gradient;
+                    self.weights[i] -= learning_rate * state.first_moment[i];
+                }
+            },
+            Optimizer::Adam {learning_rate, beta1, beta2, epsilon} => {
+                let first_correction = 1.0 - beta1.powi(state.steps);
+                let second_correction = 1.0 - beta2.powi(state.steps);
+
+                for (i, gradient) in gradients.iter().enumerate() {
+                    state.first_moment[i] = beta1 * state.first_moment[i] + (1.0 - beta1) * gradient;
+                    state.second_moment[i] = beta2 * state.second_moment[i] + (1.0 - beta2) * gradient * gradient;
+
+                    let first = state.first_moment[i] / first_correction;
+                    let second = state.second_moment[i] / second_correction;
+                    self.weights[i] -= learning_rate * first / (second.sqrt() + epsilon);
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use super::genome::Genome;
+use super::node_gene::{NodeGene, NodeRole, INPUT_X, OUTPUT_X};
+use super::connection_gene::ConnectionGene;
+use crate::aggregation_functions::Aggregation;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/* - GenomeBuilder -
+Builds a genome by hand without touching innovation numbers or x/y positions
+    let mut builder = GenomeBuilder::new();
+    let bias = builder.add_input("bias");
+    builder.add_input("x");
+    builder.add_hidden("h");
+    builder.add_output("y");
+    builder.connect("x", "h", 1.5).connect(bias, "h", -0.5).connect("h", "y", 2.0);
+    let genome = builder.build()?;
+nodes are referred to by name or by the index add_* returned, mistakes (unknown names, cycles...) come out of build
+build numbers the nodes like the calculator wants them, inputs first then outputs (in the order they were added),
+then hidden nodes, hidden nodes are spread between the inputs and outputs by how deep they are
+Neat::build_genome numbers everything with a Neat's innovation tables instead, for a Neat the first input is the bias
+ */
+
+//a node given to GenomeBuilder::connect/set_aggregation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeRef {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for NodeRef {
+    fn from(index: usize) -> Self {
+        NodeRef::Index(index)
+    }
+}
+
+impl From<&str> for NodeRef {
+    fn from(name: &str) -> Self {
+        NodeRef::Name(String::from(name))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GenomeBuilder {
+    nodes: Vec<(String, NodeRole, Aggregation)>, //in the order they were added, the position is the node's index
+    aggregations: Vec<(NodeRef, Aggregation)>,
+    connections: Vec<(NodeRef, NodeRef, f64, bool)>, //(from, to, weight, enabled)
+}
+
+impl GenomeBuilder {
+    pub fn new() -> Self {
+        GenomeBuilder::default()
+    }
+
+    pub fn add_input(&mut self, name: &str) -> usize {
+        self.add_node(name, NodeRole::Input)
+    }
+
+    pub fn add_hidden(&mut self, name: &str) -> usize {
+        self.add_node(name, NodeRole::Hidden)
+    }
+
+    pub fn add_output(&mut self, name: &str) -> usize {
+        self.add_node(name, NodeRole::Output)
+    }
+
+    //nodes are sum unless set otherwise
+    pub fn set_aggregation<N: Into<NodeRef>>(&mut self, node: N, aggregation: Aggregation) -> &mut Self {
+        self.aggregations.push((node.into(), aggregation));
+        self
+    }
+
+    pub fn connect<F: Into<NodeRef>, T: Into<NodeRef>>(&mut self, from: F, to: T, weight: f64) -> &mut Self {
+        self.connections.push((from.into(), to.into(), weight, true));
+        self
+    }
+
+    pub fn connect_disabled<F: Into<NodeRef>, T: Into<NodeRef>>(&mut self, from: F, to: T, weight: f64) -> &mut Self {
+        self.connections.push((from.into(), to.into(), weight, false));
+        self
+    }
+
+    pub fn build(&self) -> Result<Genome, String> {
+        let mut nodes = self.nodes.clone();
+        for (node, aggregation) in &self.aggregations {
+            let index = self.resolve(node)?;
+            if nodes[index].1 == NodeRole::Input {
+                return Err(format!("input node '{}' doesn't aggregate anything", nodes[index].0));
+            }
+            nodes[index].2 = *aggregation;
+        }
+
+        let mut connections: Vec<(usize, usize, f64, bool)> = Vec::new();
+        for (from, to, weight, enabled) in &self.connections {
+            let (from, to) = (self.resolve(from)?, self.resolve(to)?);
+            if nodes[to].1 == NodeRole::Input {
+                return Err(format!("connection from '{}' goes into input node '{}'", nodes[from].0, nodes[to].0));
+            }
+            if nodes[from].1 == NodeRole::Output {
+                return Err(format!("connection to '{}' comes out of output node '{}'", nodes[to].0, nodes[from].0));
+            }
+            connections.push((from, to, *weight, *enabled));
+        }
+
+        let depths = GenomeBuilder::hidden_depths(&nodes, &connections)?;
+        let max_depth = depths.values().copied().max().unwrap_or(0);
+
+        //inputs, then outputs, then hidden nodes, each in the order they were added
+        let mut order: Vec<usize> = Vec::new();
+        for role in [NodeRole::Input, NodeRole::Output, NodeRole::Hidden].iter() {
+            order.extend((0..nodes.len()).filter(|index| nodes[*index].1 == *role));
+        }
+
+        let mut genome = Genome::new();
+        let mut innovation_numbers: HashMap<usize, usize> = HashMap::new();
+        for (innovation_number, index) in order.iter().enumerate() {
+            let (_name, role, aggregation) = &nodes[*index];
+            let same_role: Vec<usize> = order.iter().copied().filter(|other| nodes[*other].1 == *role).collect();
+            let y = same_role.iter().position(|other| other == index).unwrap() as f64 / same_role.len() as f64;
+            let x = match role {
+                NodeRole::Input => INPUT_X,
+                NodeRole::Output => OUTPUT_X,
+                NodeRole::Hidden => INPUT_X + (OUTPUT_X - INPUT_X) * depths[index] as f64 / (max_depth + 1) as f64,
+            };
+
+            genome.add_node(Rc::new(NodeGene::new_with_role(innovation_number, x, y, *role).with_aggregation(*aggregation)));
+            innovation_numbers.insert(*index, innovation_number);
+        }
+
+        for (innovation_number, (from, to, weight, enabled)) in connections.iter().enumerate() {
+            let mut connection = ConnectionGene::new(innovation_number, Rc::clone(&genome.nodes[&innovation_numbers[from]]),
+                                                     Rc::clone(&genome.nodes[&innovation_numbers[to]]));
+            connection.weight = *weight;
+            connection.enabled = *enabled;
+            genome.add_connection(connection);
+        }
+
+        if let Some(violation) = genome.validate().first() {
+            return Err(format!("invalid genome: {}", violation));
+        }
+
+        Ok(genome)
+    }
+
+    fn add_node(&mut self, name: &str, role: NodeRole) -> usize {
+        self.nodes.push((String::from(name), role, Aggregation::Sum));
+        self.nodes.len() - 1
+    }
+
+    fn resolve(&self, node: &NodeRef) -> Result<usize, String> {
+        match node {
+            NodeRef::Index(index) if *index < self.nodes.len() => Ok(*index),
+            NodeRef::Index(index) => Err(format!("there's no node {}", index)),
+            NodeRef::Name(name) => {
+                let mut matching = (0..self.nodes.len()).filter(|index| &self.nodes[*index].0 == name);
+                match (matching.next(), matching.next()) {
+                    (Some(index), None) => Ok(index),
+                    (None, _) => Err(format!("there's no node named '{}'", name)),
+                    (Some(_), Some(_)) => Err(format!("more than one node is named '{}'", name)),
+                }
+            },
+        }
+    }
+
+    //longest path (in connections) from an input to each hidden node, 1 for a hidden node right after the inputs
+    fn hidden_depths(nodes: &[(String, NodeRole, Aggregation)], connections: &[(usize, usize, f64, bool)]) -> Result<HashMap<usize, usize>, String> {
+        let mut depths: HashMap<usize, usize> = HashMap::new();
+        let mut remaining: Vec<usize> = (0..nodes.len()).filter(|index| nodes[*index].1 == NodeRole::Hidden).collect();
+
+        //a hidden node's depth is known once every hidden node feeding it has one
+        while !remaining.is_empty() {
+            let ready: Vec<usize> = remaining.iter().copied().filter(|node| {
+                connections.iter().all(|(from, to, _weight, _enabled)| to != node || nodes[*from].1 != NodeRole::Hidden || depths.contains_key(from))
+            }).collect();
+            if ready.is_empty() {
+                let names: Vec<&str> = remaining.iter().map(|node| nodes[*node].0.as_str()).collect();
+                return Err(format!("hidden nodes {:?} are connected in a cycle", names));
+            }
+
+            for node in &ready {
+                let depth = connections.iter().filter(|(_from, to, _weight, _enabled)| to == node)
+                    .map(|(from, _to, _weight, _enabled)| depths.get(from).copied().unwrap_or(0) + 1)
+                    .max().unwrap_or(1);
+                depths.insert(*node, depth);
+            }
+            remaining.retain(|node| !ready.contains(node));
+        }
+
+        Ok(depths)
+    }
+}
@@ -1,15 +1,22 @@
 use super::node::Node;
+use crate::feed_forward::plasticity::HebbianRule;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 pub struct Connection {
-    pub weight: f64,
+    pub weight: f64, //the genetic weight
     pub node_ref: Rc<RefCell<Node>>,
+    current_weight: Cell<f64>, //what the connection runs with, the hebbian rule changes it after every run
+    hebbian: HebbianRule,
 }
 
 impl Connection {
     pub(super) fn new(weight: f64, node: Rc<RefCell<Node>>) -> Self{
-        Connection {weight, node_ref: node }
+        Connection {weight, node_ref: node, current_weight: Cell::new(weight), hebbian: HebbianRule::default()}
+    }
+
+    pub(super) fn with_hebbian(self, hebbian: HebbianRule) -> Self {
+        Connection {hebbian, ..self}
     }
 
     pub(super) fn get_weighted_connection_value(&self) -> f64 {
@@ -17,10 +24,25 @@ impl Connection {
         match node.output {
             None => panic!("ERROR, no output in input node - illegal for this basic feed-forward network"),
             Some(output) => {
-                output * self.weight
+                output * self.current_weight.get()
             }
         }
     }
+
+    pub(super) fn is_plastic(&self) -> bool {
+        self.hebbian.is_plastic()
+    }
+
+    //post is the output of the node this connection goes into, factor scales the change (see plasticity::modulation_factor)
+    pub(super) fn learn(&self, post: f64, factor: f64) {
+        if let Some(pre) = self.node_ref.borrow().output {
+            self.current_weight.set(self.current_weight.get() + factor * self.hebbian.weight_change(pre, post));
+        }
+    }
+
+    pub(super) fn reset_weight(&self) {
+        self.current_weight.set(self.weight);
+    }
 }
 
 impl PartialEq for Connection {
@@ -35,11 +57,12 @@ impl Eq for Connection {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aggregation_functions::Aggregation;
 
     #[test]
     fn new_connection_has_weight_and_node_given() {
         let weight = 55_f64;
-        let node_ref = Rc::new(RefCell::new(Node::new(0.5)));
+        let node_ref = Rc::new(RefCell::new(Node::new(Aggregation::Sum)));
 
         let new_connection = Connection::new(weight, Rc::clone(&node_ref));
 
@@ -51,7 +74,7 @@ mod tests {
     #[should_panic]
     fn get_weighted_connection_value_panics_with_new_node() {
         let weight = 55_f64;
-        let node_ref = Rc::new(RefCell::new(Node::new(0.5)));
+        let node_ref = Rc::new(RefCell::new(Node::new(Aggregation::Sum)));
 
         let new_connection = Connection::new(weight, Rc::clone(&node_ref));
 
@@ -61,7 +84,7 @@ mod tests {
     #[test]
     fn get_weighted_connection_value_returns_as_expected_with_doctored_node_output() {
         let weight = 55_f64;
-        let node_ref = Rc::new(RefCell::new(Node::new(0.5)));
+        let node_ref = Rc::new(RefCell::new(Node::new(Aggregation::Sum)));
 
         let node_output = 0.22_f64;
         node_ref.borrow_mut().set_output(Some(node_output));
@@ -71,4 +94,21 @@ mod tests {
         let return_value = new_connection.get_weighted_connection_value();
         assert_eq!(return_value, weight * node_output);
     }
+
+    #[test]
+    fn learn_changes_the_weight_until_reset() {
+        let node_ref = Rc::new(RefCell::new(Node::new(Aggregation::Sum)));
+        node_ref.borrow_mut().set_output(Some(0.5));
+
+        let new_connection = Connection::new(2.0, Rc::clone(&node_ref)).with_hebbian(HebbianRule::new(1.0, 0.0, 0.0, 0.5, 0.1));
+        assert!(new_connection.is_plastic());
+
+        //0.1 * (1 * 0.5 * 0.4 + 0.5)
+        new_connection.learn(0.4, 1.0);
+        assert!((new_connection.get_weighted_connection_value() - 0.5 * 2.07).abs() < 1e-12);
+        assert_eq!(new_connection.weight, 2.0, "the genetic weight stays");
+
+        new_connection.reset_weight();
+        assert_eq!(new_connection.get_weighted_connection_value(), 0.5 * 2.0);
+    }
 }
\ No newline at end of file
@@ -8,20 +8,20 @@ use crate::feed_forward::calculator::node::Node;
 use crate::feed_forward::connection_gene::ConnectionGene;
 use crate::feed_forward::gene::Gene;
 use crate::feed_forward::genome::Genome;
+use crate::feed_forward::layout::evaluation_order;
+use crate::feed_forward::node_gene::NodeRole;
 
 pub(crate) mod connection;
 pub(crate) mod node;
 
 pub(crate) struct Calculator<F> where
     F: Fn(f64) -> f64 {
-    input_nodes: Vec<Rc<RefCell<Node>>>,
-    output_nodes: Vec<Rc<RefCell<Node>>>,
-    hidden_nodes: Vec<Rc<RefCell<Node>>>, //order of these can change
-
-    input_innovation_num_to_position: HashMap<usize, usize>,
-    output_inv_to_position: HashMap<usize, usize>,
+    input_nodes: Vec<Rc<RefCell<Node>>>, //in innovation number order, the i-th input goes to the i-th of these
+    output_nodes: Vec<Rc<RefCell<Node>>>, //in innovation number order, like the outputs
+    hidden_nodes: Vec<Rc<RefCell<Node>>>, //in evaluation order
 
     activation_function: F,
+    plastic: bool, //whether any connection has a hebbian rule, see plasticity
 }
 
 impl<F> Calculator<F> where
@@ -31,9 +31,8 @@ impl<F> Calculator<F> where
             input_nodes: Vec::new(),
             hidden_nodes: Vec::new(),
             output_nodes: Vec::new(),
-            input_innovation_num_to_position: HashMap::new(),
-            output_inv_to_position: HashMap::new(),
             activation_function,
+            plastic: false,
         }
     }
 
@@ -42,21 +41,44 @@ impl<F> Calculator<F> where
         Self::new(&*genome, activation_function)
     }
 
+    //panics if the genome's nodes can't be put in order (see try_new)
     pub fn new(genome: &Genome, activation_function: F) -> Self {
+        match Self::try_new(genome, activation_function) {
+            Err(message) => panic!("can't make a calculator: {}", message),
+            Ok(calculator) => calculator,
+        }
+    }
+
+    //errors when the enabled connections have a cycle
+    pub fn try_new(genome: &Genome, activation_function: F) -> Result<Self, String> {
         let mut new_calculator = Self::new_with_defaults(activation_function);
-        new_calculator.add_genome_data_to_calculator(genome);
-        new_calculator
+        new_calculator.add_genome_data_to_calculator(genome)?;
+        Ok(new_calculator)
     }
 
-    fn add_genome_data_to_calculator(&mut self, genome: &Genome) {
+    fn add_genome_data_to_calculator(&mut self, genome: &Genome) -> Result<(), String> {
         let mut node_innovation_num_to_ref: HashMap<usize, Rc<RefCell<Node>>> = HashMap::new(); //so that the same nodes are the same (not copies/clones)
 
-        //start of node stuffs
-        for (_inv_num, genome_node) in genome.nodes.iter() {
-            let node: Rc<RefCell<Node>> = Node::new_node_ref_with_refcell_from_x(genome_node.get_x());
+        //start of node stuffs, inputs/outputs are in innovation number order (the numbers don't have to be 0..n)
+        let mut inv_nums: Vec<usize> = genome.nodes.keys().copied().collect();
+        inv_nums.sort_unstable();
+        for inv_num in inv_nums {
+            let genome_node = &genome.nodes[&inv_num];
+            let node: Rc<RefCell<Node>> = Node::new_node_ref_with_refcell(genome_node.get_aggregation());
 
             node_innovation_num_to_ref.insert(genome_node.get_innovation_number(), Rc::clone(&node));
-            self.add_node_to_calculator(node, genome_node.get_innovation_number());
+            match genome_node.get_role() {
+                NodeRole::Input => self.add_node_to_calculator(node, NodeRole::Input),
+                NodeRole::Output => self.add_node_to_calculator(node, NodeRole::Output),
+                NodeRole::Hidden => {}, //added below, in evaluation order
+            }
+        }
+
+        //hidden nodes are run in a topological order, so a node is run after everything feeding it
+        for inv_num in evaluation_order(genome)? {
+            if genome.nodes[&inv_num].get_role() == NodeRole::Hidden {
+                self.add_node_to_calculator(Rc::clone(&node_innovation_num_to_ref[&inv_num]), NodeRole::Hidden);
+            }
         }
 
         //start of connections stuff
@@ -65,61 +87,59 @@ impl<F> Calculator<F> where
 
             Self::add_connection_to_nodes_from_gene(&mut node_innovation_num_to_ref, genome_connection);
         }
+        self.plastic = self.hidden_nodes.iter().chain(self.output_nodes.iter()).any(|node| node.borrow().has_plastic_connections());
 
-        self.hidden_nodes.sort_unstable();
+        Ok(())
     }
 
     fn add_connection_to_nodes_from_gene(mut node_innovation_num_to_ref_map: &mut HashMap<usize, Rc<RefCell<Node>>>, connection_gene: &ConnectionGene) {
         let (from_node, to_node) = get_node_refs_from_connection_gene(&mut node_innovation_num_to_ref_map, &connection_gene);
 
-        let new_connection: Connection = Connection::new(connection_gene.weight, from_node);
+        let new_connection: Connection = Connection::new(connection_gene.weight, from_node).with_hebbian(connection_gene.hebbian);
 
-        &mut to_node.borrow_mut().connections.push(Rc::new(new_connection));
-    }
-
-    fn add_node_to_calculator(&mut self, node_ref: Rc<RefCell<Node>>, innovation_number: usize) {
-        let node_x: f64 = node_ref.borrow().x;
-
-        let is_hidden_node: bool = node_x > 0.1 && node_x < 0.9;
-        if !is_hidden_node {
-            let node_vector_length = self.get_node_vector_from_x(node_x).len();
-            self.get_node_to_position_map_from_x_mut(node_x).insert(innovation_number, node_vector_length);
-        }
-
-        self.get_node_vector_from_x_mut(node_x).push(node_ref);
-    }
-
-    fn get_node_vector_from_x_mut(&mut self, node_x: f64) -> &mut Vec<Rc<RefCell<Node>>> {
-        if node_x <= 0.1 { //input node
-            &mut self.input_nodes
-        } else if node_x >= 0.9 { //output node
-            &mut self.output_nodes
-        } else { //hidden node
-            &mut self.hidden_nodes
+        //modulatory nodes only gate learning (see plasticity)
+        if connection_gene.from.is_modulatory() {
+            to_node.borrow_mut().modulating_connections.push(Rc::new(new_connection));
+        } else {
+            to_node.borrow_mut().connections.push(Rc::new(new_connection));
         }
     }
 
-    fn get_node_vector_from_x(&mut self, node_x: f64) -> &Vec<Rc<RefCell<Node>>> {
-        self.get_node_vector_from_x_mut(node_x)
+    fn add_node_to_calculator(&mut self, node_ref: Rc<RefCell<Node>>, role: NodeRole) {
+        self.get_node_vector_from_role_mut(role).push(node_ref);
     }
 
-    fn get_node_to_position_map_from_x_mut(&mut self, node_x: f64) -> &mut HashMap<usize, usize> {
-        if node_x <= 0.1 { //input node
-            &mut self.input_innovation_num_to_position
-        } else if node_x >= 0.9 { //output node
-            &mut self.output_inv_to_position
-        } else {
-            panic!("whoa, that's illegal");
+    fn get_node_vector_from_role_mut(&mut self, role: NodeRole) -> &mut Vec<Rc<RefCell<Node>>> {
+        match role {
+            NodeRole::Input => &mut self.input_nodes,
+            NodeRole::Output => &mut self.output_nodes,
+            NodeRole::Hidden => &mut self.hidden_nodes,
         }
     }
 
-    //assumes that input vector maps to innovation number (0th in inputs = node with inv num 0)
+    //the i-th input goes to the input node with the i-th smallest innovation number, outputs are in the same order
+    //plastic connections update their weights after the outputs are worked out, so they affect the next run
     pub fn run(&self, inputs: &Vec<f64>) -> Vec<f64> {
         if inputs.len() < self.input_nodes.len() { panic!("BAD INPUT TO CALCULATOR"); }
 
         self.process_input_nodes(inputs);
         self.process_hidden_nodes();
-        self.get_outputs_from_nodes()
+        let outputs = self.get_outputs_from_nodes();
+
+        if self.plastic {
+            for node in self.hidden_nodes.iter().chain(self.output_nodes.iter()) {
+                node.borrow().learn();
+            }
+        }
+
+        outputs
+    }
+
+    //puts every connection back to its genetic weight, undoing what the hebbian rules learned
+    pub fn reset_state(&self) {
+        for node in self.input_nodes.iter().chain(self.hidden_nodes.iter()).chain(self.output_nodes.iter()) {
+            node.borrow_mut().reset();
+        }
     }
 
     fn get_outputs_from_nodes(&self) -> Vec<f64> {
@@ -130,11 +150,8 @@ impl<F> Calculator<F> where
 
     fn process_input_nodes(&self, inputs: &Vec<f64>) {
         //map input arguments to input_node's output
-        for node_innovation_number in 0..self.input_nodes.len() {
-            let node_position = self.input_innovation_num_to_position.get(&node_innovation_number).unwrap();
-            let node: &mut Node = &mut self.input_nodes[*node_position].borrow_mut();
-
-            node.set_output(Some(inputs[node_innovation_number]));
+        for (node_ref, input) in self.input_nodes.iter().zip(inputs.iter()) {
+            node_ref.borrow_mut().set_output(Some(*input));
         }
     }
 
@@ -147,9 +164,7 @@ impl<F> Calculator<F> where
     }
 
     fn process_output_nodes(&self, outputs: &mut Vec<f64>) {
-        for i in (self.input_nodes.len()..).take(self.output_nodes.len()) {
-            let node_position = self.output_inv_to_position.get(&i).unwrap();
-            let node_rc: &Rc<RefCell<Node>> = &self.output_nodes[*node_position];
+        for node_rc in &self.output_nodes {
             node_rc.borrow_mut().run_node(&self.activation_function);
 
             outputs.push(node_rc.borrow().output.unwrap());
@@ -159,12 +174,14 @@ impl<F> Calculator<F> where
 
 fn get_node_refs_from_connection_gene(node_innovation_num_to_ref: &mut HashMap<usize, Rc<RefCell<Node>>>, connection_gene: &&ConnectionGene) -> (Rc<RefCell<Node>>, Rc<RefCell<Node>>) {
     let from_node: Rc<RefCell<Node>> = match node_innovation_num_to_ref.get(&connection_gene.from.get_innovation_number()) {
-        None => panic!(),
+        None => panic!("connection {} comes from node {} which isn't in the genome (see Genome::validate)",
+                       connection_gene.get_innovation_number(), connection_gene.from.get_innovation_number()),
         Some(node) => Rc::clone(node)
     };
 
     let to_node: Rc<RefCell<Node>> = match node_innovation_num_to_ref.get(&connection_gene.to.get_innovation_number()) {
-        None => panic!(),
+        None => panic!("connection {} goes to node {} which isn't in the genome (see Genome::validate)",
+                       connection_gene.get_innovation_number(), connection_gene.to.get_innovation_number()),
         Some(node) => Rc::clone(node)
     };
 
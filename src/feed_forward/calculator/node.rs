@@ -1,25 +1,32 @@
 use crate::random_hash_set::RandomHashSet;
 use super::connection::Connection;
-use std::cmp::Ordering;
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::plasticity::modulation_factor;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 pub struct Node {
     pub(super) connections: RandomHashSet<Connection>,
+    pub(super) modulating_connections: Vec<Rc<Connection>>, //from modulatory nodes, they gate learning instead of feeding the output
     pub(super) output: Option<f64>,
-    pub x: f64,
+    modulation: Option<f64>, //None without modulating connections, otherwise their weighted sum from the last run
+    aggregation: Aggregation,
 }
 
 impl Node {
-    pub(super) fn new(x: f64) -> Self {
-        Node {connections: RandomHashSet::new(), output: None, x}
+    pub(super) fn new(aggregation: Aggregation) -> Self {
+        Node {connections: RandomHashSet::new(), modulating_connections: Vec::new(), output: None, modulation: None, aggregation}
     }
 
     // goes over connections and processes from_node's output with connection weight,
-    // averages this, then yeets through activation function
+    // aggregates this (sums by default), then yeets through activation function
     pub(super) fn run_node<F>(&mut self, activation_function: F) where
         F: Fn(f64) -> f64 {
         self.output = Some(self.get_activated_output(activation_function)); // gets output, saves
+
+        if !self.modulating_connections.is_empty() {
+            self.modulation = Some(self.modulating_connections.iter().map(|connection| connection.get_weighted_connection_value()).sum());
+        }
     }
 
     fn get_activated_output<F>(&self, activation_function: F) -> f64 where
@@ -29,44 +36,48 @@ impl Node {
     }
 
     fn get_total_in_from_connections(&self) -> f64 {
-        let mut total_in: f64 = 0.0;
-        for connection in self.connections.get_data() { total_in += connection.get_weighted_connection_value(); }
-        total_in
+        let weighted_values: Vec<f64> = self.connections.get_data().iter().map(|connection| connection.get_weighted_connection_value()).collect();
+        self.aggregation.apply(&weighted_values)
     }
 
-    //for setting the input nodes' values
-    pub(super) fn set_output(&mut self, output: Option<f64>) {
-        self.output = output;
+    //runs the hebbian rule of every incoming connection with this node's last output
+    //scaled by the modulation when the node has modulating connections
+    pub(super) fn learn(&self) {
+        let factor = self.modulation.map_or(1.0, modulation_factor);
+        if let Some(post) = self.output {
+            for connection in self.connections.get_data() {
+                connection.learn(post, factor);
+            }
+        }
     }
 
-    pub(super) fn new_node_ref_with_refcell_from_x(x: f64) -> Rc<RefCell<Node>> {
-        Rc::new(RefCell::new(Node::new(x)))
+    pub(super) fn has_plastic_connections(&self) -> bool {
+        self.connections.get_data().iter().any(|connection| connection.is_plastic())
     }
-}
 
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.x > other.x {
-            Some(Ordering::Greater)
-        }
-        else if self.x < other.x {
-            Some(Ordering::Less)
-        }
-        else {
-            Some(Ordering::Equal)
+    //genetic weights back, outputs cleared
+    pub(super) fn reset(&mut self) {
+        self.output = None;
+        self.modulation = None;
+        for connection in self.connections.get_data() {
+            connection.reset_weight();
         }
     }
-}
 
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if self.x > other.x { Ordering::Greater } else if self.x < other.x { Ordering::Less } else { Ordering::Equal }
+    //for setting the input nodes' values
+    pub(super) fn set_output(&mut self, output: Option<f64>) {
+        self.output = output;
+    }
+
+    pub(super) fn new_node_ref_with_refcell(aggregation: Aggregation) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node::new(aggregation)))
     }
 }
 
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
-        self.connections.get_data() == other.connections.get_data() && self.output == other.output && self.x == other.x
+        self.connections.get_data() == other.connections.get_data() && self.output == other.output
+            && self.aggregation == other.aggregation
     }
 }
 
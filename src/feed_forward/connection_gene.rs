@@ -1,5 +1,6 @@
 use super::gene::Gene;
 use super::node_gene::NodeGene;
+use super::plasticity::HebbianRule;
 use std::rc::Rc;
 
 static DEFAULT_WEIGHT: f64 = 1.0;
@@ -15,21 +16,25 @@ pub struct ConnectionGene {
 
     pub(crate) weight: f64,
     pub(crate) enabled: bool,
+    pub(crate) hebbian: HebbianRule, //all 0 (not plastic) unless mutated, see plasticity
 }
 
 impl ConnectionGene {
     pub fn new(innovation_number: usize, from: Rc<NodeGene>, to: Rc<NodeGene>) -> Self {
-        ConnectionGene{innovation_number, from, to, weight: DEFAULT_WEIGHT, enabled: DEFAULT_ENABLED}
+        ConnectionGene{innovation_number, from, to, weight: DEFAULT_WEIGHT, enabled: DEFAULT_ENABLED, hebbian: HebbianRule::default()}
     }
+}
 
-    //copies all values from given connection reference, returns new connection
-    pub fn clone(old_gene: &ConnectionGene) -> Self {
+//copies all values from given connection reference, returns new connection
+impl Clone for ConnectionGene {
+    fn clone(&self) -> Self {
         ConnectionGene{
-            innovation_number: old_gene.innovation_number,
-            from: Rc::clone(&old_gene.from),
-            to: Rc::clone(&old_gene.to),
-            weight: old_gene.weight,
-            enabled: old_gene.enabled,
+            innovation_number: self.innovation_number,
+            from: Rc::clone(&self.from),
+            to: Rc::clone(&self.to),
+            weight: self.weight,
+            enabled: self.enabled,
+            hebbian: self.hebbian,
         }
     }
 }
@@ -48,7 +53,7 @@ impl PartialEq for ConnectionGene {
     fn eq(&self, other: &Self) -> bool { // they gotta be equal on every single option
         self.innovation_number == other.innovation_number && self.from == other.from
             && self.to == other.to && self.weight == other.weight
-            && self.enabled == other.enabled
+            && self.enabled == other.enabled && self.hebbian == other.hebbian
     }
 }
 
@@ -0,0 +1,113 @@
+use super::genome::Genome;
+use super::layout::NetworkLayout;
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use std::collections::HashMap;
+
+/* - CTRNN -
+A genome run as a continuous-time recurrent neural network, every hidden/output node has a state y that moves towards
+what the node would output in a normal network, as fast as its time constant (tau) allows
+    tau * dy/dt = -y + activation(aggregation(weight * incoming values))
+incoming values are the inputs for input nodes and the states of other nodes, so a value takes time to get through
+every node is updated from the same previous states, so unlike the other phenotypes the genome can be recurrent:
+connections can go backwards (to.x <= from.x), to the node itself or out of an output, the rest of Genome::validate
+still applies (the neat mutations only make feed-forward genomes, recurrent ones have to be built by hand)
+    Ctrnn::new(&genome, activation, with_bias) - with_bias works like Network (the first input is fixed at 1.0)
+    advance(&inputs, dt, steps) - steps euler steps of dt each with the inputs held, returns the output states
+    reset() - every state back to 0
+time constants are node genes (NodeGene::get_time_constant), they're inherited and mutated (mutate_probability_time_constant)
+connections out of modulatory nodes are left out, like in a Network
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ctrnn {
+    activation: Activation,
+    with_bias: bool,
+
+    number_of_input_nodes: usize, //bias included, their values come first in states
+    nodes: Vec<CtrnnNode>, //hidden then output nodes, states[number_of_input_nodes + i] is nodes[i]'s state
+    output_positions: Vec<usize>, //state index of every output, in output order
+    states: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CtrnnNode {
+    aggregation: Aggregation,
+    time_constant: f64,
+    incoming: Vec<(usize, f64)>, //(state index, weight)
+}
+
+impl Ctrnn {
+    //errors on an invalid genome, cycles and recurrent connections are fine (see above)
+    pub fn new(genome: &Genome, activation: Activation, with_bias: bool) -> Result<Self, String> {
+        let layout = NetworkLayout::new_recurrent(genome)?;
+        if with_bias && layout.inputs.is_empty() {
+            return Err(String::from("genome needs a bias node"));
+        }
+
+        let mut state_indexes: HashMap<usize, usize> = HashMap::new();
+        for node in layout.inputs.iter().chain(layout.calculated_nodes()) {
+            state_indexes.insert(*node, state_indexes.len());
+        }
+
+        let nodes: Vec<CtrnnNode> = layout.calculated_nodes().map(|node| CtrnnNode {
+            aggregation: layout.get_aggregation(*node),
+            time_constant: genome.nodes[node].get_time_constant(),
+            incoming: layout.get_incoming(*node).iter().map(|(_con_num, from, weight)| (state_indexes[from], *weight)).collect(),
+        }).collect();
+
+        Ok(Ctrnn {
+            activation,
+            with_bias,
+            number_of_input_nodes: layout.inputs.len(),
+            output_positions: layout.outputs.iter().map(|node| state_indexes[node]).collect(),
+            states: vec![0.0; state_indexes.len()],
+            nodes,
+        })
+    }
+
+    pub fn advance(&mut self, inputs: &[f64], dt: f64, steps: usize) -> Vec<f64> {
+        if inputs.len() != self.get_number_of_inputs() {
+            panic!("expected {} inputs, got {}", self.get_number_of_inputs(), inputs.len());
+        }
+
+        let bias_offset = if self.with_bias { 1 } else { 0 };
+        if self.with_bias {
+            self.states[0] = 1.0;
+        }
+        self.states[bias_offset..self.number_of_input_nodes].copy_from_slice(inputs);
+
+        for _ in 0..steps {
+            //every node moves from the same previous states
+            let changes: Vec<f64> = self.nodes.iter().enumerate().map(|(i, node)| {
+                let weighted_values: Vec<f64> = node.incoming.iter().map(|(from, weight)| weight * self.states[*from]).collect();
+                let target = self.activation.apply(node.aggregation.apply(&weighted_values));
+                dt * (target - self.states[self.number_of_input_nodes + i]) / node.time_constant
+            }).collect();
+
+            for (i, change) in changes.iter().enumerate() {
+                self.states[self.number_of_input_nodes + i] += change;
+            }
+        }
+
+        self.get_outputs()
+    }
+
+    //the output states as they are now
+    pub fn get_outputs(&self) -> Vec<f64> {
+        self.output_positions.iter().map(|position| self.states[*position]).collect()
+    }
+
+    pub fn reset(&mut self) {
+        self.states.iter_mut().for_each(|state| *state = 0.0);
+    }
+
+    //what advance takes, the bias isn't counted
+    pub fn get_number_of_inputs(&self) -> usize {
+        if self.with_bias { self.number_of_input_nodes - 1 } else { self.number_of_input_nodes }
+    }
+
+    pub fn get_number_of_outputs(&self) -> usize {
+        self.output_positions.len()
+    }
+}
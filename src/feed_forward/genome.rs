@@ -1,17 +1,21 @@
-use super::node_gene::NodeGene;
+use super::node_gene::{NodeGene, NodeRole};
 use super::connection_gene::ConnectionGene;
 use super::gene::Gene;
+use super::mutation_rates::MutationRates;
+use crate::aggregation_functions::Aggregation;
 use std::rc::Rc;
 use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct Genome {
     pub(crate) connections: HashMap<usize, ConnectionGene>, //connections with same inv_num need to share to/from nodes
     pub(crate) nodes: HashMap<usize, Rc<NodeGene>>, //nodes with same inv_num need to be exact same node (to maintain x,y values) - not Rc as a node's values are constant
+    pub(crate) mutation_rates: Option<MutationRates>, //the genome's own mutation probabilities, only set by a self-adaptive Neat
 }
 
 impl Genome {
     pub fn new() -> Self {
-        Genome {connections: HashMap::new(), nodes: HashMap::new()}
+        Genome {connections: HashMap::new(), nodes: HashMap::new(), mutation_rates: None}
     }
 
     //If node not already contained, add
@@ -21,11 +25,102 @@ impl Genome {
         }
     }
 
-    pub fn add_connection(&mut self, connection: ConnectionGene) {
+    //a connection uses the genome's own nodes when it has them, they can differ from the shared ones (eg. their aggregation)
+    pub fn add_connection(&mut self, mut connection: ConnectionGene) {
         if !self.connections.contains_key(&connection.get_innovation_number()) {
+            if let Some(from) = self.nodes.get(&connection.from.get_innovation_number()) {
+                connection.from = Rc::clone(from);
+            }
+            if let Some(to) = self.nodes.get(&connection.to.get_innovation_number()) {
+                connection.to = Rc::clone(to);
+            }
+
             self.connections.insert(connection.get_innovation_number(), connection);
         }
     }
+
+    pub fn get_mutation_rates(&self) -> Option<MutationRates> {
+        self.mutation_rates
+    }
+
+    //swaps out the node with the same innovation number, connections to/from it use the new one
+    pub fn replace_node(&mut self, node: Rc<NodeGene>) {
+        let innovation_number = node.get_innovation_number();
+
+        for connection in self.connections.values_mut() {
+            if connection.from.get_innovation_number() == innovation_number {
+                connection.from = Rc::clone(&node);
+            }
+            if connection.to.get_innovation_number() == innovation_number {
+                connection.to = Rc::clone(&node);
+            }
+        }
+
+        self.nodes.insert(innovation_number, node);
+    }
+
+    //renders the genome as a graphviz digraph, inputs on the left and outputs on the right
+    //disabled connections are drawn dashed, modulatory nodes are diamonds
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph genome {\n    rankdir=LR;\n");
+
+        let mut node_keys: Vec<&usize> = self.nodes.keys().collect();
+        node_keys.sort_unstable();
+
+        let mut input_nodes = Vec::new();
+        let mut output_nodes = Vec::new();
+
+        for key in node_keys {
+            let node = &self.nodes[key];
+            let shape = match node.get_role() {
+                NodeRole::Input => {
+                    input_nodes.push(*key);
+                    "box"
+                },
+                NodeRole::Output => {
+                    output_nodes.push(*key);
+                    "doublecircle"
+                },
+                NodeRole::Hidden if node.is_modulatory() => "diamond",
+                NodeRole::Hidden => "circle",
+            };
+
+            //only non-default aggregations are labelled
+            let label = match node.get_aggregation() {
+                Aggregation::Sum => key.to_string(),
+                aggregation => format!("{}\\n{}", key, aggregation),
+            };
+            dot.push_str(&format!("    n{} [label=\"{}\", shape={}];\n", key, label, shape));
+        }
+
+        for (rank, nodes) in [("source", input_nodes), ("sink", output_nodes)].iter() {
+            if nodes.is_empty() { continue; }
+
+            let names: Vec<String> = nodes.iter().map(|key| format!("n{};", key)).collect();
+            dot.push_str(&format!("    {{ rank={}; {} }}\n", rank, names.join(" ")));
+        }
+
+        let mut connection_keys: Vec<&usize> = self.connections.keys().collect();
+        connection_keys.sort_unstable();
+
+        for key in connection_keys {
+            let connection = &self.connections[key];
+            let style = if connection.enabled { "solid" } else { "dashed" };
+
+            dot.push_str(&format!("    n{} -> n{} [label=\"{:.3}\", style={}];\n",
+                                  connection.from.get_innovation_number(), connection.to.get_innovation_number(),
+                                  connection.weight, style));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Default for Genome {
+    fn default() -> Self {
+        Genome::new()
+    }
 }
 
 impl PartialEq for Genome {
@@ -34,6 +129,7 @@ impl PartialEq for Genome {
             && self.connections.len() == other.connections.len()
             && self.nodes == other.nodes
             && self.connections == other.connections
+            && self.mutation_rates == other.mutation_rates
     }
 }
 
@@ -0,0 +1,146 @@
+use super::genome::Genome;
+use super::gene::Gene;
+use super::node_gene::NodeRole;
+use super::validation::GenomeViolation;
+use crate::aggregation_functions::Aggregation;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+//a genome flattened into the order the calculator runs it in
+//connections out of modulatory nodes are left out of incoming, they only matter for plasticity (which this doesn't do)
+pub(crate) struct NetworkLayout {
+    pub(crate) inputs: Vec<usize>, //node innovation numbers, position is the calculator's input index
+    pub(crate) hidden: Vec<usize>, //in evaluation order (innovation number order from new_recurrent)
+    pub(crate) outputs: Vec<usize>, //position is the calculator's output index
+    pub(crate) incoming: HashMap<usize, Vec<(usize, usize, f64)>>, //node -> enabled (connection, from node, weight)
+    pub(crate) aggregations: HashMap<usize, Aggregation>, //node -> how its incoming values are combined
+}
+
+impl NetworkLayout {
+    pub(crate) fn new(genome: &Genome) -> Result<Self, String> {
+        if let Some(violation) = genome.validate().first() {
+            return Err(format!("invalid genome: {}", violation));
+        }
+
+        let hidden = evaluation_order(genome)?.into_iter().filter(|node| genome.nodes[node].get_role() == NodeRole::Hidden).collect();
+        Ok(NetworkLayout::with_hidden(genome, hidden))
+    }
+
+    //for phenotypes that update every node at once (Ctrnn), so the genome can have cycles, connections that go
+    //backwards or to the node itself, and outputs feeding other nodes, everything else still has to be valid
+    pub(crate) fn new_recurrent(genome: &Genome) -> Result<Self, String> {
+        let violation = genome.validate().into_iter()
+            .find(|violation| !matches!(violation, GenomeViolation::NotFeedForward {..} | GenomeViolation::ConnectionToSelf {..}));
+        if let Some(violation) = violation {
+            return Err(format!("invalid genome: {}", violation));
+        }
+
+        let mut hidden: Vec<usize> = genome.nodes.values().filter(|node| node.get_role() == NodeRole::Hidden).map(|node| node.get_innovation_number()).collect();
+        hidden.sort_unstable();
+        Ok(NetworkLayout::with_hidden(genome, hidden))
+    }
+
+    fn with_hidden(genome: &Genome, hidden: Vec<usize>) -> Self {
+        let mut inputs: Vec<usize> = genome.nodes.values().filter(|node| node.get_role() == NodeRole::Input).map(|node| node.get_innovation_number()).collect();
+        let mut outputs: Vec<usize> = genome.nodes.values().filter(|node| node.get_role() == NodeRole::Output).map(|node| node.get_innovation_number()).collect();
+        inputs.sort_unstable();
+        outputs.sort_unstable();
+
+        let mut incoming: HashMap<usize, Vec<(usize, usize, f64)>> = HashMap::new();
+        for connection in genome.connections.values() {
+            if !connection.enabled || connection.from.is_modulatory() { continue; }
+
+            incoming.entry(connection.to.get_innovation_number()).or_default()
+                .push((connection.get_innovation_number(), connection.from.get_innovation_number(), connection.weight));
+        }
+        for connections in incoming.values_mut() {
+            connections.sort_unstable_by_key(|(con_num, _from, _weight)| *con_num);
+        }
+
+        let aggregations = genome.nodes.iter().map(|(key, node)| (*key, node.get_aggregation())).collect();
+
+        NetworkLayout {inputs, hidden, outputs, incoming, aggregations}
+    }
+
+    //hidden then output nodes, the ones that get calculated
+    pub(crate) fn calculated_nodes(&self) -> impl Iterator<Item=&usize> {
+        self.hidden.iter().chain(self.outputs.iter())
+    }
+
+    //nodes with a path to an output (outputs included), anything else doesn't change the result
+    pub(crate) fn needed_nodes(&self) -> HashSet<usize> {
+        let mut needed: HashSet<usize> = self.outputs.iter().copied().collect();
+        let mut to_visit: Vec<usize> = self.outputs.clone();
+
+        while let Some(node) = to_visit.pop() {
+            for (_con_num, from, _weight) in self.get_incoming(node) {
+                if needed.insert(*from) {
+                    to_visit.push(*from);
+                }
+            }
+        }
+
+        needed
+    }
+
+    pub(crate) fn get_aggregation(&self, node: usize) -> Aggregation {
+        self.aggregations[&node]
+    }
+
+    pub(crate) fn get_incoming(&self, node: usize) -> &[(usize, usize, f64)] {
+        match self.incoming.get(&node) {
+            None => &[],
+            Some(connections) => connections,
+        }
+    }
+}
+
+//hidden and output nodes in an order where every node comes after the nodes feeding it (enabled connections only),
+//ties go to the lower innovation number so the order is stable
+//errors on a cycle, or an output feeding another node (outputs are calculated after every hidden node)
+pub(crate) fn evaluation_order(genome: &Genome) -> Result<Vec<usize>, String> {
+    let mut waiting_on: HashMap<usize, usize> = HashMap::new(); //node -> number of calculated nodes feeding it that aren't done
+    let mut feeds: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    for node in genome.nodes.values().filter(|node| node.get_role() != NodeRole::Input) {
+        waiting_on.insert(node.get_innovation_number(), 0);
+    }
+
+    let mut connections: Vec<_> = genome.connections.values().filter(|connection| connection.enabled).collect();
+    connections.sort_unstable_by_key(|connection| connection.get_innovation_number());
+    for connection in connections {
+        let (from, to) = (connection.from.get_innovation_number(), connection.to.get_innovation_number());
+        if connection.from.get_role() == NodeRole::Output {
+            return Err(format!("connection {} goes out of output node {}", connection.get_innovation_number(), from));
+        }
+        if !waiting_on.contains_key(&from) || !waiting_on.contains_key(&to) {
+            continue; //from an input, or a node that isn't in the genome (see Genome::validate)
+        }
+
+        *waiting_on.get_mut(&to).unwrap() += 1;
+        feeds.entry(from).or_default().push(to);
+    }
+
+    let mut ready: BTreeSet<usize> = waiting_on.iter().filter(|(_node, count)| **count == 0).map(|(node, _count)| *node).collect();
+    let mut order = Vec::with_capacity(waiting_on.len());
+
+    while let Some(node) = ready.iter().next().copied() {
+        ready.remove(&node);
+        order.push(node);
+
+        for to in feeds.get(&node).map_or(&[][..], |feeds| feeds) {
+            let count = waiting_on.get_mut(to).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.insert(*to);
+            }
+        }
+    }
+
+    if order.len() < waiting_on.len() {
+        let mut in_cycle: Vec<usize> = waiting_on.iter().filter(|(_node, count)| **count > 0).map(|(node, _count)| *node).collect();
+        in_cycle.sort_unstable();
+        return Err(format!("can't order nodes {:?}, there's a cycle", in_cycle));
+    }
+
+    Ok(order)
+}
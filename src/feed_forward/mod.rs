@@ -1,8 +1,18 @@
 #[cfg(test)]
 pub(crate) mod tests;
 
-pub(crate) mod gene;
-pub(crate) mod node_gene;
-pub(crate) mod connection_gene;
-pub(crate) mod genome;
-pub(crate) mod calculator;
\ No newline at end of file
+pub mod gene;
+pub mod node_gene;
+pub mod connection_gene;
+pub mod plasticity;
+pub mod mutation_rates;
+pub mod genome;
+pub mod serialization;
+pub mod validation;
+pub(crate) mod calculator;
+pub(crate) mod layout;
+pub mod backprop;
+pub mod network;
+pub mod ctrnn;
+pub mod spiking;
+pub mod builder;
\ No newline at end of file
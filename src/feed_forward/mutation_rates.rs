@@ -0,0 +1,38 @@
+/* - Mutation rates -
+A genome's own probabilities (0 to 1) for the five main mutations, only used when the Neat is self-adaptive
+(neat::adaptive_mutation mutates and scales them, this is just the gene)
+written as `mutation_rates add_node=0.05 add_connection=0.1 random_weight=0.1 weight_shift=0.5 toggle_connection=0.02`
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MutationRates {
+    pub add_node: f64,
+    pub add_connection: f64,
+    pub random_weight: f64,
+    pub weight_shift: f64,
+    pub toggle_connection: f64,
+}
+
+impl MutationRates {
+    pub const NAMES: [&'static str; 5] = ["add_node", "add_connection", "random_weight", "weight_shift", "toggle_connection"];
+
+    pub fn new(add_node: f64, add_connection: f64, random_weight: f64, weight_shift: f64, toggle_connection: f64) -> Self {
+        MutationRates {add_node, add_connection, random_weight, weight_shift, toggle_connection}
+    }
+
+    //in NAMES order
+    pub fn to_array(&self) -> [f64; 5] {
+        [self.add_node, self.add_connection, self.random_weight, self.weight_shift, self.toggle_connection]
+    }
+
+    pub fn from_array(probabilities: [f64; 5]) -> Self {
+        MutationRates::new(probabilities[0], probabilities[1], probabilities[2], probabilities[3], probabilities[4])
+    }
+
+    //what went wrong, None if every probability is usable
+    pub fn check(&self) -> Option<String> {
+        MutationRates::NAMES.iter().zip(self.to_array().iter())
+            .find(|(_name, probability)| !(0.0..=1.0).contains(*probability))
+            .map(|(name, probability)| format!("{} probability has to be between 0 and 1, got {}", name, probability))
+    }
+}
@@ -0,0 +1,210 @@
+use super::genome::Genome;
+use super::node_gene::NodeGene;
+use super::connection_gene::ConnectionGene;
+use super::gene::Gene;
+use super::layout::NetworkLayout;
+use super::serialization::{ParseError, parse_gene_line, strip_comment, parse_fields, required_field, check_known_fields};
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use crate::output_decoder::{OutputDecoder, DecodedOutput};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+/* - Network -
+A genome baked into something that can be run on its own, it doesn't share anything with a Neat so it can be kept
+(or sent to another thread) after the population is gone
+    Network::new(&genome, activation, with_bias) - with_bias works like Backprop/the C export, the first input node
+        is fixed at 1.0 and left out of the inputs (Neat::get_client_network turns it on)
+    run(&inputs) - same outputs as Calculator::run/Neat::use_client
+    run_decoded(&inputs) - the outputs put through the network's decoder (raw unless set, see output_decoder)
+    to_genome() - the genome back, disabled connections are left out
+hebbian rules aren't kept, a network always runs with the genetic weights (see plasticity)
+text format is a `network` line followed by the genome's genes
+    network activation=<name> bias=<bool> decoder=<decoder>  (decoder is left out when it's raw)
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    activation: Activation,
+    with_bias: bool,
+    decoder: OutputDecoder,
+
+    nodes: Vec<NodeGene>, //innovation number order, for to_genome
+    connections: Vec<(usize, usize, usize, f64)>, //enabled (connection, from node, to node, weight), innovation number order
+
+    number_of_input_nodes: usize, //bias included, their values come first
+    calculated: Vec<(Aggregation, Vec<(usize, f64)>)>, //evaluation order, the incoming (value index, weight) of each node
+    output_positions: Vec<usize>, //value index of every output, in output order
+}
+
+impl Network {
+    pub fn new(genome: &Genome, activation: Activation, with_bias: bool) -> Result<Self, String> {
+        let layout = NetworkLayout::new(genome)?;
+        if with_bias && layout.inputs.is_empty() {
+            return Err(String::from("genome needs a bias node"));
+        }
+
+        //hidden nodes that can't reach an output are left out of the evaluation
+        let needed = layout.needed_nodes();
+        let mut value_indexes: HashMap<usize, usize> = HashMap::new();
+        for node in layout.inputs.iter().chain(layout.calculated_nodes().filter(|node| needed.contains(node))) {
+            value_indexes.insert(*node, value_indexes.len());
+        }
+
+        let calculated = layout.calculated_nodes().filter(|node| needed.contains(node)).map(|node| {
+            let incoming = layout.get_incoming(*node).iter().map(|(_con_num, from, weight)| (value_indexes[from], *weight)).collect();
+            (layout.get_aggregation(*node), incoming)
+        }).collect();
+
+        let mut nodes: Vec<NodeGene> = genome.nodes.values().map(|node| NodeGene::clone(node)).collect();
+        nodes.sort_unstable_by_key(|node| node.get_innovation_number());
+
+        let mut connections: Vec<(usize, usize, usize, f64)> = genome.connections.values()
+            .filter(|connection| connection.enabled)
+            .map(|connection| (connection.get_innovation_number(), connection.from.get_innovation_number(),
+                               connection.to.get_innovation_number(), connection.weight))
+            .collect();
+        connections.sort_unstable_by_key(|(con_num, _from, _to, _weight)| *con_num);
+
+        Ok(Network {
+            activation,
+            with_bias,
+            decoder: OutputDecoder::Raw,
+            nodes,
+            connections,
+            number_of_input_nodes: layout.inputs.len(),
+            calculated,
+            output_positions: layout.outputs.iter().map(|node| value_indexes[node]).collect(),
+        })
+    }
+
+    pub fn run(&self, inputs: &[f64]) -> Vec<f64> {
+        if inputs.len() != self.get_number_of_inputs() {
+            panic!("expected {} inputs, got {}", self.get_number_of_inputs(), inputs.len());
+        }
+
+        let mut values: Vec<f64> = Vec::with_capacity(self.number_of_input_nodes + self.calculated.len());
+        if self.with_bias {
+            values.push(1.0);
+        }
+        values.extend_from_slice(inputs);
+
+        for (aggregation, incoming) in &self.calculated {
+            let weighted_values: Vec<f64> = incoming.iter().map(|(from, weight)| weight * values[*from]).collect();
+            values.push(self.activation.apply(aggregation.apply(&weighted_values)));
+        }
+
+        self.output_positions.iter().map(|position| values[*position]).collect()
+    }
+
+    pub fn run_decoded(&self, inputs: &[f64]) -> DecodedOutput {
+        self.decoder.decode(&self.run(inputs))
+    }
+
+    //what run takes, the bias isn't counted
+    pub fn get_number_of_inputs(&self) -> usize {
+        if self.with_bias { self.number_of_input_nodes - 1 } else { self.number_of_input_nodes }
+    }
+
+    pub fn get_number_of_outputs(&self) -> usize {
+        self.output_positions.len()
+    }
+
+    pub fn get_activation(&self) -> Activation {
+        self.activation
+    }
+
+    pub fn has_bias(&self) -> bool {
+        self.with_bias
+    }
+
+    pub fn get_decoder(&self) -> &OutputDecoder {
+        &self.decoder
+    }
+
+    pub fn set_decoder(&mut self, decoder: OutputDecoder) -> Result<(), String> {
+        decoder.check(self.get_number_of_outputs())?;
+        self.decoder = decoder;
+        Ok(())
+    }
+
+    pub fn to_genome(&self) -> Genome {
+        let mut genome = Genome::new();
+        for node in &self.nodes {
+            genome.add_node(Rc::new(node.clone()));
+        }
+
+        for (con_num, from, to, weight) in &self.connections {
+            let mut connection = ConnectionGene::new(*con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[to]));
+            connection.weight = *weight;
+            genome.add_connection(connection);
+        }
+
+        genome
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("network activation={} bias={}", self.activation, self.with_bias);
+        if self.decoder != OutputDecoder::Raw {
+            text.push_str(&format!(" decoder={}", self.decoder));
+        }
+        text.push('\n');
+        self.to_genome().write_genes(&mut text);
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Network, ParseError> {
+        let mut header: Option<(Activation, bool, OutputDecoder)> = None;
+        let mut gene_lines = Vec::new();
+
+        for (line_i, line) in text.lines().enumerate() {
+            let line_num = line_i + 1;
+            let mut tokens = strip_comment(line).split_whitespace();
+
+            match tokens.next() {
+                None => continue,
+                Some("network") => {
+                    if header.is_some() {
+                        return Err(ParseError::new(line_num, String::from("more than one network line")));
+                    }
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["activation", "bias", "decoder"])?;
+                    let activation = required_field::<String>(line_num, &fields, "activation")?.parse()
+                        .map_err(|message| ParseError::new(line_num, message))?;
+                    let decoder = match fields.get("decoder") {
+                        None => OutputDecoder::Raw,
+                        Some(value) => value.parse().map_err(|message| ParseError::new(line_num, message))?,
+                    };
+                    header = Some((activation, required_field(line_num, &fields, "bias")?, decoder));
+                },
+                Some(_) => {
+                    if let Some(gene_line) = parse_gene_line(line_num, line)? {
+                        gene_lines.push((line_num, gene_line));
+                    }
+                },
+            }
+        }
+
+        let (activation, with_bias, decoder) = match header {
+            None => return Err(ParseError::new(0, String::from("missing 'network activation=<name> bias=<bool>' line"))),
+            Some(header) => header,
+        };
+        let genome = Genome::from_gene_lines(gene_lines, &HashMap::new())?;
+
+        let mut network = Network::new(&genome, activation, with_bias).map_err(|message| ParseError::new(0, message))?;
+        network.set_decoder(decoder).map_err(|message| ParseError::new(0, message))?;
+        Ok(network)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Network> {
+        let text = fs::read_to_string(path)?;
+        Network::from_text(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
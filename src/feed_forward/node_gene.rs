@@ -1,17 +1,110 @@
 use super::gene;
+use crate::aggregation_functions::Aggregation;
+use super::spiking::NeuronParameters;
+use std::fmt;
+use std::str::FromStr;
+
+//where new input/output nodes go, hidden nodes are somewhere in between
+pub const INPUT_X: f64 = 0.1;
+pub const OUTPUT_X: f64 = 0.9;
+//how fast a node reacts in a CTRNN (see ctrnn), 1 unless mutated, never below MIN_TIME_CONSTANT
+pub const DEFAULT_TIME_CONSTANT: f64 = 1.0;
+pub const MIN_TIME_CONSTANT: f64 = 0.01;
+
+//what a node is for, the calculator takes inputs from input nodes and gives outputs from output nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeRole {
+    Input,
+    Hidden,
+    Output,
+}
+
+impl NodeRole {
+    //the role older genomes (without explicit roles) had, from the node's x
+    pub fn from_x(x: f64) -> Self {
+        if x <= INPUT_X {
+            NodeRole::Input
+        } else if x >= OUTPUT_X {
+            NodeRole::Output
+        } else {
+            NodeRole::Hidden
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeRole::Input => "input",
+            NodeRole::Hidden => "hidden",
+            NodeRole::Output => "output",
+        }
+    }
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        [NodeRole::Input, NodeRole::Hidden, NodeRole::Output].iter().copied().find(|role| role.name() == text.trim())
+            .ok_or_else(|| format!("unknown node role '{}'", text.trim()))
+    }
+}
 
 //Nodes as used in a genome
 //Nodes don't really hold unique value (for two nodes in the same position) so we can reference the same node from everywhere
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NodeGene {
     innovation_number: usize,
-    x: f64, // 0.1 is input, 0.9 is output, everything is in between
+    x: f64, //connections go from a smaller x to a bigger one, inputs are at INPUT_X and outputs at OUTPUT_X
     y: f64,
+    role: NodeRole,
+    aggregation: Aggregation, //how the calculator combines the node's inputs, nodes in the node bank are always sum
+    modulatory: bool, //only hidden nodes, their connections gate hebbian learning instead of feeding values (see plasticity)
+    time_constant: f64, //only used by a CTRNN
+    neuron: NeuronParameters, //only used by a spiking network
 }
 
 impl NodeGene {
+    //the role comes from x (see NodeRole::from_x)
     pub fn new(innovation_number: usize, x: f64, y: f64) -> Self {
-        NodeGene {innovation_number, x, y}
+        NodeGene::new_with_role(innovation_number, x, y, NodeRole::from_x(x))
+    }
+
+    pub fn new_with_role(innovation_number: usize, x: f64, y: f64, role: NodeRole) -> Self {
+        NodeGene {innovation_number, x, y, role, aggregation: Aggregation::Sum, modulatory: false, time_constant: DEFAULT_TIME_CONSTANT,
+                   neuron: NeuronParameters::default()}
+    }
+
+    //a copy of this node with a different aggregation
+    //the copy isn't shared, a genome using it has to use it for its connections too (see Genome::replace_node)
+    pub fn with_aggregation(&self, aggregation: Aggregation) -> Self {
+        NodeGene {aggregation, ..self.clone()}
+    }
+
+    //a copy of this node that is/isn't modulatory, not shared like with_aggregation
+    pub fn with_modulatory(&self, modulatory: bool) -> Self {
+        NodeGene {modulatory, ..self.clone()}
+    }
+
+    //a copy of this node with a different time constant, not shared like with_aggregation
+    pub fn with_time_constant(&self, time_constant: f64) -> Self {
+        NodeGene {time_constant, ..self.clone()}
+    }
+
+    //a copy of this node with different spiking neuron parameters, not shared like with_aggregation
+    pub fn with_neuron(&self, neuron: NeuronParameters) -> Self {
+        NodeGene {neuron, ..self.clone()}
+    }
+
+    //a copy of this node with the other node's genome-only settings (aggregation, modulatory, time constant, neuron)
+    pub(crate) fn with_settings_of(&self, other: &NodeGene) -> Self {
+        NodeGene {aggregation: other.aggregation, modulatory: other.modulatory, time_constant: other.time_constant,
+                  neuron: other.neuron, ..self.clone()}
     }
 
     pub fn get_x(&self) -> f64 {
@@ -21,6 +114,26 @@ impl NodeGene {
     pub fn get_y(&self) -> f64 {
         self.y
     }
+
+    pub fn get_role(&self) -> NodeRole {
+        self.role
+    }
+
+    pub fn get_aggregation(&self) -> Aggregation {
+        self.aggregation
+    }
+
+    pub fn is_modulatory(&self) -> bool {
+        self.modulatory
+    }
+
+    pub fn get_time_constant(&self) -> f64 {
+        self.time_constant
+    }
+
+    pub fn get_neuron(&self) -> NeuronParameters {
+        self.neuron
+    }
 }
 
 impl gene::Gene for NodeGene {
@@ -36,6 +149,8 @@ impl gene::Gene for NodeGene {
 impl PartialEq for NodeGene {
     fn eq(&self, other: &Self) -> bool {
         self.innovation_number == other.innovation_number && self.x == other.x && self.y == other.y
+            && self.role == other.role && self.aggregation == other.aggregation && self.modulatory == other.modulatory
+            && self.time_constant == other.time_constant && self.neuron == other.neuron
     }
 }
 
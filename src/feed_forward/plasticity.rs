@@ -0,0 +1,75 @@
+use std::fmt;
+use std::str::FromStr;
+
+/* - Hebbian plasticity -
+A connection with a hebbian rule changes its weight every time the calculator runs, from the activations on each end
+    weight += learning_rate * (a*pre*post + b*pre + c*post + d)
+pre is the from node's output and post the to node's output for that run
+the rule is part of the connection gene so it evolves (mutate_probability_hebbian), a connection starts with no rule (all 0)
+Calculator::reset_state puts the genetic weights back, eg. between episodes
+connections out of a modulatory node (see NodeGene::is_modulatory) don't feed values, they gate learning instead
+    m = sum of the modulatory outputs * weights coming into a node
+    the node's incoming connections change by tanh(m / 2) * the rule above, nodes without modulatory inputs aren't gated
+a Network (and the exports) use the genetic weights, they don't learn
+written as a:b:c:d:learning_rate, eg. `hebbian=1:0:0:0:0.1` on a connection line
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HebbianRule {
+    pub a: f64, //correlation (pre * post)
+    pub b: f64, //presynaptic
+    pub c: f64, //postsynaptic
+    pub d: f64, //constant
+    pub learning_rate: f64,
+}
+
+impl HebbianRule {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, learning_rate: f64) -> Self {
+        HebbianRule {a, b, c, d, learning_rate}
+    }
+
+    //whether the rule ever changes the weight
+    pub fn is_plastic(&self) -> bool {
+        self.learning_rate != 0.0 && (self.a != 0.0 || self.b != 0.0 || self.c != 0.0 || self.d != 0.0)
+    }
+
+    pub fn weight_change(&self, pre: f64, post: f64) -> f64 {
+        self.learning_rate * (self.a * pre * post + self.b * pre + self.c * post + self.d)
+    }
+
+    pub(crate) fn get_coefficient_mut(&mut self, index: usize) -> &mut f64 {
+        match index {
+            0 => &mut self.a,
+            1 => &mut self.b,
+            2 => &mut self.c,
+            3 => &mut self.d,
+            _ => &mut self.learning_rate,
+        }
+    }
+}
+
+//how much a node's modulatory input m scales its connections' weight changes
+pub fn modulation_factor(modulation: f64) -> f64 {
+    (modulation / 2.0).tanh()
+}
+
+impl fmt::Display for HebbianRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}:{}:{}", self.a, self.b, self.c, self.d, self.learning_rate)
+    }
+}
+
+impl FromStr for HebbianRule {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let values = text.trim().split(':').map(|value| value.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| format!("expected a:b:c:d:learning_rate, got '{}'", text.trim()))?;
+
+        match values.as_slice() {
+            [a, b, c, d, learning_rate] => Ok(HebbianRule::new(*a, *b, *c, *d, *learning_rate)),
+            _ => Err(format!("expected 5 values (a:b:c:d:learning_rate), got {}", values.len())),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use super::genome::Genome;
+use super::node_gene::{NodeGene, NodeRole, DEFAULT_TIME_CONSTANT};
+use super::connection_gene::ConnectionGene;
+use super::gene::Gene;
+use super::plasticity::HebbianRule;
+use super::mutation_rates::MutationRates;
+use super::spiking::NeuronParameters;
+use crate::aggregation_functions::Aggregation;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/* - Genome text format -
+one gene per line, '#' starts a comment
+    node <inv_num> x=<x> y=<y> role=<role> aggregation=<name> modulatory=<bool> time_constant=<tau> neuron=<a:b:c:d:threshold:tau>
+        (role is left out when it's the one x gives, see NodeRole::from_x, the rest are left out when they're the
+        default: sum, false, DEFAULT_TIME_CONSTANT and NeuronParameters::default())
+    connection <inv_num> from=<node_inv_num> to=<node_inv_num> weight=<weight> enabled=<bool> hebbian=<a:b:c:d:rate>
+        (hebbian is left out when the connection doesn't have a rule, see plasticity)
+    mutation_rates add_node=<p> add_connection=<p> random_weight=<p> weight_shift=<p> toggle_connection=<p>
+        (only when the genome carries its own mutation probabilities, see mutation_rates)
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize, //1-based, 0 if the error isn't tied to a line
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, message: String) -> Self {
+        ParseError {line, message}
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+//a single `node`/`connection` line, before the nodes are linked together
+pub(crate) enum GeneLine {
+    Node(NodeGene),
+    Connection {innovation_number: usize, from: usize, to: usize, weight: f64, enabled: bool, hebbian: HebbianRule},
+    MutationRates(MutationRates),
+}
+
+impl Genome {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.write_genes(&mut text);
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Genome, ParseError> {
+        let mut gene_lines = Vec::new();
+
+        for (line_i, line) in text.lines().enumerate() {
+            if let Some(gene_line) = parse_gene_line(line_i + 1, line)? {
+                gene_lines.push((line_i + 1, gene_line));
+            }
+        }
+
+        Genome::from_gene_lines(gene_lines, &HashMap::new())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Genome> {
+        let text = fs::read_to_string(path)?;
+        Genome::from_text(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    //nodes are written in innovation number order so the output is stable
+    pub(crate) fn write_genes(&self, text: &mut String) {
+        if let Some(mutation_rates) = &self.mutation_rates {
+            text.push_str("mutation_rates");
+            for (name, probability) in MutationRates::NAMES.iter().zip(mutation_rates.to_array().iter()) {
+                text.push_str(&format!(" {}={}", name, probability));
+            }
+            text.push('\n');
+        }
+
+        let mut node_keys: Vec<&usize> = self.nodes.keys().collect();
+        node_keys.sort_unstable();
+        for key in node_keys {
+            let node = &self.nodes[key];
+            text.push_str(&format!("node {} x={} y={}", node.get_innovation_number(), node.get_x(), node.get_y()));
+            if node.get_role() != NodeRole::from_x(node.get_x()) {
+                text.push_str(&format!(" role={}", node.get_role()));
+            }
+            if node.get_aggregation() != Aggregation::Sum {
+                text.push_str(&format!(" aggregation={}", node.get_aggregation()));
+            }
+            if node.is_modulatory() {
+                text.push_str(" modulatory=true");
+            }
+            if node.get_time_constant() != DEFAULT_TIME_CONSTANT {
+                text.push_str(&format!(" time_constant={}", node.get_time_constant()));
+            }
+            if node.get_neuron() != NeuronParameters::default() {
+                text.push_str(&format!(" neuron={}", node.get_neuron()));
+            }
+            text.push('\n');
+        }
+
+        let mut connection_keys: Vec<&usize> = self.connections.keys().collect();
+        connection_keys.sort_unstable();
+        for key in connection_keys {
+            let connection = &self.connections[key];
+            text.push_str(&format!("connection {} from={} to={} weight={} enabled={}",
+                                   connection.get_innovation_number(), connection.from.get_innovation_number(),
+                                   connection.to.get_innovation_number(), connection.weight, connection.enabled));
+            if connection.hebbian != HebbianRule::default() {
+                text.push_str(&format!(" hebbian={}", connection.hebbian));
+            }
+            text.push('\n');
+        }
+    }
+
+    //node_bank lets a caller (eg. a checkpoint) make nodes refer to its existing shared nodes
+    pub(crate) fn from_gene_lines(gene_lines: Vec<(usize, GeneLine)>, node_bank: &HashMap<usize, Rc<NodeGene>>) -> Result<Genome, ParseError> {
+        let mut genome = Genome::new();
+        let mut connections = Vec::new();
+
+        for (line, gene_line) in gene_lines {
+            match gene_line {
+                GeneLine::Node(node) => {
+                    if genome.nodes.contains_key(&node.get_innovation_number()) {
+                        return Err(ParseError::new(line, format!("duplicate node {}", node.get_innovation_number())));
+                    }
+
+                    match node_bank.get(&node.get_innovation_number()) {
+                        Some(bank_node) if bank_node.get_role() != node.get_role() => {
+                            return Err(ParseError::new(line, format!("node {} has role {} here but {} in the node bank",
+                                                                     node.get_innovation_number(), node.get_role(), bank_node.get_role())));
+                        },
+                        Some(bank_node) if bank_node.with_settings_of(&node) == **bank_node => genome.add_node(Rc::clone(bank_node)),
+                        Some(bank_node) => genome.add_node(Rc::new(bank_node.with_settings_of(&node))),
+                        None => genome.add_node(Rc::new(node)),
+                    }
+                },
+                GeneLine::Connection {innovation_number, from, to, weight, enabled, hebbian} => connections.push((line, innovation_number, from, to, weight, enabled, hebbian)),
+                GeneLine::MutationRates(mutation_rates) => {
+                    if genome.mutation_rates.is_some() {
+                        return Err(ParseError::new(line, String::from("duplicate mutation_rates")));
+                    }
+                    genome.mutation_rates = Some(mutation_rates);
+                },
+            }
+        }
+
+        for (line, innovation_number, from, to, weight, enabled, hebbian) in connections {
+            let (from_node, to_node) = match (genome.nodes.get(&from), genome.nodes.get(&to)) {
+                (Some(from_node), Some(to_node)) => (Rc::clone(from_node), Rc::clone(to_node)),
+                _ => return Err(ParseError::new(line, format!("connection {} uses a node that isn't in the genome", innovation_number))),
+            };
+
+            if genome.connections.contains_key(&innovation_number) {
+                return Err(ParseError::new(line, format!("duplicate connection {}", innovation_number)));
+            }
+
+            let mut connection = ConnectionGene::new(innovation_number, from_node, to_node);
+            connection.weight = weight;
+            connection.enabled = enabled;
+            connection.hebbian = hebbian;
+            genome.add_connection(connection);
+        }
+
+        if let Some(violation) = genome.validate().first() {
+            return Err(ParseError::new(0, format!("invalid genome: {}", violation)));
+        }
+
+        Ok(genome)
+    }
+}
+
+//returns None for blank/comment lines
+pub(crate) fn parse_gene_line(line: usize, text: &str) -> Result<Option<GeneLine>, ParseError> {
+    let text = strip_comment(text);
+    let mut tokens = text.split_whitespace();
+
+    let kind = match tokens.next() {
+        None => return Ok(None),
+        Some(kind) => kind,
+    };
+
+    //the only line without an innovation number
+    if kind == "mutation_rates" {
+        let fields = parse_fields(line, tokens)?;
+        check_known_fields(line, &fields, &MutationRates::NAMES)?;
+
+        let mut probabilities = [0.0; 5];
+        for (probability, name) in probabilities.iter_mut().zip(MutationRates::NAMES.iter()) {
+            *probability = required_field(line, &fields, name)?;
+        }
+        let mutation_rates = MutationRates::from_array(probabilities);
+        if let Some(problem) = mutation_rates.check() {
+            return Err(ParseError::new(line, problem));
+        }
+        return Ok(Some(GeneLine::MutationRates(mutation_rates)));
+    }
+
+    let innovation_number: usize = match tokens.next() {
+        None => return Err(ParseError::new(line, format!("missing innovation number after '{}'", kind))),
+        Some(token) => parse_value(line, "innovation number", token)?,
+    };
+
+    let fields = parse_fields(line, tokens)?;
+
+    match kind {
+        "node" => {
+            let x = required_field(line, &fields, "x")?;
+            let y = required_field(line, &fields, "y")?;
+            let role = match fields.get("role") {
+                None => NodeRole::from_x(x),
+                Some(value) => value.parse().map_err(|message| ParseError::new(line, message))?,
+            };
+            let aggregation = match fields.get("aggregation") {
+                None => Aggregation::Sum,
+                Some(value) => value.parse().map_err(|message| ParseError::new(line, message))?,
+            };
+            let modulatory = match fields.get("modulatory") {
+                None => false,
+                Some(value) => parse_value(line, "modulatory", value)?,
+            };
+            let time_constant = match fields.get("time_constant") {
+                None => DEFAULT_TIME_CONSTANT,
+                Some(value) => parse_value(line, "time_constant", value)?,
+            };
+            let neuron = match fields.get("neuron") {
+                None => NeuronParameters::default(),
+                Some(value) => value.parse().map_err(|message| ParseError::new(line, message))?,
+            };
+            check_known_fields(line, &fields, &["x", "y", "role", "aggregation", "modulatory", "time_constant", "neuron"])?;
+
+            let node = NodeGene::new_with_role(innovation_number, x, y, role).with_aggregation(aggregation)
+                .with_modulatory(modulatory).with_time_constant(time_constant).with_neuron(neuron);
+            Ok(Some(GeneLine::Node(node)))
+        },
+        "connection" => {
+            let from = required_field(line, &fields, "from")?;
+            let to = required_field(line, &fields, "to")?;
+            let weight = required_field(line, &fields, "weight")?;
+            let enabled = match fields.get("enabled") {
+                None => true,
+                Some(value) => parse_value(line, "enabled", value)?,
+            };
+            let hebbian = match fields.get("hebbian") {
+                None => HebbianRule::default(),
+                Some(value) => value.parse().map_err(|message| ParseError::new(line, message))?,
+            };
+            check_known_fields(line, &fields, &["from", "to", "weight", "enabled", "hebbian"])?;
+
+            Ok(Some(GeneLine::Connection {innovation_number, from, to, weight, enabled, hebbian}))
+        },
+        _ => Err(ParseError::new(line, format!("unknown gene kind '{}'", kind))),
+    }
+}
+
+pub(crate) fn strip_comment(text: &str) -> &str {
+    match text.find('#') {
+        None => text,
+        Some(index) => &text[..index],
+    }
+}
+
+//turns key=value tokens into a map
+pub(crate) fn parse_fields<'a, I>(line: usize, tokens: I) -> Result<HashMap<&'a str, &'a str>, ParseError> where
+    I: Iterator<Item=&'a str> {
+    let mut fields = HashMap::new();
+
+    for token in tokens {
+        match token.split_once('=') {
+            None => return Err(ParseError::new(line, format!("expected key=value, got '{}'", token))),
+            Some((key, value)) => {
+                if fields.insert(key, value).is_some() {
+                    return Err(ParseError::new(line, format!("'{}' given twice", key)));
+                }
+            },
+        }
+    }
+
+    Ok(fields)
+}
+
+pub(crate) fn required_field<T: FromStr>(line: usize, fields: &HashMap<&str, &str>, key: &str) -> Result<T, ParseError> {
+    match fields.get(key) {
+        None => Err(ParseError::new(line, format!("missing '{}'", key))),
+        Some(value) => parse_value(line, key, value),
+    }
+}
+
+pub(crate) fn check_known_fields(line: usize, fields: &HashMap<&str, &str>, known: &[&str]) -> Result<(), ParseError> {
+    for key in fields.keys() {
+        if !known.contains(key) {
+            return Err(ParseError::new(line, format!("unknown field '{}'", key)));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn parse_value<T: FromStr>(line: usize, name: &str, value: &str) -> Result<T, ParseError> {
+    value.parse::<T>().map_err(|_| ParseError::new(line, format!("bad value '{}' for {}", value, name)))
+}
@@ -0,0 +1,311 @@
+use super::genome::Genome;
+use super::layout::NetworkLayout;
+use super::node_gene::MIN_TIME_CONSTANT;
+use crate::aggregation_functions::Aggregation;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/* - Spiking network -
+A genome run as spiking neurons, inputs are turned into spike trains and the outputs are how many times each output
+node spiked
+    SpikingNetwork::new(&genome, model, encoding, with_bias) - with_bias works like Network, the bias is an input of 1.0
+    run(&inputs, dt, steps) - steps steps of dt (ms) with the inputs held, returns each output's spike count
+    reset() - every neuron back to its resting state
+every step a hidden/output node takes in current = aggregation(weight * spiked) from the nodes feeding it
+(1 if that node spiked this step, nodes are stepped in evaluation order), then
+    lif - v = v * (1 - dt / membrane_tau) + current, spikes and goes back to 0 when v >= threshold
+    izhikevich - v += dt * (0.04v^2 + 5v + 140 - u + IZHIKEVICH_CURRENT_SCALE * current), u += dt * a * (b*v - u),
+        spikes when v >= 30 then v = c and u += d
+each node has its own NeuronParameters (NodeGene::get_neuron), inherited and mutated (mutate_probability_neuron)
+connections out of modulatory nodes are left out, like in a Network
+ */
+
+//izhikevich neurons need a current of about 10 to fire, weights are around 1
+pub const IZHIKEVICH_CURRENT_SCALE: f64 = 20.0;
+const IZHIKEVICH_SPIKE_POTENTIAL: f64 = 30.0;
+pub const MIN_THRESHOLD: f64 = 0.01;
+
+//a node's neuron, lif uses threshold and membrane_tau, izhikevich uses a, b, c and d
+//written as a:b:c:d:threshold:membrane_tau, eg. `neuron=0.02:0.2:-65:8:1:10` on a node line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeuronParameters {
+    pub a: f64, //how fast the recovery variable u recovers
+    pub b: f64, //how much u follows v
+    pub c: f64, //v after a spike
+    pub d: f64, //how much u goes up after a spike
+    pub threshold: f64,
+    pub membrane_tau: f64, //how fast v leaks (ms)
+}
+
+impl NeuronParameters {
+    //how far mutate_neuron shifts each parameter, times a sample of the weight shift distribution
+    pub(crate) const SHIFT_SCALES: [f64; 6] = [0.01, 0.05, 5.0, 2.0, 0.2, 2.0];
+
+    pub fn get_mut(&mut self, index: usize) -> &mut f64 {
+        match index {
+            0 => &mut self.a,
+            1 => &mut self.b,
+            2 => &mut self.c,
+            3 => &mut self.d,
+            4 => &mut self.threshold,
+            _ => &mut self.membrane_tau,
+        }
+    }
+
+    //what went wrong, None if the parameters can be run
+    pub fn check(&self) -> Option<String> {
+        if self.threshold.is_nan() || self.threshold < MIN_THRESHOLD {
+            return Some(format!("threshold {} is below {}", self.threshold, MIN_THRESHOLD));
+        }
+        if self.membrane_tau.is_nan() || self.membrane_tau < MIN_TIME_CONSTANT {
+            return Some(format!("membrane_tau {} is below {}", self.membrane_tau, MIN_TIME_CONSTANT));
+        }
+        None
+    }
+
+    //keeps the parameters check would complain about in range
+    pub(crate) fn clamped(&self) -> Self {
+        NeuronParameters {
+            threshold: self.threshold.max(MIN_THRESHOLD),
+            membrane_tau: self.membrane_tau.max(MIN_TIME_CONSTANT),
+            ..*self
+        }
+    }
+}
+
+//izhikevich's regular spiking neuron, a lif neuron that leaks over 10ms
+impl Default for NeuronParameters {
+    fn default() -> Self {
+        NeuronParameters {a: 0.02, b: 0.2, c: -65.0, d: 8.0, threshold: 1.0, membrane_tau: 10.0}
+    }
+}
+
+impl fmt::Display for NeuronParameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}:{}:{}:{}", self.a, self.b, self.c, self.d, self.threshold, self.membrane_tau)
+    }
+}
+
+impl FromStr for NeuronParameters {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let values = text.trim().split(':').map(|value| value.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| format!("expected a:b:c:d:threshold:membrane_tau, got '{}'", text.trim()))?;
+
+        match values.as_slice() {
+            [a, b, c, d, threshold, membrane_tau] => Ok(NeuronParameters {a: *a, b: *b, c: *c, d: *d, threshold: *threshold, membrane_tau: *membrane_tau}),
+            _ => Err(format!("expected 6 values (a:b:c:d:threshold:membrane_tau), got {}", values.len())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NeuronModel {
+    LeakyIntegrateAndFire,
+    Izhikevich,
+}
+
+impl NeuronModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NeuronModel::LeakyIntegrateAndFire => "lif",
+            NeuronModel::Izhikevich => "izhikevich",
+        }
+    }
+}
+
+impl fmt::Display for NeuronModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for NeuronModel {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        [NeuronModel::LeakyIntegrateAndFire, NeuronModel::Izhikevich].iter().copied().find(|model| model.name() == text.trim())
+            .ok_or_else(|| format!("unknown neuron model '{}'", text.trim()))
+    }
+}
+
+//how an input value (0 to 1, clamped) becomes spikes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEncoding {
+    //evenly spaced spikes, value * max_rate of them per ms
+    Rate {max_rate: f64},
+    //one spike per run, the bigger the value the earlier, values <= 0 don't spike
+    Temporal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpikingNetwork {
+    model: NeuronModel,
+    encoding: InputEncoding,
+    with_bias: bool,
+
+    number_of_input_nodes: usize, //bias included, they come first in spiked
+    nodes: Vec<SpikingNode>, //hidden (evaluation order) then output nodes, spiked[number_of_input_nodes + i] is nodes[i]
+    output_positions: Vec<usize>, //index in nodes of every output, in output order
+
+    spiked: Vec<bool>, //this step
+    rate_phases: Vec<f64>, //rate encoding, how close each input is to its next spike
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SpikingNode {
+    aggregation: Aggregation,
+    parameters: NeuronParameters,
+    incoming: Vec<(usize, f64)>, //(index in spiked, weight)
+    v: f64,
+    u: f64,
+}
+
+impl SpikingNode {
+    fn reset(&mut self, model: NeuronModel) {
+        match model {
+            NeuronModel::LeakyIntegrateAndFire => {
+                self.v = 0.0;
+                self.u = 0.0;
+            },
+            NeuronModel::Izhikevich => {
+                self.v = self.parameters.c;
+                self.u = self.parameters.b * self.parameters.c;
+            },
+        }
+    }
+
+    //returns whether the node spiked
+    fn step(&mut self, model: NeuronModel, current: f64, dt: f64) -> bool {
+        let parameters = self.parameters;
+        match model {
+            NeuronModel::LeakyIntegrateAndFire => {
+                self.v = self.v * (1.0 - dt / parameters.membrane_tau).max(0.0) + current;
+                if self.v >= parameters.threshold {
+                    self.v = 0.0;
+                    return true;
+                }
+            },
+            NeuronModel::Izhikevich => {
+                let (v, u) = (self.v, self.u);
+                self.v += dt * (0.04 * v * v + 5.0 * v + 140.0 - u + IZHIKEVICH_CURRENT_SCALE * current);
+                self.u += dt * parameters.a * (parameters.b * v - u);
+                if self.v >= IZHIKEVICH_SPIKE_POTENTIAL {
+                    self.v = parameters.c;
+                    self.u += parameters.d;
+                    return true;
+                }
+            },
+        }
+        false
+    }
+}
+
+impl SpikingNetwork {
+    pub fn new(genome: &Genome, model: NeuronModel, encoding: InputEncoding, with_bias: bool) -> Result<Self, String> {
+        let layout = NetworkLayout::new(genome)?;
+        if with_bias && layout.inputs.is_empty() {
+            return Err(String::from("genome needs a bias node"));
+        }
+
+        let mut spike_indexes: HashMap<usize, usize> = HashMap::new();
+        for node in layout.inputs.iter().chain(layout.calculated_nodes()) {
+            spike_indexes.insert(*node, spike_indexes.len());
+        }
+
+        let nodes: Vec<SpikingNode> = layout.calculated_nodes().map(|node| SpikingNode {
+            aggregation: layout.get_aggregation(*node),
+            parameters: genome.nodes[node].get_neuron(),
+            incoming: layout.get_incoming(*node).iter().map(|(_con_num, from, weight)| (spike_indexes[from], *weight)).collect(),
+            v: 0.0,
+            u: 0.0,
+        }).collect();
+
+        let mut network = SpikingNetwork {
+            model,
+            encoding,
+            with_bias,
+            number_of_input_nodes: layout.inputs.len(),
+            output_positions: layout.outputs.iter().map(|node| spike_indexes[node] - layout.inputs.len()).collect(),
+            spiked: vec![false; spike_indexes.len()],
+            rate_phases: vec![0.0; layout.inputs.len()],
+            nodes,
+        };
+        network.reset();
+        Ok(network)
+    }
+
+    //inputs are encoded fresh every run, the neurons carry on from the last run unless reset
+    pub fn run(&mut self, inputs: &[f64], dt: f64, steps: usize) -> Vec<usize> {
+        if inputs.len() != self.get_number_of_inputs() {
+            panic!("expected {} inputs, got {}", self.get_number_of_inputs(), inputs.len());
+        }
+
+        let mut values: Vec<f64> = Vec::with_capacity(self.number_of_input_nodes);
+        if self.with_bias {
+            values.push(1.0);
+        }
+        values.extend(inputs.iter().map(|value| value.clamp(0.0, 1.0)));
+        self.rate_phases.iter_mut().for_each(|phase| *phase = 0.0);
+
+        let mut counts = vec![0; self.output_positions.len()];
+        for step in 0..steps {
+            for (i, value) in values.iter().enumerate() {
+                self.spiked[i] = self.input_spikes(i, *value, dt, step, steps);
+            }
+
+            for i in 0..self.nodes.len() {
+                let node = &self.nodes[i];
+                let weighted_spikes: Vec<f64> = node.incoming.iter()
+                    .map(|(from, weight)| if self.spiked[*from] { *weight } else { 0.0 }).collect();
+                let current = node.aggregation.apply(&weighted_spikes);
+                self.spiked[self.number_of_input_nodes + i] = self.nodes[i].step(self.model, current, dt);
+            }
+
+            for (count, position) in counts.iter_mut().zip(self.output_positions.iter()) {
+                if self.spiked[self.number_of_input_nodes + position] {
+                    *count += 1;
+                }
+            }
+        }
+
+        counts
+    }
+
+    fn input_spikes(&mut self, input: usize, value: f64, dt: f64, step: usize, steps: usize) -> bool {
+        match self.encoding {
+            InputEncoding::Rate {max_rate} => {
+                self.rate_phases[input] += value * max_rate * dt;
+                if self.rate_phases[input] >= 1.0 {
+                    self.rate_phases[input] -= 1.0;
+                    return true;
+                }
+                false
+            },
+            InputEncoding::Temporal => value > 0.0 && step == ((1.0 - value) * (steps - 1) as f64).round() as usize,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.reset(self.model);
+        }
+        self.spiked.iter_mut().for_each(|spiked| *spiked = false);
+    }
+
+    pub fn get_model(&self) -> NeuronModel {
+        self.model
+    }
+
+    //what run takes, the bias isn't counted
+    pub fn get_number_of_inputs(&self) -> usize {
+        if self.with_bias { self.number_of_input_nodes - 1 } else { self.number_of_input_nodes }
+    }
+
+    pub fn get_number_of_outputs(&self) -> usize {
+        self.output_positions.len()
+    }
+}
@@ -1,10 +1,19 @@
 use std::rc::Rc;
 use super::genome::Genome;
-use super::node_gene::NodeGene;
+use super::node_gene::{NodeGene, NodeRole};
 use super::connection_gene::ConnectionGene;
 use super::calculator::Calculator;
 use super::gene::Gene;
-use crate::activation_functions::ActivationFunctions;
+use super::validation::GenomeViolation;
+use super::backprop::{Backprop, FineTuneSettings, Loss, Optimizer};
+use super::network::Network;
+use super::ctrnn::Ctrnn;
+use super::spiking::{SpikingNetwork, NeuronModel, NeuronParameters, InputEncoding};
+use super::builder::GenomeBuilder;
+use super::plasticity::{HebbianRule, modulation_factor};
+use crate::activation_functions::{Activation, ActivationFunctions};
+use crate::aggregation_functions::Aggregation;
+use crate::output_decoder::{OutputDecoder, DecodedOutput};
 
 pub fn get_testing_genome_0() -> Genome {
     //create genome
@@ -76,6 +85,57 @@ pub fn get_testing_genome_1() -> Genome {
     genome
 }
 
+//get_testing_genome_1 with node 8 moved right of node 7, so every connection goes left to right (see Genome::validate)
+pub fn get_testing_genome_2() -> Genome {
+    let mut genome: Genome = Genome::new();
+
+    for (inv_num, x) in [(0, 0.1), (1, 0.1), (2, 0.9), (3, 0.9), (4, 0.9), (5, 0.9), (6, 0.5), (7, 0.5), (8, 0.7)].iter() {
+        genome.add_node(Rc::new(NodeGene::new(*inv_num, *x, 0.1)));
+    }
+
+    let edges: [(usize, usize); 7] = [(0, 6), (6,2), (6,4), (1,7), (7,8), (8,3), (8,5)];
+    let weights: [f64; 7] = [1.72, 0.5, 1.5, 1.0, 10.0, 0.001, 5.27];
+
+    for (con_i, (edge, weight)) in edges.iter().zip(weights.iter()).enumerate() {
+        let mut con = ConnectionGene::new(con_i, Rc::clone(&genome.nodes[&edge.0]), Rc::clone(&genome.nodes[&edge.1]));
+        con.weight = *weight;
+        genome.connections.insert(con.get_innovation_number(), con);
+    }
+
+    genome
+}
+
+//2 inputs into a hidden sum node, then a hidden node per aggregation taking all 3 of those,
+// then an output per aggregation taking 2 of the hidden ones
+//nodes 0-1 are inputs, 2-8 outputs, 9 the sum node and 10-16 the other hidden nodes
+pub fn get_aggregation_testing_genome() -> Genome {
+    let mut genome: Genome = Genome::new();
+    let count = Aggregation::ALL.len();
+    let (output, hidden) = (|i: usize| 2 + i, |i: usize| 3 + count + i);
+
+    genome.add_node(Rc::new(NodeGene::new(0, 0.1, 0.1)));
+    genome.add_node(Rc::new(NodeGene::new(1, 0.1, 0.1)));
+    genome.add_node(Rc::new(NodeGene::new(2 + count, 0.3, 0.1)));
+    for (i, aggregation) in Aggregation::ALL.iter().enumerate() {
+        genome.add_node(Rc::new(NodeGene::new(hidden(i), 0.6, 0.1).with_aggregation(*aggregation)));
+        genome.add_node(Rc::new(NodeGene::new(output(i), 0.9, 0.1).with_aggregation(*aggregation)));
+    }
+
+    let mut edges: Vec<(usize, usize)> = vec![(0, 2 + count), (1, 2 + count)];
+    for i in 0..count {
+        edges.extend_from_slice(&[(0, hidden(i)), (1, hidden(i)), (2 + count, hidden(i))]);
+        edges.extend_from_slice(&[(hidden(i), output(i)), (hidden((i + 1) % count), output(i))]);
+    }
+
+    for (con_num, (from, to)) in edges.iter().enumerate() {
+        let mut con = ConnectionGene::new(con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[to]));
+        con.weight = ((con_num * 37) % 23) as f64 / 8.0 - 1.3125; //never 0
+        genome.add_connection(con);
+    }
+
+    genome
+}
+
 #[test]
 fn test_calculator() {
     let mut genome = get_testing_genome_0();
@@ -110,4 +170,629 @@ fn test_calculator() {
     assert_eq!(*output.get(1).unwrap(), ActivationFunctions::scuffed_sigmoid(0.5), "Output 1: testing with scuffed sigmoid activation function");
     assert_eq!(*output.get(2).unwrap(), ActivationFunctions::scuffed_sigmoid(ActivationFunctions::scuffed_sigmoid(2.0 * 1.0 + 1.0 * 1.0) * 1.0), "Output 2: testing with scuffed sigmoid activation function");
     assert_eq!(*output.get(3).unwrap(), ActivationFunctions::scuffed_sigmoid(ActivationFunctions::scuffed_sigmoid(2.0 * 1.0 + 1.0 * 1.0) * 0.75), "Output 3: testing with scuffed sigmoid activation function");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_calculator_with_gaps_in_innovation_numbers() {
+    //inputs 2 and 5, outputs 4 and 9, like a genome that lost nodes
+    let mut genome: Genome = Genome::new();
+    for (inv_num, x) in [(5, 0.1), (2, 0.1), (9, 0.9), (4, 0.9)].iter() {
+        genome.add_node(Rc::new(NodeGene::new(*inv_num, *x, 0.1)));
+    }
+    let mut con0 = ConnectionGene::new(0, Rc::clone(&genome.nodes[&2]), Rc::clone(&genome.nodes[&4]));
+    con0.weight = 3.0;
+    let mut con1 = ConnectionGene::new(1, Rc::clone(&genome.nodes[&5]), Rc::clone(&genome.nodes[&9]));
+    con1.weight = 10.0;
+    genome.add_connection(con0);
+    genome.add_connection(con1);
+
+    //inputs/outputs go in innovation number order
+    let calc = Calculator::new(&genome, ActivationFunctions::identity);
+    assert_eq!(calc.run(&vec![1.0, 2.0]), vec![3.0, 20.0]);
+
+    let network = Network::new(&genome, Activation::Identity, false).unwrap();
+    assert_eq!(network.run(&[1.0, 2.0]), calc.run(&vec![1.0, 2.0]));
+}
+
+#[test]
+fn test_genome_text_round_trip() {
+    let mut genome = get_testing_genome_0();
+    genome.connections.get_mut(&3).unwrap().enabled = false;
+
+    let text = genome.to_text();
+    let loaded = Genome::from_text(&text).unwrap();
+
+    assert!(loaded == genome, "genome should be the same after saving and loading");
+    assert_eq!(loaded.to_text(), text);
+}
+
+#[test]
+fn test_genome_from_text_errors() {
+    let missing_node = "node 0 x=0.1 y=0\nconnection 0 from=0 to=1 weight=1\n";
+    assert_eq!(Genome::from_text(missing_node).err().unwrap().line, 2, "connection to a node that doesn't exist");
+
+    let bad_value = "# comment\nnode 0 x=zero y=0\n";
+    assert_eq!(Genome::from_text(bad_value).err().unwrap().line, 2, "x isn't a number");
+
+    assert!(Genome::from_text("neuron 0 x=0.1 y=0").is_err(), "unknown gene kind");
+    assert!(Genome::from_text("node 0 x=0.1 y=0 z=4").is_err(), "unknown field");
+}
+
+#[test]
+fn test_genome_to_dot() {
+    let mut genome = get_testing_genome_0();
+    genome.connections.get_mut(&3).unwrap().enabled = false;
+
+    let dot = genome.to_dot();
+
+    assert!(dot.starts_with("digraph genome {"));
+    assert!(dot.contains("n6 [label=\"6\", shape=circle];"), "hidden node");
+    assert!(dot.contains("{ rank=source; n0; n1; }"), "input nodes");
+    assert!(dot.contains("n0 -> n2 [label=\"0.500\", style=solid];"), "enabled connection");
+    assert!(dot.contains("n6 -> n4 [label=\"1.000\", style=dashed];"), "disabled connection");
+}
+
+#[test]
+fn test_validate() {
+    assert_eq!(get_testing_genome_0().validate(), vec![]);
+    assert_eq!(get_testing_genome_2().validate(), vec![]);
+
+    let mut genome = get_testing_genome_0();
+
+    //output -> hidden, goes backwards
+    let backwards = ConnectionGene::new(6, Rc::clone(&genome.nodes[&2]), Rc::clone(&genome.nodes[&6]));
+    //same nodes as connection 0
+    let duplicate = ConnectionGene::new(7, Rc::clone(&genome.nodes[&0]), Rc::clone(&genome.nodes[&2]));
+    //into an input, and from a node the genome doesn't have
+    let into_input = ConnectionGene::new(8, Rc::new(NodeGene::new(9, 0.5, 0.1)), Rc::clone(&genome.nodes[&1]));
+    genome.add_connection(backwards);
+    genome.add_connection(duplicate);
+    genome.add_connection(into_input);
+
+    assert_eq!(genome.validate(), vec![
+        GenomeViolation::NotFeedForward {connection: 6, from_x: 0.9, to_x: 0.5},
+        GenomeViolation::DuplicateConnection {connections: (0, 7), from: 0, to: 2},
+        GenomeViolation::MissingNode {connection: 8, node: 9},
+        GenomeViolation::NotFeedForward {connection: 8, from_x: 0.5, to_x: 0.1},
+        GenomeViolation::ConnectionIntoInput {connection: 8, node: 1},
+    ]);
+}
+
+#[test]
+fn test_genome_from_text_validates() {
+    let backwards = "node 0 x=0.1 y=0\nnode 1 x=0.9 y=0\nconnection 0 from=1 to=0 weight=1\n";
+    assert!(Genome::from_text(backwards).is_err());
+}
+
+#[test]
+fn test_backprop_matches_calculator() {
+    for genome in [get_testing_genome_0(), get_testing_genome_2()].iter() {
+        let calculator = Calculator::new(genome, ActivationFunctions::tanh);
+        let backprop = Backprop::new(genome, Activation::Tanh, false).unwrap();
+        let with_bias = Backprop::new(genome, Activation::Tanh, true).unwrap();
+
+        for inputs in [vec![1.0, 0.5], vec![-0.3, 2.0]].iter() {
+            let expected = calculator.run(inputs);
+            let outputs = backprop.run(inputs);
+            for (output, expected) in outputs.iter().zip(&expected) {
+                assert!((output - expected).abs() < 1e-12, "backprop gave {}, calculator gave {}", output, expected);
+            }
+        }
+
+        //the bias is fixed at 1.0 and left out of the inputs
+        assert_eq!(with_bias.run(&[0.5]), backprop.run(&[1.0, 0.5]));
+    }
+}
+
+#[test]
+fn test_backprop_gradients_match_finite_differences() {
+    let genome = get_testing_genome_0();
+    let dataset = vec![
+        (vec![1.0, 0.5], vec![0.2, 0.9, 0.4, 0.6]),
+        (vec![-0.5, 0.25], vec![0.7, 0.1, 0.5, 0.3]),
+    ];
+    let cases = [
+        (Activation::Tanh, Loss::MeanSquaredError),
+        (Activation::Sigmoid, Loss::MeanSquaredError),
+        (Activation::Sigmoid, Loss::BinaryCrossEntropy),
+        (Activation::Selu, Loss::MeanSquaredError),
+        (Activation::Gaussian, Loss::MeanSquaredError),
+    ];
+
+    for (activation, loss) in cases.iter() {
+        let gradients = Backprop::new(&genome, *activation, false).unwrap().get_gradients(&dataset, *loss);
+        assert_eq!(gradients.len(), genome.connections.len());
+
+        for (con_num, gradient) in &gradients {
+            //central difference on the weight
+            let loss_with_shift = |shift: f64| {
+                let mut shifted = get_testing_genome_0();
+                shifted.connections.get_mut(con_num).unwrap().weight += shift;
+                Backprop::new(&shifted, *activation, false).unwrap().get_loss(&dataset, *loss)
+            };
+            let numerical = (loss_with_shift(1e-6) - loss_with_shift(-1e-6)) / 2e-6;
+
+            assert!((gradient - numerical).abs() < 1e-6, "{} {:?} connection {}: backprop gave {}, finite differences gave {}",
+                    activation, loss, con_num, gradient, numerical);
+        }
+    }
+}
+
+#[test]
+fn test_backprop_fine_tunes_weights() {
+    //the targets come from the same topology with different weights, so the loss can get close to 0
+    let mut teacher = get_testing_genome_0();
+    for (con_num, connection) in teacher.connections.iter_mut() {
+        connection.weight = 0.3 * *con_num as f64 - 0.6;
+    }
+    let teacher_calculator = Calculator::new(&teacher, ActivationFunctions::tanh);
+    let dataset: Vec<(Vec<f64>, Vec<f64>)> = (0..20).map(|i| {
+        let inputs = vec![(i as f64 / 10.0) - 1.0, ((i * 7) % 20) as f64 / 10.0 - 1.0];
+        let targets = teacher_calculator.run(&inputs);
+        (inputs, targets)
+    }).collect();
+
+    for optimizer in [Optimizer::adam(0.05), Optimizer::Sgd {learning_rate: 0.2, momentum: 0.9}].iter() {
+        let mut genome = get_testing_genome_0();
+        let mut backprop = Backprop::new(&genome, Activation::Tanh, false).unwrap();
+        let start_loss = backprop.get_loss(&dataset, Loss::MeanSquaredError);
+
+        let settings = FineTuneSettings {optimizer: *optimizer, epochs: 300, batch_size: 5, ..FineTuneSettings::default()};
+        let losses = backprop.train(&dataset, &settings);
+        assert_eq!(losses.len(), 300);
+        assert!(*losses.last().unwrap() < start_loss / 100.0, "{:?}: loss went from {} to {}", optimizer, start_loss, losses.last().unwrap());
+
+        //the genome only changes when the weights are written back
+        assert!(genome == get_testing_genome_0());
+        backprop.write_weights(&mut genome);
+        for (con_num, weight) in backprop.get_weights() {
+            assert_eq!(genome.connections[&con_num].weight, weight);
+        }
+        let calculator = Calculator::new(&genome, ActivationFunctions::tanh);
+        for (inputs, _targets) in &dataset {
+            let (tuned, calculated) = (backprop.run(inputs), calculator.run(inputs));
+            for (tuned, calculated) in tuned.iter().zip(&calculated) {
+                assert!((tuned - calculated).abs() < 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_calculator_aggregations() {
+    for aggregation in Aggregation::ALL.iter() {
+        let genome = get_aggregation_genome_0(*aggregation);
+        assert_eq!(genome.validate(), vec![], "connections use the replaced nodes");
+
+        let output = Calculator::new(&genome, ActivationFunctions::identity).run(&vec![1.0, 1.0]);
+        let node_6 = aggregation.apply(&[2.0, 1.0]);
+        assert_eq!(output[2], node_6, "{}", aggregation);
+        assert_eq!(output[3], aggregation.apply(&[0.75 * node_6, -0.5, 1.5]), "{}", aggregation);
+    }
+
+    //worked out by hand, node 6 is (2 * 1) * (1 * 1) and node 5 is (0.75 * 2) * (-0.5 * 1) * (1.5 * 1)
+    let output = Calculator::new(&get_aggregation_genome_0(Aggregation::Product), ActivationFunctions::identity).run(&vec![1.0, 1.0]);
+    assert_eq!(output, vec![0.5, 0.5, 2.0, -1.125]);
+}
+
+//testing genome 0 with 2 more connections into node 5, nodes 6 (2 inputs) and 5 (3 inputs) use the given aggregation
+fn get_aggregation_genome_0(aggregation: Aggregation) -> Genome {
+    let mut genome = get_testing_genome_0();
+    for (con_num, from, weight) in [(6, 0, -0.5), (7, 1, 1.5)].iter() {
+        let mut connection = ConnectionGene::new(*con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[&5]));
+        connection.weight = *weight;
+        genome.add_connection(connection);
+    }
+    for node in [6, 5].iter() {
+        let replacement = genome.nodes[node].with_aggregation(aggregation);
+        genome.replace_node(Rc::new(replacement));
+    }
+
+    genome
+}
+
+#[test]
+fn test_genome_text_round_trip_aggregations() {
+    let genome = get_aggregation_testing_genome();
+
+    let text = genome.to_text();
+    assert!(text.contains("node 16 x=0.6 y=0.1 aggregation=max_abs\n"), "{}", text);
+    assert!(text.contains("node 9 x=0.3 y=0.1\n"), "sum is left out:\n{}", text);
+
+    let loaded = Genome::from_text(&text).unwrap();
+    assert!(loaded == genome, "genome should be the same after saving and loading");
+    assert_eq!(loaded.validate(), vec![]);
+
+    assert!(Genome::from_text("node 0 x=0.5 y=0 aggregation=average").is_err(), "unknown aggregation");
+}
+
+#[test]
+fn test_genome_to_dot_aggregations() {
+    let dot = get_aggregation_genome_0(Aggregation::Median).to_dot();
+
+    assert!(dot.contains("n6 [label=\"6\\nmedian\", shape=circle];"), "{}", dot);
+    assert!(dot.contains("n2 [label=\"2\", shape=doublecircle];"), "sum isn't labelled:\n{}", dot);
+}
+
+#[test]
+fn test_backprop_aggregations() {
+    let genome = get_aggregation_testing_genome();
+    let calculator = Calculator::new(&genome, ActivationFunctions::tanh);
+    let backprop = Backprop::new(&genome, Activation::Tanh, false).unwrap();
+
+    let dataset: Vec<(Vec<f64>, Vec<f64>)> = vec![
+        (vec![1.0, 0.5], (0..7).map(|i| i as f64 / 10.0).collect()),
+        (vec![-0.7, 0.3], (0..7).map(|i| 0.5 - i as f64 / 10.0).collect()),
+    ];
+
+    for (inputs, _targets) in &dataset {
+        for (output, expected) in backprop.run(inputs).iter().zip(&calculator.run(inputs)) {
+            assert!((output - expected).abs() < 1e-12, "backprop gave {}, calculator gave {}", output, expected);
+        }
+    }
+
+    for (con_num, gradient) in backprop.get_gradients(&dataset, Loss::MeanSquaredError) {
+        let loss_with_shift = |shift: f64| {
+            let mut shifted = get_aggregation_testing_genome();
+            shifted.connections.get_mut(&con_num).unwrap().weight += shift;
+            Backprop::new(&shifted, Activation::Tanh, false).unwrap().get_loss(&dataset, Loss::MeanSquaredError)
+        };
+        let numerical = (loss_with_shift(1e-6) - loss_with_shift(-1e-6)) / 2e-6;
+
+        assert!((gradient - numerical).abs() < 1e-6, "connection {}: backprop gave {}, finite differences gave {}", con_num, gradient, numerical);
+    }
+}
+
+#[test]
+fn test_network_matches_calculator() {
+    for genome in [get_testing_genome_0(), get_testing_genome_2(), get_aggregation_testing_genome()].iter() {
+        let calculator = Calculator::new(genome, ActivationFunctions::tanh);
+        let network = Network::new(genome, Activation::Tanh, false).unwrap();
+        let with_bias = Network::new(genome, Activation::Tanh, true).unwrap();
+
+        //the calculator adds up connections in hashmap order, so allow for rounding
+        for inputs in [vec![1.0, 0.5], vec![-0.3, 2.0]].iter() {
+            for (output, expected) in network.run(inputs).iter().zip(&calculator.run(inputs)) {
+                assert!((output - expected).abs() < 1e-12, "network gave {}, calculator gave {}", output, expected);
+            }
+        }
+        assert_eq!(with_bias.run(&[0.5]), network.run(&[1.0, 0.5]), "the bias is fixed at 1.0 and left out of the inputs");
+        assert_eq!((network.get_number_of_inputs(), with_bias.get_number_of_inputs()), (2, 1));
+
+        //owned, so it can be cloned and sent off to another thread
+        let copy = with_bias.clone();
+        let outputs = std::thread::spawn(move || copy.run(&[0.5])).join().unwrap();
+        assert_eq!(outputs, with_bias.run(&[0.5]));
+    }
+}
+
+#[test]
+fn test_network_text_round_trip() {
+    let mut genome = get_aggregation_testing_genome();
+    genome.connections.get_mut(&3).unwrap().enabled = false;
+    let network = Network::new(&genome, Activation::Selu, true).unwrap();
+
+    let text = network.to_text();
+    assert!(text.starts_with("network activation=selu bias=true\n"), "{}", text);
+
+    let loaded = Network::from_text(&text).unwrap();
+    assert_eq!(loaded, network);
+    assert_eq!(loaded.run(&[0.7]), network.run(&[0.7]));
+
+    let unpacked = network.to_genome();
+    assert_eq!(unpacked.validate(), vec![]);
+    assert_eq!(unpacked.connections.len(), genome.connections.len() - 1, "disabled connections are left out");
+
+    assert!(Network::from_text(&genome.to_text()).is_err(), "missing network line");
+    assert!(Network::from_text(&format!("network activation=swish bias=true\n{}", genome.to_text())).is_err());
+
+    let mut decoded = network.clone();
+    assert!(decoded.set_decoder(OutputDecoder::Scale {from: (0.0, 1.0), to: vec![(0.0, 1.0), (0.0, 1.0)]}).is_err(), "one range or one per output");
+    decoded.set_decoder(OutputDecoder::Threshold(0.5)).unwrap();
+    let labels = network.run(&[0.7]).iter().map(|output| *output >= 0.5).collect();
+    assert_eq!(decoded.run_decoded(&[0.7]), DecodedOutput::Labels(labels));
+    let text = decoded.to_text();
+    assert!(text.starts_with("network activation=selu bias=true decoder=threshold(0.5)\n"), "{}", text);
+    assert_eq!(Network::from_text(&text).unwrap(), decoded);
+}
+
+#[test]
+fn test_genome_builder() {
+    //xor out of an OR and an AND node
+    let mut builder = GenomeBuilder::new();
+    let bias = builder.add_input("bias");
+    builder.add_input("a");
+    builder.add_input("b");
+    let or = builder.add_hidden("or");
+    builder.add_hidden("and");
+    builder.add_output("xor");
+    builder.connect("a", or, 1.0).connect("b", or, 1.0).connect(bias, or, -0.5)
+        .connect("a", "and", 1.0).connect("b", "and", 1.0).connect(bias, "and", -1.5)
+        .connect(or, "xor", 1.0).connect("and", "xor", -2.0).connect(bias, "xor", -0.5);
+    let genome = builder.build().unwrap();
+
+    //inputs, then outputs, then hidden nodes
+    assert_eq!(genome.nodes[&3].get_x(), 0.9);
+    assert_eq!((genome.nodes[&4].get_x(), genome.nodes[&5].get_x()), (0.5, 0.5));
+    assert_eq!(genome.connections.len(), 9);
+
+    let network = Network::new(&genome, Activation::BinaryStep, true).unwrap();
+    for (a, b) in [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)].iter() {
+        let expected = if a != b { 1.0 } else { 0.0 };
+        assert_eq!(network.run(&[*a, *b]), vec![expected], "{} xor {}", a, b);
+    }
+
+    //deeper hidden nodes go further right, aggregations are kept
+    let mut builder = GenomeBuilder::new();
+    builder.add_input("in");
+    builder.add_hidden("second");
+    builder.add_hidden("first");
+    builder.add_output("out");
+    builder.connect("in", "first", 1.0).connect("first", "second", 1.0).connect("second", "out", 1.0).connect("in", "out", 0.5)
+        .connect_disabled("first", "out", 3.0).set_aggregation("out", Aggregation::Max);
+    let genome = builder.build().unwrap();
+    assert!(genome.nodes[&2].get_x() > genome.nodes[&3].get_x(), "second comes after first");
+    assert_eq!(genome.nodes[&1].get_aggregation(), Aggregation::Max);
+    assert_eq!(genome.connections.values().filter(|connection| !connection.enabled).count(), 1);
+
+    let build_error = |connect: &dyn Fn(&mut GenomeBuilder)| {
+        let mut builder = GenomeBuilder::new();
+        builder.add_input("in");
+        builder.add_hidden("h");
+        builder.add_hidden("h2");
+        builder.add_output("out");
+        connect(&mut builder);
+        builder.build().err()
+    };
+    assert!(build_error(&|builder| { builder.connect("in", "out", 1.0); }).is_none());
+    assert!(build_error(&|builder| { builder.connect("in", "missing", 1.0); }).unwrap().contains("missing"));
+    assert!(build_error(&|builder| { builder.connect(7, "out", 1.0); }).is_some());
+    assert!(build_error(&|builder| { builder.connect("h", "in", 1.0); }).unwrap().contains("input"));
+    assert!(build_error(&|builder| { builder.connect("out", "h", 1.0); }).unwrap().contains("output"));
+    assert!(build_error(&|builder| { builder.connect("h", "h2", 1.0).connect("h2", "h", 1.0); }).unwrap().contains("cycle"));
+    assert!(build_error(&|builder| { builder.connect("in", "out", 1.0).connect("in", "out", 2.0); }).is_some());
+    assert!(build_error(&|builder| { builder.add_hidden("h"); }).is_none(), "names only have to be unique when they're used");
+    assert!(build_error(&|builder| { builder.add_hidden("h"); builder.connect("in", "h", 1.0); }).unwrap().contains("more than one"));
+}
+
+//input 0, output 1, hidden 2 and 3 with x going the wrong way for 0 -> 2 -> 3 -> 1
+fn get_misordered_genome(extra_connections: &[(usize, usize, usize, bool)]) -> Genome {
+    let mut genome = Genome::new();
+    genome.add_node(Rc::new(NodeGene::new(0, 0.1, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(1, 0.9, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(2, 0.6, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(3, 0.4, 0.0)));
+
+    let connections = [(0, 0, 2, true), (1, 2, 3, true), (2, 3, 1, true)];
+    for (con_num, from, to, enabled) in connections.iter().chain(extra_connections.iter()) {
+        let mut connection = ConnectionGene::new(*con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[to]));
+        connection.weight = 2.0;
+        connection.enabled = *enabled;
+        genome.add_connection(connection);
+    }
+    genome
+}
+
+#[test]
+fn test_calculator_topological_order() {
+    //x would run node 3 before node 2
+    let genome = get_misordered_genome(&[]);
+    let calc = Calculator::new(&genome, ActivationFunctions::identity);
+    assert_eq!(calc.run(&vec![1.5]), vec![12.0]);
+
+    let cycle = get_misordered_genome(&[(3, 3, 2, true)]);
+    let error = Calculator::try_new(&cycle, ActivationFunctions::identity).err().unwrap();
+    assert!(error.contains("cycle"), "{}", error);
+
+    //only enabled connections count
+    let disabled_cycle = get_misordered_genome(&[(3, 3, 2, false)]);
+    assert_eq!(Calculator::new(&disabled_cycle, ActivationFunctions::identity).run(&vec![1.5]), vec![12.0]);
+
+    let mut from_output = get_misordered_genome(&[]);
+    let connection = ConnectionGene::new(3, Rc::clone(&from_output.nodes[&1]), Rc::clone(&from_output.nodes[&3]));
+    from_output.add_connection(connection);
+    assert!(Calculator::try_new(&from_output, ActivationFunctions::identity).err().unwrap().contains("output"));
+}
+
+#[test]
+fn test_hebbian_plasticity() {
+    let mut genome = get_misordered_genome(&[]);
+    genome.connections.get_mut(&2).unwrap().hebbian = HebbianRule::new(1.0, 0.0, 0.0, 0.0, 0.1);
+
+    let calc = Calculator::new(&genome, ActivationFunctions::identity);
+    assert_eq!(calc.run(&vec![1.5]), vec![12.0]);
+    //the last connection goes from 6 to 12, so its weight goes up by 0.1 * 6 * 12
+    assert!((calc.run(&vec![1.5])[0] - 6.0 * 9.2).abs() < 1e-9);
+
+    calc.reset_state();
+    assert_eq!(calc.run(&vec![1.5]), vec![12.0], "reset_state puts the genetic weights back");
+    assert_eq!(genome.connections[&2].weight, 2.0, "the genome isn't changed");
+
+    assert!(!HebbianRule::new(1.0, 1.0, 1.0, 1.0, 0.0).is_plastic(), "no learning rate");
+
+    //rules are kept in the text format, connections without one don't write it
+    let mut genome = get_aggregation_testing_genome();
+    let con_key = *genome.connections.keys().next().unwrap();
+    genome.connections.get_mut(&con_key).unwrap().hebbian = HebbianRule::new(1.0, 0.0, 0.0, 0.0, 0.1);
+    let text = genome.to_text();
+    assert!(text.contains("hebbian=1:0:0:0:0.1") && text.matches("hebbian").count() == 1, "{}", text);
+    assert!(Genome::from_text(&text).unwrap() == genome);
+    assert_eq!("0.5: -1:0:0:1".parse(), Ok(HebbianRule::new(0.5, -1.0, 0.0, 0.0, 1.0)));
+    assert!("1:0:0:0".parse::<HebbianRule>().is_err());
+    assert!("1:0:x:0:1".parse::<HebbianRule>().is_err());
+}
+
+//input 0 -> output 1 (plastic, learns 1 per run before modulation), input 0 -> modulatory 2 -> output 1
+fn get_modulated_genome(modulating_weight: f64) -> Genome {
+    let mut genome = Genome::new();
+    genome.add_node(Rc::new(NodeGene::new(0, 0.1, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(1, 0.9, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(2, 0.5, 0.0).with_modulatory(true)));
+
+    for (con_num, from, to, weight) in [(0, 0, 1, 2.0), (1, 0, 2, 1.0), (2, 2, 1, modulating_weight)].iter() {
+        let mut connection = ConnectionGene::new(*con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[to]));
+        connection.weight = *weight;
+        genome.add_connection(connection);
+    }
+    genome.connections.get_mut(&0).unwrap().hebbian = HebbianRule::new(0.0, 0.0, 0.0, 1.0, 1.0);
+    genome
+}
+
+#[test]
+fn test_modulatory_nodes() {
+    //the modulatory node doesn't change the output, only how fast connection 0 learns
+    let calc = Calculator::new(&get_modulated_genome(2.0), ActivationFunctions::identity);
+    assert_eq!(calc.run(&vec![1.0]), vec![2.0]);
+    assert!((calc.run(&vec![1.0])[0] - (2.0 + modulation_factor(2.0))).abs() < 1e-12);
+
+    let no_modulation = Calculator::new(&get_modulated_genome(0.0), ActivationFunctions::identity);
+    no_modulation.run(&vec![1.0]);
+    assert_eq!(no_modulation.run(&vec![1.0]), vec![2.0], "0 modulation stops learning");
+
+    let network = Network::new(&get_modulated_genome(2.0), Activation::Identity, false).unwrap();
+    assert_eq!(network.run(&[1.0]), vec![2.0]);
+
+    let genome = get_modulated_genome(2.0);
+    let text = genome.to_text();
+    assert!(text.contains("node 2 x=0.5 y=0 modulatory=true\n"), "{}", text);
+    assert!(Genome::from_text(&text).unwrap() == genome);
+    assert!(genome.to_dot().contains("shape=diamond"));
+
+    let mut modulatory_input = get_modulated_genome(2.0);
+    modulatory_input.replace_node(Rc::new(modulatory_input.nodes[&0].with_modulatory(true)));
+    assert_eq!(modulatory_input.validate(), vec![GenomeViolation::ModulatoryNotHidden {node: 0}]);
+}
+
+#[test]
+fn test_ctrnn() {
+    //input 0 -> hidden 2 (tau 1) -> output 1 (tau 2)
+    let mut genome = Genome::new();
+    genome.add_node(Rc::new(NodeGene::new(0, 0.1, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(1, 0.9, 0.0).with_time_constant(2.0)));
+    genome.add_node(Rc::new(NodeGene::new(2, 0.5, 0.0)));
+    genome.add_connection(ConnectionGene::new(0, Rc::clone(&genome.nodes[&0]), Rc::clone(&genome.nodes[&2])));
+    genome.add_connection(ConnectionGene::new(1, Rc::clone(&genome.nodes[&2]), Rc::clone(&genome.nodes[&1])));
+
+    let mut ctrnn = Ctrnn::new(&genome, Activation::Identity, false).unwrap();
+    assert_eq!(ctrnn.advance(&[1.0], 0.1, 1), vec![0.0], "the hidden node hadn't moved yet");
+    //the hidden node was at 0.1, the output moves a tenth of the way there at half speed
+    assert!((ctrnn.advance(&[1.0], 0.1, 1)[0] - 0.005).abs() < 1e-12);
+    assert!((ctrnn.advance(&[1.0], 0.1, 1000)[0] - 1.0).abs() < 1e-6, "settles on what a network would output");
+
+    ctrnn.reset();
+    assert_eq!(ctrnn.get_outputs(), vec![0.0]);
+    let mut faster_genome = genome.clone();
+    for node_key in [1, 2].iter() {
+        faster_genome.replace_node(Rc::new(genome.nodes[node_key].with_time_constant(0.5)));
+    }
+    let mut faster = Ctrnn::new(&faster_genome, Activation::Identity, false).unwrap();
+    assert!(faster.advance(&[1.0], 0.1, 10)[0] > ctrnn.advance(&[1.0], 0.1, 10)[0]);
+
+    let text = genome.to_text();
+    assert!(text.contains("node 1 x=0.9 y=0 time_constant=2\n"), "{}", text);
+    assert!(Genome::from_text(&text).unwrap() == genome);
+
+    genome.replace_node(Rc::new(genome.nodes[&2].with_time_constant(0.0)));
+    assert_eq!(genome.validate(), vec![GenomeViolation::BadTimeConstant {node: 2, time_constant: 0.0}]);
+    assert!(Ctrnn::new(&genome, Activation::Identity, false).is_err());
+}
+
+#[test]
+fn test_recurrent_ctrnn() {
+    //input 0 -> output 1, which feeds itself and hidden 2, which feeds output 1 back
+    let mut genome = Genome::new();
+    genome.add_node(Rc::new(NodeGene::new(0, 0.1, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(1, 0.9, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(2, 0.5, 0.0)));
+    let edges: [(usize, usize, f64); 4] = [(0, 1, 1.0), (1, 1, 0.5), (1, 2, 1.0), (2, 1, 1.0)];
+    for (con_num, (from, to, weight)) in edges.iter().enumerate() {
+        let mut connection = ConnectionGene::new(con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[to]));
+        connection.weight = *weight;
+        genome.add_connection(connection);
+    }
+    assert!(Network::new(&genome, Activation::Identity, false).is_err(), "only a ctrnn can run cycles");
+
+    //dt = tau, so every state becomes its target from the previous states
+    let mut ctrnn = Ctrnn::new(&genome, Activation::Identity, false).unwrap();
+    assert_eq!(ctrnn.advance(&[1.0], 1.0, 1), vec![1.0]);
+    assert_eq!(ctrnn.advance(&[1.0], 1.0, 1), vec![1.0 + 0.5 * 1.0]);
+    assert_eq!(ctrnn.advance(&[1.0], 1.0, 1), vec![1.0 + 0.5 * 1.5 + 1.0]);
+
+    //other violations still aren't allowed
+    let into_input = ConnectionGene::new(4, Rc::clone(&genome.nodes[&2]), Rc::clone(&genome.nodes[&0]));
+    genome.add_connection(into_input);
+    assert!(Ctrnn::new(&genome, Activation::Identity, false).is_err());
+}
+
+#[test]
+fn test_spiking_network() {
+    let mut genome = Genome::new();
+    genome.add_node(Rc::new(NodeGene::new(0, 0.1, 0.0)));
+    genome.add_node(Rc::new(NodeGene::new(1, 0.9, 0.0)));
+    let mut connection = ConnectionGene::new(0, Rc::clone(&genome.nodes[&0]), Rc::clone(&genome.nodes[&1]));
+    connection.weight = 0.5;
+    genome.add_connection(connection);
+
+    //a spike every ms, v goes 0.5, 0.95, 1.355 (spikes and resets)
+    let rate = InputEncoding::Rate {max_rate: 1.0};
+    let mut lif = SpikingNetwork::new(&genome, NeuronModel::LeakyIntegrateAndFire, rate, false).unwrap();
+    assert_eq!(lif.run(&[1.0], 1.0, 30), vec![10]);
+    lif.reset();
+    assert!(lif.run(&[0.5], 1.0, 30)[0] < 10, "fewer input spikes, fewer output spikes");
+    lif.reset();
+    assert_eq!(lif.run(&[0.0], 1.0, 30), vec![0]);
+
+    genome.connections.get_mut(&0).unwrap().weight = 2.0;
+    let mut temporal = SpikingNetwork::new(&genome, NeuronModel::LeakyIntegrateAndFire, InputEncoding::Temporal, false).unwrap();
+    assert_eq!(temporal.run(&[0.7], 1.0, 20), vec![1], "one input spike");
+    assert_eq!(temporal.run(&[0.0], 1.0, 20), vec![0]);
+
+    let mut izhikevich = SpikingNetwork::new(&genome, NeuronModel::Izhikevich, rate, false).unwrap();
+    assert!(izhikevich.run(&[1.0], 0.5, 200)[0] > 0);
+    izhikevich.reset();
+    assert_eq!(izhikevich.run(&[0.0], 0.5, 200), vec![0], "resting neurons don't spike");
+
+    //each node has its own parameters
+    let high_threshold = NeuronParameters {threshold: 100.0, ..NeuronParameters::default()};
+    genome.replace_node(Rc::new(genome.nodes[&1].with_neuron(high_threshold)));
+    let mut lif = SpikingNetwork::new(&genome, NeuronModel::LeakyIntegrateAndFire, rate, false).unwrap();
+    assert_eq!(lif.run(&[1.0], 1.0, 30), vec![0]);
+
+    let text = genome.to_text();
+    assert!(text.contains("neuron=0.02:0.2:-65:8:100:10"), "{}", text);
+    assert!(Genome::from_text(&text).unwrap() == genome);
+    assert_eq!("lif".parse(), Ok(NeuronModel::LeakyIntegrateAndFire));
+    assert!("hodgkin_huxley".parse::<NeuronModel>().is_err());
+
+    genome.replace_node(Rc::new(genome.nodes[&1].with_neuron(NeuronParameters {threshold: 0.0, ..NeuronParameters::default()})));
+    assert!(matches!(genome.validate()[..], [GenomeViolation::BadNeuronParameters {node: 1, ..}]));
+}
+
+#[test]
+fn test_node_roles() {
+    assert_eq!(NodeRole::from_x(0.1), NodeRole::Input);
+    assert_eq!(NodeRole::from_x(0.5), NodeRole::Hidden);
+    assert_eq!(NodeRole::from_x(0.9), NodeRole::Output);
+    assert_eq!("output".parse::<NodeRole>(), Ok(NodeRole::Output));
+    assert!("outputs".parse::<NodeRole>().is_err());
+
+    //the role decides what a node is, not its x
+    let mut genome = Genome::new();
+    genome.add_node(Rc::new(NodeGene::new_with_role(0, 0.0, 0.0, NodeRole::Input)));
+    genome.add_node(Rc::new(NodeGene::new_with_role(1, 0.7, 0.0, NodeRole::Output)));
+    genome.add_node(Rc::new(NodeGene::new_with_role(2, 0.05, 0.0, NodeRole::Hidden)));
+    for (con_num, from, to) in [(0, 0, 2), (1, 2, 1)].iter() {
+        let mut connection = ConnectionGene::new(*con_num, Rc::clone(&genome.nodes[from]), Rc::clone(&genome.nodes[to]));
+        connection.weight = 3.0;
+        genome.add_connection(connection);
+    }
+    assert!(genome.validate().is_empty(), "{:?}", genome.validate());
+    assert_eq!(Calculator::new(&genome, ActivationFunctions::identity).run(&vec![0.5]), vec![4.5]);
+
+    //roles x wouldn't give are written out
+    let text = genome.to_text();
+    assert!(text.contains("node 1 x=0.7 y=0 role=output\n") && text.contains("node 2 x=0.05 y=0 role=hidden\n"), "{}", text);
+    assert!(!text.contains("node 0 x=0 y=0 role"), "{}", text);
+    assert_eq!(Genome::from_text(&text).unwrap().to_text(), text);
+    assert!(Genome::from_text("node 0 x=0.1 y=0 role=middle").is_err());
+}
@@ -0,0 +1,143 @@
+use super::genome::Genome;
+use super::gene::Gene;
+use super::node_gene::{NodeRole, MIN_TIME_CONSTANT};
+use std::collections::HashMap;
+use std::fmt;
+
+/* - Genome validation -
+Checks the invariants the rest of the crate assumes a genome has
+connections also have to go left to right (from.x < to.x), mutations rely on it to never make a cycle
+(the calculator orders nodes by their connections, not their x)
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenomeViolation {
+    //node map key doesn't match the node's innovation number
+    NodeKeyMismatch {key: usize, innovation_number: usize},
+    //connection map key doesn't match the connection's innovation number
+    ConnectionKeyMismatch {key: usize, innovation_number: usize},
+    //connection uses a node that isn't in Genome::nodes (or is a different node with the same innovation number)
+    MissingNode {connection: usize, node: usize},
+    //two connections between the same nodes with different innovation numbers
+    DuplicateConnection {connections: (usize, usize), from: usize, to: usize},
+    ConnectionIntoInput {connection: usize, node: usize},
+    ConnectionToSelf {connection: usize, node: usize},
+    //from.x >= to.x, would be a recurrent connection
+    NotFeedForward {connection: usize, from_x: f64, to_x: f64},
+    //only hidden nodes can be modulatory
+    ModulatoryNotHidden {node: usize},
+    //below MIN_TIME_CONSTANT (or NaN)
+    BadTimeConstant {node: usize, time_constant: f64},
+    //spiking neuron parameters that can't be run (see NeuronParameters::check)
+    BadNeuronParameters {node: usize, problem: String},
+}
+
+impl fmt::Display for GenomeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenomeViolation::NodeKeyMismatch {key, innovation_number} =>
+                write!(f, "node stored under key {} has innovation number {}", key, innovation_number),
+            GenomeViolation::ConnectionKeyMismatch {key, innovation_number} =>
+                write!(f, "connection stored under key {} has innovation number {}", key, innovation_number),
+            GenomeViolation::MissingNode {connection, node} =>
+                write!(f, "connection {} uses node {} which isn't in the genome", connection, node),
+            GenomeViolation::DuplicateConnection {connections, from, to} =>
+                write!(f, "connections {} and {} both go from node {} to node {}", connections.0, connections.1, from, to),
+            GenomeViolation::ConnectionIntoInput {connection, node} =>
+                write!(f, "connection {} goes into input node {}", connection, node),
+            GenomeViolation::ConnectionToSelf {connection, node} =>
+                write!(f, "connection {} goes from node {} to itself", connection, node),
+            GenomeViolation::NotFeedForward {connection, from_x, to_x} =>
+                write!(f, "connection {} goes from x={} to x={}, not feed-forward", connection, from_x, to_x),
+            GenomeViolation::ModulatoryNotHidden {node} =>
+                write!(f, "node {} is modulatory but isn't a hidden node", node),
+            GenomeViolation::BadTimeConstant {node, time_constant} =>
+                write!(f, "node {} has time constant {}, it has to be at least {}", node, time_constant, MIN_TIME_CONSTANT),
+            GenomeViolation::BadNeuronParameters {node, problem} =>
+                write!(f, "node {} has bad neuron parameters: {}", node, problem),
+        }
+    }
+}
+
+impl Genome {
+    //returns every broken invariant, empty if the genome is fine
+    pub fn validate(&self) -> Vec<GenomeViolation> {
+        let mut violations = Vec::new();
+
+        let mut node_keys: Vec<&usize> = self.nodes.keys().collect();
+        node_keys.sort_unstable();
+        for key in node_keys {
+            let innovation_number = self.nodes[key].get_innovation_number();
+            if *key != innovation_number {
+                violations.push(GenomeViolation::NodeKeyMismatch {key: *key, innovation_number});
+            }
+            if self.nodes[key].is_modulatory() && self.nodes[key].get_role() != NodeRole::Hidden {
+                violations.push(GenomeViolation::ModulatoryNotHidden {node: *key});
+            }
+            let time_constant = self.nodes[key].get_time_constant();
+            if time_constant.is_nan() || time_constant < MIN_TIME_CONSTANT {
+                violations.push(GenomeViolation::BadTimeConstant {node: *key, time_constant});
+            }
+            if let Some(problem) = self.nodes[key].get_neuron().check() {
+                violations.push(GenomeViolation::BadNeuronParameters {node: *key, problem});
+            }
+        }
+
+        let mut connection_keys: Vec<&usize> = self.connections.keys().collect();
+        connection_keys.sort_unstable();
+
+        let mut node_pairs: HashMap<(usize, usize), usize> = HashMap::new();
+
+        for key in connection_keys {
+            let connection = &self.connections[key];
+            let innovation_number = connection.get_innovation_number();
+            let (from, to) = (connection.from.get_innovation_number(), connection.to.get_innovation_number());
+
+            if *key != innovation_number {
+                violations.push(GenomeViolation::ConnectionKeyMismatch {key: *key, innovation_number});
+            }
+
+            for node in [&connection.from, &connection.to].iter() {
+                match self.nodes.get(&node.get_innovation_number()) {
+                    Some(genome_node) if genome_node == *node => {},
+                    _ => violations.push(GenomeViolation::MissingNode {connection: innovation_number, node: node.get_innovation_number()}),
+                }
+            }
+
+            match node_pairs.get(&(from, to)) {
+                Some(other) => violations.push(GenomeViolation::DuplicateConnection {connections: (*other, innovation_number), from, to}),
+                None => { node_pairs.insert((from, to), innovation_number); },
+            }
+
+            if from == to {
+                violations.push(GenomeViolation::ConnectionToSelf {connection: innovation_number, node: from});
+            } else if connection.from.get_x() >= connection.to.get_x() {
+                violations.push(GenomeViolation::NotFeedForward {connection: innovation_number, from_x: connection.from.get_x(), to_x: connection.to.get_x()});
+            }
+
+            if connection.to.get_role() == NodeRole::Input {
+                violations.push(GenomeViolation::ConnectionIntoInput {connection: innovation_number, node: to});
+            }
+        }
+
+        violations
+    }
+
+    //only does anything in debug builds, panics listing the violations
+    pub(crate) fn debug_validate(&self, context: &str) {
+        if cfg!(debug_assertions) {
+            let violations = self.validate();
+            if !violations.is_empty() {
+                let messages: Vec<String> = violations.iter().map(|violation| violation.to_string()).collect();
+                panic!("invalid genome after {}: {}", context, messages.join("; "));
+            }
+        }
+    }
+
+    //debug_validate for a genome made from others, skipped if they were already broken since that can't be fixed
+    pub(crate) fn debug_validate_from(&self, sources: &[&Genome], context: &str) {
+        if cfg!(debug_assertions) && sources.iter().all(|source| source.validate().is_empty()) {
+            self.debug_validate(context);
+        }
+    }
+}
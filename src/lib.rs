@@ -1,6 +1,9 @@
-mod feed_forward;
+pub mod feed_forward;
 mod random_hash_set;
+pub mod export;
 pub mod activation_functions;
+pub mod aggregation_functions;
+pub mod output_decoder;
 pub mod neat;
 
 #[cfg(test)]
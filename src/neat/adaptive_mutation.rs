@@ -0,0 +1,216 @@
+use crate::feed_forward::genome::Genome;
+use crate::feed_forward::mutation_rates::MutationRates;
+use super::mutation::Mutation;
+use super::weight_distribution::WeightDistribution;
+use super::Neat;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/* - Adaptive mutation -
+The five main mutation probabilities (add node, add connection, random weight, weight shift, toggle connection) can change
+during a run instead of staying what the Neat was given (see neat::mutation)
+    self-adaptation (mutation_rate_adaptation > 0) - every genome carries its own probabilities (feed_forward::mutation_rates),
+        new clients start with the Neat's, children get a random parent's and every time a genome is mutated its
+        probabilities are mutated first, p -> p * e^(mutation_rate_adaptation * N(0,1)) (at most 1), so ones that do well spread
+    a schedule - makes all five mutations more/less likely as the run goes on
+        fixed - always as likely
+        decay(rate, min) - rate^generation times as likely, not less than min times
+        stagnation(generations, rate, max) - once the best score hasn't improved for generations generations,
+            rate^(generations stuck - generations + 1) times as likely, not more than max times
+both can be used at once, the schedule scales the genome's own probabilities, a probability of 0 never mutates either way
+get_mutation_rate_report gives the probabilities the last update_clients actually mutated with
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MutationSchedule {
+    #[default]
+    Fixed,
+    Decay {rate: f64, min: f64},
+    Stagnation {generations: u64, rate: f64, max: f64},
+}
+
+impl MutationSchedule {
+    //how many times as likely the mutations are
+    pub fn multiplier(&self, generation: u64, generations_since_fitness_improved: u64) -> f64 {
+        match *self {
+            MutationSchedule::Fixed => 1.0,
+            MutationSchedule::Decay {rate, min} => rate.powf(generation as f64).max(min),
+            MutationSchedule::Stagnation {generations, rate, max} => {
+                if generations_since_fitness_improved < generations {
+                    return 1.0;
+                }
+                rate.powf((generations_since_fitness_improved - generations + 1) as f64).min(max)
+            },
+        }
+    }
+}
+
+impl fmt::Display for MutationSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationSchedule::Fixed => write!(f, "fixed"),
+            MutationSchedule::Decay {rate, min} => write!(f, "decay({}, {})", rate, min),
+            MutationSchedule::Stagnation {generations, rate, max} => write!(f, "stagnation({}, {}, {})", generations, rate, max),
+        }
+    }
+}
+
+impl FromStr for MutationSchedule {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bad_format = || format!("expected fixed, decay(rate, min) or stagnation(generations, rate, max), got '{}'", text.trim());
+
+        if text.trim() == "fixed" {
+            return Ok(MutationSchedule::Fixed);
+        }
+
+        let (name, parameters) = text.trim().strip_suffix(')')
+            .and_then(|text| text.split_once('('))
+            .ok_or_else(bad_format)?;
+        let parameters: Vec<&str> = parameters.split(',').map(|parameter| parameter.trim()).collect();
+
+        match (name.trim(), parameters.as_slice()) {
+            ("decay", [rate, min]) => {
+                let (rate, min): (f64, f64) = (rate.parse().map_err(|_| bad_format())?, min.parse().map_err(|_| bad_format())?);
+                if !(rate > 0.0 && rate <= 1.0 && min > 0.0 && min <= 1.0) {
+                    return Err(format!("decay's rate and min have to be above 0 and at most 1, got {} and {}", rate, min));
+                }
+                Ok(MutationSchedule::Decay {rate, min})
+            },
+            ("stagnation", [generations, rate, max]) => {
+                let generations: u64 = generations.parse().map_err(|_| bad_format())?;
+                let (rate, max): (f64, f64) = (rate.parse().map_err(|_| bad_format())?, max.parse().map_err(|_| bad_format())?);
+                if !(rate >= 1.0 && max >= 1.0 && max.is_finite()) {
+                    return Err(format!("stagnation's rate and max have to be at least 1, got {} and {}", rate, max));
+                }
+                Ok(MutationSchedule::Stagnation {generations, rate, max})
+            },
+            _ => Err(bad_format()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationRateReport {
+    pub generation: u64, //the generation that was mutated (before update_clients moved it on)
+    pub multiplier: f64, //from the schedule
+    pub mean: MutationRates, //mean probabilities over the clients
+}
+
+impl Neat {
+    //the probabilities mutate_random uses for the genome, a self-adaptive genome's are mutated first
+    pub(super) fn next_mutation_rates(&mut self, genome: &mut Genome) -> MutationRates {
+        if self.mutation_rate_adaptation > 0.0 {
+            let rates = genome.mutation_rates.unwrap_or_else(|| self.get_base_mutation_rates());
+            genome.mutation_rates = Some(adapt_mutation_rates(&rates, self.mutation_rate_adaptation, &mut self.cached_rng));
+        }
+
+        self.get_genome_mutation_rates(genome)
+    }
+
+    //the probabilities the genome would be mutated with this generation
+    fn get_genome_mutation_rates(&self, genome: &Genome) -> MutationRates {
+        let rates = match genome.mutation_rates {
+            Some(rates) if self.mutation_rate_adaptation > 0.0 => rates,
+            _ => self.get_base_mutation_rates(),
+        };
+        scale_mutation_rates(&rates, self.mutation_multiplier)
+    }
+
+    //from the Neat's settings (see neat::mutation)
+    pub(super) fn get_base_mutation_rates(&self) -> MutationRates {
+        let probability = |mutation| self.get_mutation_probability(mutation);
+        MutationRates::new(probability(Mutation::AddNode), probability(Mutation::AddConnection), probability(Mutation::RandomWeight),
+                           probability(Mutation::WeightShift), probability(Mutation::ToggleConnection))
+    }
+
+    //call with the generation's best score before mutating, sets this generation's multiplier
+    pub(super) fn update_mutation_schedule(&mut self, best_fitness: f64) {
+        if best_fitness > self.best_fitness {
+            self.best_fitness = best_fitness;
+            self.generations_since_fitness_improved = 0;
+        } else {
+            self.generations_since_fitness_improved += 1;
+        }
+
+        self.mutation_multiplier = self.mutation_schedule.multiplier(self.generation, self.generations_since_fitness_improved);
+    }
+
+    //call after mutating the clients
+    pub(super) fn record_mutation_rates(&mut self) {
+        if self.clients.is_empty() {
+            return;
+        }
+
+        let all_rates: Vec<[f64; 5]> = self.clients.values()
+            .map(|client_ref| self.get_genome_mutation_rates(&client_ref.borrow().get_genome().borrow()).to_array())
+            .collect();
+
+        let mut mean_probabilities = [0.0; 5];
+        for rates in &all_rates {
+            for (mean_probability, probability) in mean_probabilities.iter_mut().zip(rates.iter()) {
+                *mean_probability += probability / all_rates.len() as f64;
+            }
+        }
+
+        self.last_mutation_rates = Some(MutationRateReport {
+            generation: self.generation,
+            multiplier: self.mutation_multiplier,
+            mean: MutationRates::from_array(mean_probabilities),
+        });
+    }
+
+    //None until update_clients has run
+    pub fn get_mutation_rate_report(&self) -> Option<&MutationRateReport> {
+        self.last_mutation_rates.as_ref()
+    }
+
+    pub fn get_mutation_schedule(&self) -> MutationSchedule {
+        self.mutation_schedule
+    }
+}
+
+//each probability p becomes p * e^(adaptation * N(0,1)), at most 1 (every time), 0 stays 0
+fn adapt_mutation_rates<R: Rng>(rates: &MutationRates, adaptation: f64, rng: &mut R) -> MutationRates {
+    let distribution = WeightDistribution::Gaussian {sigma: adaptation};
+    MutationRates::from_array(rates.to_array().map(|probability| (probability * distribution.sample(rng).exp()).min(1.0)))
+}
+
+//multiplier times as likely to mutate, at most 1
+fn scale_mutation_rates(rates: &MutationRates, multiplier: f64) -> MutationRates {
+    MutationRates::from_array(rates.to_array().map(|probability| (probability * multiplier).min(1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_goes_down_to_min() {
+        let schedule: MutationSchedule = "decay(0.5, 0.2)".parse().unwrap();
+        assert_eq!(schedule.multiplier(0, 0), 1.0);
+        assert_eq!(schedule.multiplier(1, 0), 0.5);
+        assert_eq!(schedule.multiplier(5, 0), 0.2);
+    }
+
+    #[test]
+    fn stagnation_goes_up_when_stuck() {
+        let schedule: MutationSchedule = "stagnation(2, 2, 5)".parse().unwrap();
+        assert_eq!(schedule.multiplier(10, 1), 1.0);
+        assert_eq!(schedule.multiplier(10, 2), 2.0);
+        assert_eq!(schedule.multiplier(10, 3), 4.0);
+        assert_eq!(schedule.multiplier(10, 4), 5.0);
+    }
+
+    #[test]
+    fn parses_what_it_writes() {
+        for schedule in [MutationSchedule::Fixed, MutationSchedule::Decay {rate: 0.99, min: 0.1}, MutationSchedule::Stagnation {generations: 10, rate: 1.5, max: 4.0}] {
+            assert_eq!(schedule.to_string().parse::<MutationSchedule>(), Ok(schedule));
+        }
+        assert!("decay(2, 0.1)".parse::<MutationSchedule>().is_err());
+        assert!("stagnation(10, 0.5, 4)".parse::<MutationSchedule>().is_err());
+        assert!("decay(0.9)".parse::<MutationSchedule>().is_err());
+    }
+}
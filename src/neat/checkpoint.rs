@@ -0,0 +1,287 @@
+use crate::feed_forward::serialization::{ParseError, GeneLine, parse_gene_line, parse_fields, required_field, check_known_fields, parse_value, strip_comment};
+use crate::feed_forward::node_gene::{NodeGene, NodeRole};
+use crate::feed_forward::genome::Genome;
+use super::client::Client;
+use super::config::NeatConfig;
+use super::hall_of_fame::HallOfFameEntry;
+use super::adaptive_mutation::MutationSchedule;
+use super::population::SpeciesId;
+use super::Neat;
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+/* - Checkpoint -
+The evolving state of a Neat (innovation tables, clients and the hall of fame) as text
+the settings aren't saved, a checkpoint is loaded on top of the NeatConfig it was made with
+species aren't saved either, clients are re-sorted into species on load
+    generation <n>
+    phased_search phase=<phase> ... (only when using phased search)
+    mutation_schedule best_fitness=<score> stale_fitness=<n>  (only when using the stagnation schedule)
+    bank_node <inv_num> x=<x> y=<y> role=<role>  (role is left out when it's the one x gives, see NodeRole::from_x)
+    innovation <connection_inv_num> from=<node_inv_num> to=<node_inv_num>
+    replacement <connection_inv_num> node=<node_inv_num>
+    modulatory <connection_inv_num> node=<node_inv_num>
+    client <name> score=<score>
+    node ... / connection ... (genome of the client above, see feed_forward::serialization)
+    hall_of_fame fitness=<fitness> generation=<n> species=<name>  (species is left out when there wasn't one)
+    node ... / connection ... (genome of the hall of fame entry above)
+ */
+
+//(name, score, gene lines)
+type LoadedClient = (String, f64, Vec<(usize, GeneLine)>);
+//(fitness, generation, species, gene lines)
+type LoadedHallOfFameEntry = (f64, u64, Option<SpeciesId>, Vec<(usize, GeneLine)>);
+
+impl Neat {
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.checkpoint_to_text())
+    }
+
+    pub fn load_checkpoint<P: AsRef<Path>>(config: &NeatConfig, path: P) -> io::Result<Neat> {
+        let text = fs::read_to_string(path)?;
+        Neat::checkpoint_from_text(config, &text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn checkpoint_to_text(&self) -> String {
+        let mut text = String::from("# neat checkpoint\n");
+        text.push_str(&format!("generation {}\n", self.generation));
+
+        if let Some(phased_search) = &self.phased_search {
+            text.push_str(&format!("phased_search phase={} best_fitness={} stale_fitness={} lowest_complexity={} stale_complexity={}",
+                                   phased_search.phase, phased_search.best_fitness, phased_search.generations_since_fitness_improved,
+                                   phased_search.lowest_complexity, phased_search.generations_since_complexity_dropped));
+            if let Some(ceiling) = phased_search.complexity_ceiling {
+                text.push_str(&format!(" ceiling={}", ceiling));
+            }
+            text.push('\n');
+        }
+
+        if let MutationSchedule::Stagnation {..} = self.mutation_schedule {
+            text.push_str(&format!("mutation_schedule best_fitness={} stale_fitness={}\n", self.best_fitness, self.generations_since_fitness_improved));
+        }
+
+        let mut node_keys: Vec<&usize> = self.node_bank.keys().collect();
+        node_keys.sort_unstable();
+        for key in node_keys {
+            let node = &self.node_bank[key];
+            text.push_str(&format!("bank_node {} x={} y={}", key, node.get_x(), node.get_y()));
+            if node.get_role() != NodeRole::from_x(node.get_x()) {
+                text.push_str(&format!(" role={}", node.get_role()));
+            }
+            text.push('\n');
+        }
+
+        let mut innovations: Vec<(&(usize, usize), &usize)> = self.nodes_to_connection_map.iter().collect();
+        innovations.sort_unstable_by_key(|(_nodes, con_num)| **con_num);
+        for ((from, to), con_num) in innovations {
+            text.push_str(&format!("innovation {} from={} to={}\n", con_num, from, to));
+        }
+
+        let mut replacements: Vec<(&usize, &usize)> = self.connection_to_replacement_node_map.iter().collect();
+        replacements.sort_unstable();
+        for (con_num, node_num) in replacements {
+            text.push_str(&format!("replacement {} node={}\n", con_num, node_num));
+        }
+
+        let mut modulatory_nodes: Vec<(&usize, &usize)> = self.connection_to_modulatory_node_map.iter().collect();
+        modulatory_nodes.sort_unstable();
+        for (con_num, node_num) in modulatory_nodes {
+            text.push_str(&format!("modulatory {} node={}\n", con_num, node_num));
+        }
+
+        let mut client_names: Vec<&String> = self.clients.keys().collect();
+        client_names.sort_unstable();
+        for name in client_names {
+            let client = self.clients[name].borrow();
+            text.push_str(&format!("client {} score={}\n", name, client.get_score()));
+            client.get_genome().borrow().write_genes(&mut text);
+        }
+
+        for entry in &self.hall_of_fame {
+            text.push_str(&format!("hall_of_fame fitness={} generation={}", entry.fitness, entry.generation));
+            if let Some(species) = &entry.species {
+                text.push_str(&format!(" species={}", species));
+            }
+            text.push('\n');
+            entry.genome.write_genes(&mut text);
+        }
+
+        text
+    }
+
+    pub fn checkpoint_from_text(config: &NeatConfig, text: &str) -> Result<Neat, ParseError> {
+        let mut neat = Neat::from_config(config).map_err(|message| ParseError::new(0, message))?;
+        let default_bank_size = neat.node_bank.len();
+        let default_roles: Vec<NodeRole> = (0..default_bank_size).map(|inv_num| neat.node_bank[&inv_num].get_role()).collect();
+
+        neat.node_bank.clear();
+        neat.nodes_to_connection_map.clear();
+        neat.connection_to_replacement_node_map.clear();
+        neat.connection_to_modulatory_node_map.clear();
+
+        let mut clients: Vec<LoadedClient> = Vec::new();
+        let mut hall_of_fame: Vec<LoadedHallOfFameEntry> = Vec::new();
+        let mut genes_for_hall_of_fame = false; //whether gene lines belong to the last client or hall of fame line
+
+        for (line_i, line) in text.lines().enumerate() {
+            let line_num = line_i + 1;
+            let mut tokens = strip_comment(line).split_whitespace();
+
+            match tokens.next() {
+                None => continue,
+                Some("generation") => {
+                    match tokens.next() {
+                        None => return Err(ParseError::new(line_num, String::from("missing generation number"))),
+                        Some(value) => neat.generation = parse_value(line_num, "generation", value)?,
+                    }
+                },
+                Some("phased_search") => {
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["phase", "best_fitness", "stale_fitness", "lowest_complexity", "stale_complexity", "ceiling"])?;
+
+                    //only restored if the config still asks for phased search
+                    if let Some(phased_search) = &mut neat.phased_search {
+                        phased_search.phase = required_field::<String>(line_num, &fields, "phase")?.parse().map_err(|message| ParseError::new(line_num, message))?;
+                        phased_search.best_fitness = required_field(line_num, &fields, "best_fitness")?;
+                        phased_search.generations_since_fitness_improved = required_field(line_num, &fields, "stale_fitness")?;
+                        phased_search.lowest_complexity = required_field(line_num, &fields, "lowest_complexity")?;
+                        phased_search.generations_since_complexity_dropped = required_field(line_num, &fields, "stale_complexity")?;
+                        phased_search.complexity_ceiling = match fields.get("ceiling") {
+                            None => None,
+                            Some(value) => Some(parse_value(line_num, "ceiling", value)?),
+                        };
+                    }
+                },
+                Some("mutation_schedule") => {
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["best_fitness", "stale_fitness"])?;
+                    neat.best_fitness = required_field(line_num, &fields, "best_fitness")?;
+                    neat.generations_since_fitness_improved = required_field(line_num, &fields, "stale_fitness")?;
+                },
+                Some("bank_node") => {
+                    let inv_num: usize = next_number(line_num, &mut tokens)?;
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["x", "y", "role"])?;
+                    let x = required_field(line_num, &fields, "x")?;
+                    let role = match fields.get("role") {
+                        None => NodeRole::from_x(x),
+                        Some(value) => value.parse().map_err(|message| ParseError::new(line_num, message))?,
+                    };
+                    let node = NodeGene::new_with_role(inv_num, x, required_field(line_num, &fields, "y")?, role);
+                    neat.node_bank.insert(inv_num, Rc::new(node));
+                },
+                Some("innovation") => {
+                    let con_num: usize = next_number(line_num, &mut tokens)?;
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["from", "to"])?;
+                    neat.nodes_to_connection_map.insert((required_field(line_num, &fields, "from")?, required_field(line_num, &fields, "to")?), con_num);
+                },
+                Some("replacement") => {
+                    let con_num: usize = next_number(line_num, &mut tokens)?;
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["node"])?;
+                    neat.connection_to_replacement_node_map.insert(con_num, required_field(line_num, &fields, "node")?);
+                },
+                Some("modulatory") => {
+                    let con_num: usize = next_number(line_num, &mut tokens)?;
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["node"])?;
+                    neat.connection_to_modulatory_node_map.insert(con_num, required_field(line_num, &fields, "node")?);
+                },
+                Some("client") => {
+                    let name = match tokens.next() {
+                        None => return Err(ParseError::new(line_num, String::from("missing client name"))),
+                        Some(name) => String::from(name),
+                    };
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["score"])?;
+                    clients.push((name, required_field(line_num, &fields, "score")?, Vec::new()));
+                    genes_for_hall_of_fame = false;
+                },
+                Some("hall_of_fame") => {
+                    let fields = parse_fields(line_num, tokens)?;
+                    check_known_fields(line_num, &fields, &["fitness", "generation", "species"])?;
+                    let species = fields.get("species").map(|name| SpeciesId::new(&String::from(*name)));
+                    hall_of_fame.push((required_field(line_num, &fields, "fitness")?, required_field(line_num, &fields, "generation")?, species, Vec::new()));
+                    genes_for_hall_of_fame = true;
+                },
+                Some(_) => {
+                    let gene_lines = if genes_for_hall_of_fame {
+                        hall_of_fame.last_mut().map(|(_fitness, _generation, _species, gene_lines)| gene_lines)
+                    } else {
+                        clients.last_mut().map(|(_name, _score, gene_lines)| gene_lines)
+                    };
+                    match gene_lines {
+                        None => return Err(ParseError::new(line_num, String::from("gene given before any client"))),
+                        Some(gene_lines) => {
+                            if let Some(gene_line) = parse_gene_line(line_num, line)? {
+                                gene_lines.push((line_num, gene_line));
+                            }
+                        },
+                    }
+                },
+            }
+        }
+
+        //the input/output nodes have to line up with the config
+        if neat.node_bank.len() < default_bank_size {
+            return Err(ParseError::new(0, format!("checkpoint has {} nodes but the config needs at least {}", neat.node_bank.len(), default_bank_size)));
+        }
+        for (inv_num, role) in default_roles.iter().enumerate() {
+            match neat.node_bank.get(&inv_num) {
+                Some(node) if node.get_role() == *role => {},
+                _ => return Err(ParseError::new(0, format!("node {} should have role {} to match the config", inv_num, role))),
+            }
+        }
+
+        for (name, score, gene_lines) in clients {
+            let genome: Genome = Genome::from_gene_lines(gene_lines, &neat.node_bank)?;
+            for node_inv_num in genome.nodes.keys() {
+                if !neat.node_bank.contains_key(node_inv_num) {
+                    return Err(ParseError::new(0, format!("client {} has node {} which isn't in the node bank", name, node_inv_num)));
+                }
+            }
+
+            neat.add_loaded_client(name, score, genome);
+        }
+
+        //only kept if the config still asks for a hall of fame
+        if neat.hall_of_fame_size > 0 {
+            for (fitness, generation, species, gene_lines) in hall_of_fame {
+                let genome: Genome = Genome::from_gene_lines(gene_lines, &neat.node_bank)?;
+                neat.add_to_hall_of_fame(HallOfFameEntry {genome, fitness, generation, species});
+            }
+        }
+
+        //from_config numbered the seed genome with the tables that were just replaced
+        if let Some(seed_genome) = neat.seed_genome.take() {
+            neat.set_seed_genome(&seed_genome).map_err(|message| ParseError::new(0, format!("can't seed from the checkpoint's node bank: {}", message)))?;
+        }
+
+        neat.sort_clients_into_species();
+
+        Ok(neat)
+    }
+
+    fn add_loaded_client(&mut self, name: String, score: f64, genome: Genome) {
+        let mut client = Client::new(Rc::new(RefCell::new(genome)), self.get_default_species());
+        client.set_name(String::clone(&name));
+        client.set_score(score);
+        client.generate_calculator(self.activation_function);
+
+        let client_ref = Rc::new(RefCell::new(client));
+        self.get_default_species().borrow_mut().force_put(Rc::clone(&client_ref), self.get_default_species());
+        self.clients.insert(name, client_ref);
+    }
+}
+
+fn next_number<'a, I>(line: usize, tokens: &mut I) -> Result<usize, ParseError> where
+    I: Iterator<Item=&'a str> {
+    match tokens.next() {
+        None => Err(ParseError::new(line, String::from("missing innovation number"))),
+        Some(token) => parse_value(line, "innovation number", token),
+    }
+}
@@ -1,5 +1,6 @@
 use super::species::Species;
 use crate::feed_forward::calculator::Calculator;
+use crate::activation_functions::Activation;
 use crate::feed_forward::genome::Genome;
 use std::cell::{RefCell};
 use std::rc::Rc;
@@ -59,6 +60,12 @@ impl Client {
         }
     }
 
+    pub(super) fn reset_calculator_state(&self) {
+        if let Some(calculator) = &self.calculator {
+            calculator.reset_state();
+        }
+    }
+
     pub(super) fn get_calculator(&self) -> Option<Rc<Calculator<fn(f64)->f64>>> {
         match &self.calculator {
             None => None,
@@ -70,9 +77,9 @@ impl Client {
         self.genome = genome;
     }
 
-    pub(super) fn generate_calculator(&mut self, activation_function: fn(f64)->f64) {
+    pub(super) fn generate_calculator(&mut self, activation_function: Activation) {
         self.calculator = Some(Rc::from(Calculator::new_from_ref(
-            Rc::clone(&self.genome), activation_function
+            Rc::clone(&self.genome), activation_function.function()
         )));
     }
 
@@ -88,6 +95,10 @@ impl Client {
         &self.name
     }
 
+    pub(super) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     //resets the client to a 'start of run' state
     pub(super) fn reset_client(&mut self) {
         self.score = 0.0;
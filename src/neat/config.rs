@@ -0,0 +1,370 @@
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::serialization::{ParseError, strip_comment, parse_value};
+use crate::feed_forward::genome::Genome;
+use super::Neat;
+use super::phased_search::PhasedSearch;
+use super::weight_distribution::WeightDistribution;
+use super::adaptive_mutation::MutationSchedule;
+use super::mutation::{Mutation, MutationMode};
+use super::initial_topology::InitialTopology;
+use crate::output_decoder::OutputDecoder;
+
+/* - NeatConfig -
+Everything Neat::new takes (plus the newer settings it doesn't), in a form that can be read from/written to a file
+    one `key = value` per line, '#' starts a comment
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeatConfig {
+    pub input_size: usize,
+    pub output_size: usize,
+    pub activation_function: Activation,
+    pub species_distance_threshold: f64,
+
+    //probability (0 to 1) of mutating, 0 never mutates (see neat::mutation)
+    //an older config's `mutate_chance_<name> = x` (1 in x) is read as 1/x
+    pub mutate_probability_add_node: f64,
+    pub mutate_probability_add_connection: f64,
+    pub mutate_probability_random_weight: f64,
+    pub mutate_probability_weight_shift: f64,
+    pub mutate_probability_toggle_connection: f64,
+    pub mutate_probability_remove_connection: f64,
+    pub mutate_probability_remove_node: f64,
+    pub mutate_probability_add_modulatory_node: f64, //see feed_forward::plasticity
+    pub mutate_probability_aggregation: f64,
+    pub mutate_probability_hebbian: f64, //shifts a hebbian rule coefficient (see feed_forward::plasticity)
+    pub mutate_probability_time_constant: f64, //shifts a node's CTRNN time constant (see feed_forward::ctrnn)
+    pub mutate_probability_neuron: f64, //shifts a node's spiking neuron parameter (see feed_forward::spiking)
+    pub aggregation_options: Vec<Aggregation>, //written as a comma separated list
+
+    //see neat::mutation
+    pub mutation_mode: MutationMode,
+    pub mutation_rounds: u64, //times mutate_random mutates a genome
+
+    //adaptive mutation (see neat::adaptive_mutation)
+    pub mutation_rate_adaptation: f64, //0 keeps the five main probabilities the same for every genome
+    pub mutation_schedule: MutationSchedule, //eg. `decay(0.99, 0.2)` or `stagnation(10, 1.5, 4)`
+
+    pub proportion_to_kill: f64,
+    pub c1: f64,
+    pub c2: f64,
+    pub c3: f64,
+    pub random_weight_max: f64,
+    pub random_weight_shift_max: f64,
+    pub max_mutation_attempts: u64,
+
+    //None keeps uniform(random_weight_max)/uniform(random_weight_shift_max)
+    pub random_weight_distribution: Option<WeightDistribution>,
+    pub weight_shift_distribution: Option<WeightDistribution>,
+    pub weight_mutation_rate: f64, //0 mutates one connection per weight mutation, otherwise the chance for each connection
+    pub weight_min: f64,
+    pub weight_max: f64,
+
+    //phased search (see neat::phased_search)
+    pub phased_search: bool,
+    pub phase_complexity_threshold: f64,
+    pub phase_fitness_plateau_generations: u64,
+    pub phase_complexity_plateau_generations: u64,
+
+    pub hall_of_fame_size: usize, //0 doesn't keep a hall of fame
+    pub seed_genome: Option<String>, //path of a genome file new clients start from (see neat::seed)
+    pub output_decoder: OutputDecoder, //see output_decoder, eg. `softmax` or `scale(0:1,-1:1)`
+
+    //new clients (see neat::initial_topology)
+    pub initial_topology: InitialTopology, //eg. `full_hidden(2)` or `sparse(0.5)`
+    pub initial_mutate_full: bool,
+}
+
+impl Default for NeatConfig {
+    fn default() -> Self {
+        NeatConfig {
+            input_size: 2,
+            output_size: 1,
+            activation_function: Activation::Sigmoid,
+            species_distance_threshold: 4.0,
+            mutate_probability_add_node: 0.05,
+            mutate_probability_add_connection: 0.1,
+            mutate_probability_random_weight: 0.1,
+            mutate_probability_weight_shift: 0.5,
+            mutate_probability_toggle_connection: 0.02,
+            mutate_probability_remove_connection: 0.0,
+            mutate_probability_remove_node: 0.0,
+            mutate_probability_add_modulatory_node: 0.0,
+            mutate_probability_aggregation: 0.0,
+            mutate_probability_hebbian: 0.0,
+            mutate_probability_time_constant: 0.0,
+            mutate_probability_neuron: 0.0,
+            aggregation_options: Aggregation::ALL.to_vec(),
+            mutation_mode: MutationMode::Independent,
+            mutation_rounds: 1,
+            mutation_rate_adaptation: 0.0,
+            mutation_schedule: MutationSchedule::Fixed,
+            proportion_to_kill: 0.2,
+            c1: 1.0,
+            c2: 1.0,
+            c3: 0.4,
+            random_weight_max: 2.0,
+            random_weight_shift_max: 0.5,
+            max_mutation_attempts: 100,
+            random_weight_distribution: None,
+            weight_shift_distribution: None,
+            weight_mutation_rate: 0.0,
+            weight_min: f64::NEG_INFINITY,
+            weight_max: f64::INFINITY,
+            phased_search: false,
+            phase_complexity_threshold: 20.0,
+            phase_fitness_plateau_generations: 0,
+            phase_complexity_plateau_generations: 5,
+            hall_of_fame_size: 0,
+            seed_genome: None,
+            output_decoder: OutputDecoder::Raw,
+            initial_topology: InitialTopology::FullyConnected,
+            initial_mutate_full: true,
+        }
+    }
+}
+
+impl NeatConfig {
+    //starts from the defaults, keys missing from the text keep their default value
+    pub fn parse(text: &str) -> Result<NeatConfig, ParseError> {
+        let mut config = NeatConfig::default();
+
+        for (line_i, line) in text.lines().enumerate() {
+            if let Some((key, value)) = parse_config_line(line_i + 1, line)? {
+                config.set(key, value).map_err(|message| ParseError::new(line_i + 1, message))?;
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if let Some(name) = key.strip_prefix("mutate_chance_") {
+            return self.set_mutation_chance(name, value);
+        }
+
+        let result = match key {
+            "input_size" => parse_value(0, key, value).map(|v| self.input_size = v),
+            "output_size" => parse_value(0, key, value).map(|v| self.output_size = v),
+            "activation_function" => value.parse().map(|v| self.activation_function = v).map_err(|message| ParseError::new(0, message)),
+            "species_distance_threshold" => parse_value(0, key, value).map(|v| self.species_distance_threshold = v),
+            "mutate_probability_add_node" => parse_probability(key, value).map(|v| self.mutate_probability_add_node = v),
+            "mutate_probability_add_connection" => parse_probability(key, value).map(|v| self.mutate_probability_add_connection = v),
+            "mutate_probability_random_weight" => parse_probability(key, value).map(|v| self.mutate_probability_random_weight = v),
+            "mutate_probability_weight_shift" => parse_probability(key, value).map(|v| self.mutate_probability_weight_shift = v),
+            "mutate_probability_toggle_connection" => parse_probability(key, value).map(|v| self.mutate_probability_toggle_connection = v),
+            "mutate_probability_remove_connection" => parse_probability(key, value).map(|v| self.mutate_probability_remove_connection = v),
+            "mutate_probability_remove_node" => parse_probability(key, value).map(|v| self.mutate_probability_remove_node = v),
+            "mutate_probability_add_modulatory_node" => parse_probability(key, value).map(|v| self.mutate_probability_add_modulatory_node = v),
+            "mutate_probability_aggregation" => parse_probability(key, value).map(|v| self.mutate_probability_aggregation = v),
+            "mutate_probability_hebbian" => parse_probability(key, value).map(|v| self.mutate_probability_hebbian = v),
+            "mutate_probability_time_constant" => parse_probability(key, value).map(|v| self.mutate_probability_time_constant = v),
+            "mutate_probability_neuron" => parse_probability(key, value).map(|v| self.mutate_probability_neuron = v),
+            "aggregation_options" => {
+                let options = value.split(',').map(|name| name.parse()).collect::<Result<Vec<Aggregation>, String>>()?;
+                self.aggregation_options = options;
+                Ok(())
+            },
+            "mutation_mode" => value.parse().map(|v| self.mutation_mode = v).map_err(|message| ParseError::new(0, message)),
+            "mutation_rounds" => parse_value(0, key, value).map(|v| self.mutation_rounds = v),
+            "mutation_rate_adaptation" => {
+                let adaptation: f64 = parse_value(0, key, value).map_err(|error| error.message)?;
+                if !(adaptation >= 0.0 && adaptation.is_finite()) {
+                    return Err(format!("mutation_rate_adaptation has to be a positive number, got {}", adaptation));
+                }
+                self.mutation_rate_adaptation = adaptation;
+                Ok(())
+            },
+            "mutation_schedule" => value.parse().map(|v| self.mutation_schedule = v).map_err(|message| ParseError::new(0, message)),
+            "proportion_to_kill" => parse_value(0, key, value).map(|v| self.proportion_to_kill = v),
+            "c1" => parse_value(0, key, value).map(|v| self.c1 = v),
+            "c2" => parse_value(0, key, value).map(|v| self.c2 = v),
+            "c3" => parse_value(0, key, value).map(|v| self.c3 = v),
+            "random_weight_max" => parse_value(0, key, value).map(|v| self.random_weight_max = v),
+            "random_weight_shift_max" => parse_value(0, key, value).map(|v| self.random_weight_shift_max = v),
+            "max_mutation_attempts" => parse_value(0, key, value).map(|v| self.max_mutation_attempts = v),
+            "random_weight_distribution" => value.parse().map(|v| self.random_weight_distribution = Some(v)).map_err(|message| ParseError::new(0, message)),
+            "weight_shift_distribution" => value.parse().map(|v| self.weight_shift_distribution = Some(v)).map_err(|message| ParseError::new(0, message)),
+            "weight_mutation_rate" => {
+                let rate: f64 = parse_value(0, key, value).map_err(|error| error.message)?;
+                if !(0.0..=1.0).contains(&rate) {
+                    return Err(format!("weight_mutation_rate has to be between 0 and 1, got {}", rate));
+                }
+                self.weight_mutation_rate = rate;
+                Ok(())
+            },
+            "weight_min" => parse_value(0, key, value).map(|v| self.weight_min = v),
+            "weight_max" => parse_value(0, key, value).map(|v| self.weight_max = v),
+            "phased_search" => parse_value(0, key, value).map(|v| self.phased_search = v),
+            "phase_complexity_threshold" => parse_value(0, key, value).map(|v| self.phase_complexity_threshold = v),
+            "phase_fitness_plateau_generations" => parse_value(0, key, value).map(|v| self.phase_fitness_plateau_generations = v),
+            "phase_complexity_plateau_generations" => parse_value(0, key, value).map(|v| self.phase_complexity_plateau_generations = v),
+            "hall_of_fame_size" => parse_value(0, key, value).map(|v| self.hall_of_fame_size = v),
+            "seed_genome" => {
+                self.seed_genome = Some(String::from(value));
+                Ok(())
+            },
+            "output_decoder" => value.parse().map(|v| self.output_decoder = v).map_err(|message| ParseError::new(0, message)),
+            "initial_topology" => value.parse().map(|v| self.initial_topology = v).map_err(|message| ParseError::new(0, message)),
+            "initial_mutate_full" => parse_value(0, key, value).map(|v| self.initial_mutate_full = v),
+            _ => return Err(format!("unknown config key '{}'", key)),
+        };
+
+        result.map_err(|error| error.message)
+    }
+
+    //an older config's 1 in x chance (0 being never), set as that mutation's probability
+    fn set_mutation_chance(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let mutation: Mutation = name.parse()?;
+        let chance: u64 = parse_value(0, "mutation chance", value).map_err(|error| error.message)?;
+        let probability = if chance == 0 { 0.0 } else { 1.0 / chance as f64 };
+        self.set(&format!("mutate_probability_{}", mutation), &probability.to_string())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        text.push_str(&format!("input_size = {}\n", self.input_size));
+        text.push_str(&format!("output_size = {}\n", self.output_size));
+        text.push_str(&format!("activation_function = {}\n", self.activation_function));
+        text.push_str(&format!("species_distance_threshold = {}\n", self.species_distance_threshold));
+        text.push_str(&format!("mutate_probability_add_node = {}\n", self.mutate_probability_add_node));
+        text.push_str(&format!("mutate_probability_add_connection = {}\n", self.mutate_probability_add_connection));
+        text.push_str(&format!("mutate_probability_random_weight = {}\n", self.mutate_probability_random_weight));
+        text.push_str(&format!("mutate_probability_weight_shift = {}\n", self.mutate_probability_weight_shift));
+        text.push_str(&format!("mutate_probability_toggle_connection = {}\n", self.mutate_probability_toggle_connection));
+        text.push_str(&format!("mutate_probability_remove_connection = {}\n", self.mutate_probability_remove_connection));
+        text.push_str(&format!("mutate_probability_remove_node = {}\n", self.mutate_probability_remove_node));
+        text.push_str(&format!("mutate_probability_add_modulatory_node = {}\n", self.mutate_probability_add_modulatory_node));
+        text.push_str(&format!("mutate_probability_aggregation = {}\n", self.mutate_probability_aggregation));
+        text.push_str(&format!("mutate_probability_hebbian = {}\n", self.mutate_probability_hebbian));
+        text.push_str(&format!("mutate_probability_time_constant = {}\n", self.mutate_probability_time_constant));
+        text.push_str(&format!("mutate_probability_neuron = {}\n", self.mutate_probability_neuron));
+        let aggregation_names: Vec<&str> = self.aggregation_options.iter().map(|aggregation| aggregation.name()).collect();
+        text.push_str(&format!("aggregation_options = {}\n", aggregation_names.join(", ")));
+        text.push_str(&format!("mutation_mode = {}\n", self.mutation_mode));
+        text.push_str(&format!("mutation_rounds = {}\n", self.mutation_rounds));
+        text.push_str(&format!("mutation_rate_adaptation = {}\n", self.mutation_rate_adaptation));
+        text.push_str(&format!("mutation_schedule = {}\n", self.mutation_schedule));
+        text.push_str(&format!("proportion_to_kill = {}\n", self.proportion_to_kill));
+        text.push_str(&format!("c1 = {}\n", self.c1));
+        text.push_str(&format!("c2 = {}\n", self.c2));
+        text.push_str(&format!("c3 = {}\n", self.c3));
+        text.push_str(&format!("random_weight_max = {}\n", self.random_weight_max));
+        text.push_str(&format!("random_weight_shift_max = {}\n", self.random_weight_shift_max));
+        text.push_str(&format!("max_mutation_attempts = {}\n", self.max_mutation_attempts));
+        if let Some(distribution) = &self.random_weight_distribution {
+            text.push_str(&format!("random_weight_distribution = {}\n", distribution));
+        }
+        if let Some(distribution) = &self.weight_shift_distribution {
+            text.push_str(&format!("weight_shift_distribution = {}\n", distribution));
+        }
+        text.push_str(&format!("weight_mutation_rate = {}\n", self.weight_mutation_rate));
+        text.push_str(&format!("weight_min = {}\n", self.weight_min));
+        text.push_str(&format!("weight_max = {}\n", self.weight_max));
+        text.push_str(&format!("phased_search = {}\n", self.phased_search));
+        text.push_str(&format!("phase_complexity_threshold = {}\n", self.phase_complexity_threshold));
+        text.push_str(&format!("phase_fitness_plateau_generations = {}\n", self.phase_fitness_plateau_generations));
+        text.push_str(&format!("phase_complexity_plateau_generations = {}\n", self.phase_complexity_plateau_generations));
+        text.push_str(&format!("hall_of_fame_size = {}\n", self.hall_of_fame_size));
+        if let Some(path) = &self.seed_genome {
+            text.push_str(&format!("seed_genome = {}\n", path));
+        }
+        text.push_str(&format!("output_decoder = {}\n", self.output_decoder));
+        text.push_str(&format!("initial_topology = {}\n", self.initial_topology));
+        text.push_str(&format!("initial_mutate_full = {}\n", self.initial_mutate_full));
+
+        text
+    }
+}
+
+//a mutate_probability_<name> value, 0 to 1
+fn parse_probability(key: &str, value: &str) -> Result<f64, ParseError> {
+    let probability: f64 = parse_value(0, key, value)?;
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(ParseError::new(0, format!("{} has to be between 0 and 1, got {}", key, probability)));
+    }
+    Ok(probability)
+}
+
+//returns None for blank/comment lines
+pub fn parse_config_line(line: usize, text: &str) -> Result<Option<(&str, &str)>, ParseError> {
+    let text = strip_comment(text).trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    match text.split_once('=') {
+        None => Err(ParseError::new(line, format!("expected 'key = value', got '{}'", text))),
+        Some((key, value)) => Ok(Some((key.trim(), value.trim()))),
+    }
+}
+
+impl Neat {
+    pub fn from_config(config: &NeatConfig) -> Result<Neat, String> {
+        if config.weight_min > config.weight_max {
+            return Err(format!("weight_min ({}) is bigger than weight_max ({})", config.weight_min, config.weight_max));
+        }
+
+        let mut neat = Neat::new(config.input_size, config.output_size,
+                                 config.activation_function,
+                                 config.species_distance_threshold,
+                                 config.mutate_probability_add_node,
+                                 config.mutate_probability_add_connection,
+                                 config.mutate_probability_random_weight,
+                                 config.mutate_probability_weight_shift,
+                                 config.mutate_probability_toggle_connection,
+                                 config.proportion_to_kill, config.c1,
+                                 config.c2, config.c3,
+                                 config.random_weight_max,
+                                 config.random_weight_shift_max,
+                                 config.max_mutation_attempts);
+
+        //settings Neat::new doesn't take
+        neat.mutate_probability_remove_connection = config.mutate_probability_remove_connection;
+        neat.mutate_probability_remove_node = config.mutate_probability_remove_node;
+        neat.mutate_probability_add_modulatory_node = config.mutate_probability_add_modulatory_node;
+        neat.mutate_probability_aggregation = config.mutate_probability_aggregation;
+        neat.mutate_probability_hebbian = config.mutate_probability_hebbian;
+        neat.mutate_probability_time_constant = config.mutate_probability_time_constant;
+        neat.mutate_probability_neuron = config.mutate_probability_neuron;
+        for mutation in Mutation::ALL.iter() {
+            let probability = neat.get_mutation_probability(*mutation);
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(format!("mutate_probability_{} has to be between 0 and 1, got {}", mutation, probability));
+            }
+        }
+        neat.aggregation_options = config.aggregation_options.clone();
+        neat.mutation_mode = config.mutation_mode;
+        neat.mutation_rounds = config.mutation_rounds;
+        neat.mutation_rate_adaptation = config.mutation_rate_adaptation;
+        neat.mutation_schedule = config.mutation_schedule;
+
+        if let Some(distribution) = config.random_weight_distribution {
+            neat.random_weight_distribution = distribution;
+        }
+        if let Some(distribution) = config.weight_shift_distribution {
+            neat.weight_shift_distribution = distribution;
+        }
+        neat.weight_mutation_rate = config.weight_mutation_rate;
+        neat.weight_bounds = (config.weight_min, config.weight_max);
+
+        if config.phased_search {
+            neat.phased_search = Some(PhasedSearch::new(config.phase_complexity_threshold,
+                                                        config.phase_fitness_plateau_generations,
+                                                        config.phase_complexity_plateau_generations));
+        }
+        neat.hall_of_fame_size = config.hall_of_fame_size;
+        neat.initial_topology = config.initial_topology;
+        neat.initial_mutate_full = config.initial_mutate_full;
+        neat.set_output_decoder(config.output_decoder.clone()).map_err(|message| format!("bad output_decoder: {}", message))?;
+
+        if let Some(path) = &config.seed_genome {
+            let genome = Genome::load(path).map_err(|error| format!("can't load seed genome {}: {}", path, error))?;
+            neat.set_seed_genome(&genome).map_err(|message| format!("can't seed from {}: {}", path, message))?;
+        }
+
+        Ok(neat)
+    }
+}
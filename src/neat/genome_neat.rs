@@ -2,8 +2,12 @@ use rand::prelude::*;
 use crate::feed_forward::gene::Gene;
 use crate::feed_forward::genome::Genome;
 use crate::feed_forward::connection_gene::ConnectionGene;
+use crate::feed_forward::spiking::NeuronParameters;
+use super::mutation::{Mutation, MutationMode};
+use crate::feed_forward::node_gene::{NodeGene, NodeRole, MIN_TIME_CONSTANT};
 use super::Neat;
 use std::rc::Rc;
+use std::collections::HashSet;
 
 pub(super) struct GenomeNeatMethods {}
 
@@ -125,19 +129,41 @@ impl GenomeNeatMethods {
             index_g0 += 1;
         }
 
+        //input/output nodes are in every genome, even when nothing connects to them
+        let mut nodes: Vec<Rc<NodeGene>> = genome0.nodes.values()
+            .filter(|node| node.get_role() != NodeRole::Hidden)
+            .map(Rc::clone)
+            .collect();
+
         //fill out the nodes in the new genome
-        for index_in_new in new_genome.connections.keys().map(|key| *key).collect::<Vec<usize>>() {
-            if let Some(connection) = new_genome.connections.get(&index_in_new) {
-                let (from_node, to_node) = (Rc::clone(&connection.from), Rc::clone(&connection.to));
+        for connection in new_genome.connections.values() {
+            nodes.push(Rc::clone(&connection.from));
+            nodes.push(Rc::clone(&connection.to));
+        }
 
-                //handle from node
-                new_genome.add_node(from_node);
+        for node in nodes {
+            //a node both parents have is randomly copied from one of them, like connections (their aggregations can differ)
+            let node = match (genome0.nodes.get(&node.get_innovation_number()), genome1.nodes.get(&node.get_innovation_number())) {
+                (Some(node0), Some(node1)) => if rng.gen::<bool>() { Rc::clone(node0) } else { Rc::clone(node1) },
+                _ => node,
+            };
 
-                //handle to node
-                new_genome.add_node(to_node);
-            }
+            new_genome.add_node(node);
+        }
+
+        //connections use the chosen nodes
+        for connection in new_genome.connections.values_mut() {
+            connection.from = Rc::clone(&new_genome.nodes[&connection.from.get_innovation_number()]);
+            connection.to = Rc::clone(&new_genome.nodes[&connection.to.get_innovation_number()]);
         }
 
+        //mutation probabilities come from one parent, like a gene (see neat::adaptive_mutation)
+        new_genome.mutation_rates = match (genome0.mutation_rates, genome1.mutation_rates) {
+            (Some(rates0), Some(rates1)) => if rng.gen::<bool>() { Some(rates0) } else { Some(rates1) },
+            (rates0, rates1) => rates0.or(rates1),
+        };
+
+        new_genome.debug_validate_from(&[genome0, genome1], "breeding");
         new_genome
     }
 }
@@ -146,12 +172,49 @@ pub(super) struct GenomeMutator {}
 
 impl GenomeMutator {
     pub(crate) fn mutate_random(neat: &mut Neat, genome: &mut Genome) {
-        if (0..neat.mutate_chance_add_node).choose(&mut neat.cached_rng) == Some(0) { GenomeMutator::mutate_add_node(neat, genome); }
-        if (0..neat.mutate_chance_add_connection).choose(&mut neat.cached_rng) == Some(0) { GenomeMutator::mutate_add_connection(neat, genome); }
-        if (0..neat.mutate_chance_random_weight).choose(&mut neat.cached_rng) == Some(0) { GenomeMutator::mutate_random_weight(neat, genome); }
-        if (0..neat.mutate_chance_weight_shift).choose(&mut neat.cached_rng) == Some(0) { GenomeMutator::mutate_weight_shift(neat, genome); }
-        if (0..neat.mutate_chance_toggle_connection).choose(&mut neat.cached_rng) == Some(0) { GenomeMutator::mutate_toggle_connection(neat, genome); }
+        //the five main probabilities can be the genome's own and/or scheduled (see neat::adaptive_mutation)
+        let rates = neat.next_mutation_rates(genome);
+
+        //see neat::mutation
+        for _ in 0..neat.get_mutation_rounds() {
+            //phased search only allows one of adding/removing structure at a time
+            let probabilities = neat.get_allowed_mutation_probabilities(&rates);
+
+            match neat.get_mutation_mode() {
+                MutationMode::Independent => {
+                    for (mutation, probability) in probabilities {
+                        if neat.cached_rng.gen::<f64>() < probability {
+                            GenomeMutator::mutate(neat, genome, mutation);
+                        }
+                    }
+                },
+                MutationMode::ExactlyOne => {
+                    //errors when every probability is 0, then nothing happens
+                    if let Ok((mutation, _probability)) = probabilities.choose_weighted(&mut neat.cached_rng, |(_mutation, probability)| *probability) {
+                        GenomeMutator::mutate(neat, genome, *mutation);
+                    }
+                },
+            }
+        }
+
+        genome.debug_validate("mutation");
+    }
 
+    pub(crate) fn mutate(neat: &mut Neat, genome: &mut Genome, mutation: Mutation) -> bool {
+        match mutation {
+            Mutation::AddNode => GenomeMutator::mutate_add_node(neat, genome),
+            Mutation::AddConnection => GenomeMutator::mutate_add_connection(neat, genome),
+            Mutation::AddModulatoryNode => GenomeMutator::mutate_add_modulatory_node(neat, genome),
+            Mutation::RandomWeight => GenomeMutator::mutate_random_weight(neat, genome),
+            Mutation::WeightShift => GenomeMutator::mutate_weight_shift(neat, genome),
+            Mutation::ToggleConnection => GenomeMutator::mutate_toggle_connection(neat, genome),
+            Mutation::Aggregation => GenomeMutator::mutate_aggregation(neat, genome),
+            Mutation::Hebbian => GenomeMutator::mutate_hebbian(neat, genome),
+            Mutation::TimeConstant => GenomeMutator::mutate_time_constant(neat, genome),
+            Mutation::Neuron => GenomeMutator::mutate_neuron(neat, genome),
+            Mutation::RemoveConnection => GenomeMutator::mutate_remove_connection(neat, genome),
+            Mutation::RemoveNode => GenomeMutator::mutate_remove_node(neat, genome),
+        }
     }
 
     pub(crate) fn mutate_full(neat: &mut Neat, genome: &mut Genome) {
@@ -160,6 +223,8 @@ impl GenomeMutator {
         GenomeMutator::mutate_random_weight(neat, genome);
         GenomeMutator::mutate_weight_shift(neat, genome);
         GenomeMutator::mutate_toggle_connection(neat, genome);
+
+        genome.debug_validate("mutation");
     }
 
     pub(super) fn mutate_add_node(neat: &mut Neat, genome: &mut Genome) -> bool {
@@ -188,6 +253,28 @@ impl GenomeMutator {
         false
     }
 
+    //add a modulatory node next to a random enabled connection (from -> to), it takes input from the connection's from
+    //node and modulates the to node's learning (see feed_forward::plasticity), the connection itself stays
+    //modulatory nodes have their own innovation numbers per connection, separate from mutate_add_node's
+    pub(super) fn mutate_add_modulatory_node(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let connection = match genome.connections.values().filter(|connection| connection.enabled && !connection.from.is_modulatory()).choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(connection) => connection.clone(),
+        };
+
+        let (input_connection, mut modulating_connection, node) = neat.get_modulatory_node_for_connection(&connection);
+        if genome.nodes.contains_key(&node.get_innovation_number()) {
+            return false; //the connection has one already
+        }
+        modulating_connection.weight = neat.get_new_random_weight();
+
+        genome.add_node(Rc::new(node.with_modulatory(true)));
+        genome.add_connection(input_connection);
+        genome.add_connection(modulating_connection);
+
+        true
+    }
+
     pub(super) fn mutate_add_connection(neat: &mut Neat, genome: &mut Genome) -> bool {
         let mut i = 0;
 
@@ -211,7 +298,7 @@ impl GenomeMutator {
                                 true
                             } else {
                                 let mut connection = neat.new_connection(con_inv_num, node0.get_innovation_number(), node1.get_innovation_number());
-                                connection.weight = neat.cached_rng.gen_range(-(1.0 * neat.get_random_weight_max())..(1.0 * neat.get_random_weight_max()));
+                                connection.weight = neat.get_new_random_weight();
 
                                 genome.add_connection(connection);
                                 return true;
@@ -234,6 +321,10 @@ impl GenomeMutator {
 
     //randomly change a connections weight
     pub(super) fn mutate_random_weight(neat: &mut Neat, genome: &mut Genome) -> bool {
+        if neat.get_weight_mutation_rate() > 0.0 {
+            return GenomeMutator::mutate_every_weight(neat, genome, |neat, _weight| neat.get_new_random_weight());
+        }
+
         let mut i = 0;
 
         //scuffed do/while (the stuff is in the while's expression)
@@ -248,7 +339,7 @@ impl GenomeMutator {
                 let con_key = &con_key.clone(); //because we borrow .connections as immutable then mutable so we need to drop the immutable reference
                 if let Some(connection) = genome.connections.get_mut(con_key) {
                     if connection.enabled {
-                        connection.weight = neat.cached_rng.gen_range(-(1.0 * neat.get_random_weight_max())..(1.0 * neat.get_random_weight_max()));
+                        connection.weight = neat.get_new_random_weight();
                         return true;
                     } else {
                         true
@@ -265,6 +356,10 @@ impl GenomeMutator {
     }
 
     pub(super) fn mutate_weight_shift(neat: &mut Neat, genome: &mut Genome) -> bool {
+        if neat.get_weight_mutation_rate() > 0.0 {
+            return GenomeMutator::mutate_every_weight(neat, genome, |neat, weight| neat.get_shifted_weight(weight));
+        }
+
         let mut i = 0;
 
         //scuffed do/while (the stuff is in the while's expression)
@@ -280,7 +375,7 @@ impl GenomeMutator {
                 if let Some(connection) = genome.connections.get_mut(con_key) {
                     if connection.enabled {
                         //randomly shift weight
-                        connection.weight += neat.cached_rng.gen_range(-(1.0 * neat.get_random_weight_shift_max())..(1.0 * neat.get_random_weight_shift_max()));
+                        connection.weight = neat.get_shifted_weight(connection.weight);
                         return true;
                     }
                     else {
@@ -299,6 +394,22 @@ impl GenomeMutator {
         false
     }
 
+    //each enabled connection has a weight_mutation_rate chance of getting new_weight(old_weight)
+    //returns false if no weights changed
+    fn mutate_every_weight<F>(neat: &mut Neat, genome: &mut Genome, new_weight: F) -> bool where
+        F: Fn(&mut Neat, f64) -> f64 {
+        let mut mutated = false;
+
+        for connection in genome.connections.values_mut() {
+            if connection.enabled && neat.cached_rng.gen_bool(neat.get_weight_mutation_rate().min(1.0)) {
+                connection.weight = new_weight(neat, connection.weight);
+                mutated = true;
+            }
+        }
+
+        mutated
+    }
+
     //change connection enabled/disabled
     pub(super) fn mutate_toggle_connection(neat: &mut Neat, genome: &mut Genome) -> bool {
         //gets random connection key
@@ -312,4 +423,156 @@ impl GenomeMutator {
 
         return false;
     }
-}
\ No newline at end of file
+
+    //give a random hidden/output node a different aggregation from aggregation_options
+    //the node is replaced in this genome only, the node bank keeps the shared one
+    pub(super) fn mutate_aggregation(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let node = match genome.nodes.values().filter(|node| node.get_role() != NodeRole::Input).choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(node) => Rc::clone(node),
+        };
+
+        let aggregation = match neat.aggregation_options.iter().filter(|aggregation| **aggregation != node.get_aggregation()).choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(aggregation) => *aggregation,
+        };
+
+        genome.replace_node(Rc::new(node.with_aggregation(aggregation)));
+        true
+    }
+
+    //shift one coefficient of a random enabled connection's hebbian rule by the weight shift distribution
+    //the learning rate doesn't go below 0
+    pub(super) fn mutate_hebbian(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let con_key = match genome.connections.values().filter(|connection| connection.enabled).choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(connection) => connection.get_innovation_number(),
+        };
+
+        let coefficient_index = neat.cached_rng.gen_range(0..5);
+        let shift = neat.weight_shift_distribution.sample(&mut neat.cached_rng);
+        if let Some(connection) = genome.connections.get_mut(&con_key) {
+            let coefficient = connection.hebbian.get_coefficient_mut(coefficient_index);
+            *coefficient += shift;
+            connection.hebbian.learning_rate = connection.hebbian.learning_rate.max(0.0);
+        }
+
+        true
+    }
+
+    //shift a random hidden/output node's time constant by the weight shift distribution, it stays at least MIN_TIME_CONSTANT
+    //the node is replaced in this genome only, like mutate_aggregation
+    pub(super) fn mutate_time_constant(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let node = match genome.nodes.values().filter(|node| node.get_role() != NodeRole::Input).choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(node) => Rc::clone(node),
+        };
+
+        let time_constant = (node.get_time_constant() + neat.weight_shift_distribution.sample(&mut neat.cached_rng)).max(MIN_TIME_CONSTANT);
+        genome.replace_node(Rc::new(node.with_time_constant(time_constant)));
+        true
+    }
+
+    //shift one spiking neuron parameter of a random hidden/output node by the weight shift distribution
+    //(times NeuronParameters::SHIFT_SCALES, the parameters are on very different scales), like mutate_time_constant
+    pub(super) fn mutate_neuron(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let node = match genome.nodes.values().filter(|node| node.get_role() != NodeRole::Input).choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(node) => Rc::clone(node),
+        };
+
+        let parameter_index = neat.cached_rng.gen_range(0..NeuronParameters::SHIFT_SCALES.len());
+        let shift = neat.weight_shift_distribution.sample(&mut neat.cached_rng) * NeuronParameters::SHIFT_SCALES[parameter_index];
+        let mut neuron = node.get_neuron();
+        *neuron.get_mut(parameter_index) += shift;
+
+        genome.replace_node(Rc::new(node.with_neuron(neuron.clamped())));
+        true
+    }
+
+    //delete a random connection, then any hidden node left with no connections
+    pub(super) fn mutate_remove_connection(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let con_key = match genome.connections.keys().choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(con_key) => *con_key,
+        };
+
+        genome.connections.remove(&con_key);
+        GenomeMutator::remove_orphaned_hidden_nodes(genome);
+
+        true
+    }
+
+    //delete a random hidden node and its connections
+    //every node that fed into it gets connected to every node it fed into (weight is the product of the two weights),
+    // so a node made by mutate_add_node gets its original connection back
+    pub(super) fn mutate_remove_node(neat: &mut Neat, genome: &mut Genome) -> bool {
+        let node_key = match genome.nodes.values()
+            .filter(|node| node.get_role() == NodeRole::Hidden)
+            .map(|node| node.get_innovation_number())
+            .choose(&mut neat.cached_rng) {
+            None => return false,
+            Some(node_key) => node_key,
+        };
+
+        let mut incoming: Vec<(usize, f64)> = Vec::new();
+        let mut outgoing: Vec<(usize, f64)> = Vec::new();
+
+        for con_key in genome.connections.keys().copied().collect::<Vec<usize>>() {
+            let connection = &genome.connections[&con_key];
+
+            if connection.to.get_innovation_number() == node_key {
+                if connection.enabled { incoming.push((connection.from.get_innovation_number(), connection.weight)); }
+            } else if connection.from.get_innovation_number() == node_key {
+                if connection.enabled { outgoing.push((connection.to.get_innovation_number(), connection.weight)); }
+            } else {
+                continue;
+            }
+
+            genome.connections.remove(&con_key);
+        }
+
+        //a modulatory node didn't feed values, so there's nothing to rewire
+        if genome.nodes.remove(&node_key).is_some_and(|node| node.is_modulatory()) {
+            outgoing.clear();
+        }
+
+        //rewire around the deleted node
+        for (from, in_weight) in &incoming {
+            for (to, out_weight) in &outgoing {
+                let con_num = neat.get_connection_number_from_nodes(*from, *to);
+
+                match genome.connections.get_mut(&con_num) {
+                    Some(connection) => { //already there, turn it back on if it was disabled
+                        if !connection.enabled {
+                            connection.enabled = true;
+                            connection.weight = neat.clamp_weight(in_weight * out_weight);
+                        }
+                    },
+                    None => {
+                        let mut connection = neat.new_connection(con_num, *from, *to);
+                        connection.weight = neat.clamp_weight(in_weight * out_weight);
+                        genome.add_connection(connection);
+                    },
+                }
+            }
+        }
+
+        GenomeMutator::remove_orphaned_hidden_nodes(genome);
+
+        true
+    }
+
+    fn remove_orphaned_hidden_nodes(genome: &mut Genome) {
+        let mut connected_nodes: HashSet<usize> = HashSet::new();
+        for connection in genome.connections.values() {
+            connected_nodes.insert(connection.from.get_innovation_number());
+            connected_nodes.insert(connection.to.get_innovation_number());
+        }
+
+        genome.nodes.retain(|key, node| {
+            let is_hidden_node = node.get_role() == NodeRole::Hidden;
+            !is_hidden_node || connected_nodes.contains(key)
+        });
+    }
+}
@@ -5,11 +5,16 @@ use super::genome_neat::{GenomeNeatMethods, GenomeMutator};
 use super::Neat;
 use crate::feed_forward::gene::Gene;
 use crate::feed_forward::connection_gene::ConnectionGene;
-use crate::activation_functions::ActivationFunctions;
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use super::weight_distribution::WeightDistribution;
+use crate::feed_forward::plasticity::HebbianRule;
+use crate::feed_forward::spiking::NeuronParameters;
+use super::mutation::{Mutation, MutationMode};
 
 fn get_neat_for_tests() -> Neat {
-    Neat::new(5, 2, ActivationFunctions::identity, 0.0, 0, 0,
-              0, 0, 0, 0.0,
+    Neat::new(5, 2, Activation::Identity, 0.0, 0.0, 0.0,
+              0.0, 0.0, 0.0, 0.0,
               0.1, 0.1, 0.1, 10.0, 50.0, 100)
 }
 fn get_basic_genome_for_test_using_neat(neat: &mut Neat) -> Genome {
@@ -90,6 +95,24 @@ fn test_breeding() {
     //TODO this doesn't really check breeding, though idk how i'd check that so meh...??
 }
 
+#[test]
+fn test_breeding_keeps_genomes_valid() {
+    let mut neat = get_neat_for_tests();
+
+    //both genomes use the same neat so their innovation numbers line up
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let mut genome1 = get_basic_genome_for_test_using_neat(&mut neat);
+    GenomeMutator::mutate_add_node(&mut neat, &mut genome0);
+    GenomeMutator::mutate_add_node(&mut neat, &mut genome1);
+
+    let breeded = GenomeNeatMethods::breed(&genome0, &genome1);
+    assert_eq!(breeded.validate(), vec![]);
+
+    for key in breeded.connections.keys() {
+        assert!(genome0.connections.contains_key(key) || genome1.connections.contains_key(key), "connections come from the parents");
+    }
+}
+
 #[test]
 fn test_mutate_add_node() {
     let mut neat = get_neat_for_tests();
@@ -294,4 +317,375 @@ fn test_mutate_toggle_connection() {
     }
 
     assert_eq!(node_difference, 0);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_mutate_remove_connection() {
+    let mut neat = get_neat_for_tests();
+
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    assert!(GenomeMutator::mutate_add_node(&mut neat, &mut genome0));
+
+    let original_con_count = genome0.connections.len();
+    let original_node_count = genome0.nodes.len();
+
+    //do mutate
+    assert!(GenomeMutator::mutate_remove_connection(&mut neat, &mut genome0));
+
+    assert_eq!(genome0.connections.len(), original_con_count - 1);
+    assert!(genome0.nodes.len() == original_node_count, "hidden node still has a connection, nothing else goes");
+    assert_eq!(genome0.validate(), vec![]);
+
+    //removing everything leaves just the input/output nodes
+    while GenomeMutator::mutate_remove_connection(&mut neat, &mut genome0) {}
+
+    assert_eq!(genome0.connections.len(), 0);
+    assert_eq!(genome0.nodes.len(), original_node_count - 1, "orphaned hidden node is removed");
+    assert!(genome0.nodes.values().all(|node| node.get_x() == 0.1 || node.get_x() == 0.9));
+}
+
+#[test]
+fn test_mutate_remove_node() {
+    let mut neat = get_neat_for_tests();
+
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let original_con_weights: HashMap<usize, f64> = genome0.connections.iter().map(|(key, con): (&usize, &ConnectionGene)| (*key, con.weight)).collect();
+    let original_node_count = genome0.nodes.len();
+
+    //no hidden nodes to remove
+    assert!(!GenomeMutator::mutate_remove_node(&mut neat, &mut genome0));
+
+    assert!(GenomeMutator::mutate_add_node(&mut neat, &mut genome0));
+    let split_key: usize = *genome0.connections.iter().find(|(_key, con)| !con.enabled).unwrap().0;
+    let new_con_keys: Vec<usize> = genome0.connections.keys().filter(|key| !original_con_weights.contains_key(key)).copied().collect();
+    let split_weight = genome0.connections[&new_con_keys[0]].weight * genome0.connections[&new_con_keys[1]].weight;
+
+    //do mutate
+    assert!(GenomeMutator::mutate_remove_node(&mut neat, &mut genome0));
+
+    assert_eq!(genome0.nodes.len(), original_node_count);
+    assert_eq!(genome0.connections.len(), original_con_weights.len(), "the node's connections are gone");
+    assert!(genome0.connections[&split_key].enabled, "split connection is turned back on");
+    assert_eq!(genome0.connections[&split_key].weight, split_weight);
+    assert_eq!(genome0.validate(), vec![]);
+}
+
+#[test]
+fn test_breeding_keeps_unconnected_input_output_nodes() {
+    let mut neat = get_neat_for_tests();
+
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let genome1 = get_basic_genome_for_test_using_neat(&mut neat);
+    while GenomeMutator::mutate_remove_connection(&mut neat, &mut genome0) {}
+
+    let breeded = GenomeNeatMethods::breed(&genome0, &genome1);
+
+    assert_eq!(breeded.nodes.len(), genome0.nodes.len());
+}
+
+#[test]
+fn test_weight_mutation_rate_mutates_every_connection() {
+    let mut neat = get_neat_for_tests();
+    neat.weight_mutation_rate = 1.0;
+    neat.weight_shift_distribution = WeightDistribution::Gaussian {sigma: 1.0};
+
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let original_con_weights: HashMap<usize, f64> = genome0.connections.iter().map(|(key, con): (&usize, &ConnectionGene)| (*key, con.weight)).collect();
+
+    //do mutate
+    assert!(GenomeMutator::mutate_weight_shift(&mut neat, &mut genome0));
+
+    for (key, con) in genome0.connections.iter() {
+        assert_ne!(con.weight, original_con_weights[key], "every enabled connection is shifted");
+    }
+
+    //nothing enabled, nothing to mutate
+    for con in genome0.connections.values_mut() {
+        con.enabled = false;
+    }
+    assert!(!GenomeMutator::mutate_random_weight(&mut neat, &mut genome0));
+}
+
+#[test]
+fn test_weight_bounds_clamp_mutations() {
+    let mut neat = get_neat_for_tests();
+    neat.weight_mutation_rate = 1.0;
+    neat.weight_bounds = (-1.0, 0.5);
+
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+
+    for _ in 0..20 {
+        GenomeMutator::mutate_weight_shift(&mut neat, &mut genome0);
+        GenomeMutator::mutate_random_weight(&mut neat, &mut genome0);
+        GenomeMutator::mutate_add_connection(&mut neat, &mut genome0);
+
+        for con in genome0.connections.values() {
+            assert!(con.weight >= -1.0 && con.weight <= 0.5, "weight {} is out of bounds", con.weight);
+        }
+    }
+}
+
+#[test]
+fn test_weight_bounds_clamp_new_connections() {
+    let mut neat = get_neat_for_tests();
+    neat.weight_bounds = (-1.0, 0.5);
+    neat.initial_mutate_full = false; //no weight mutations anywhere
+
+    let in_bounds = |genome: &Genome| genome.connections.values().all(|con| con.weight >= -1.0 && con.weight <= 0.5);
+
+    //the default weight (1.0) is out of bounds
+    let mut genome0 = neat.get_default_genome();
+    assert!(!genome0.connections.is_empty());
+    assert!(in_bounds(&genome0), "new client's connections");
+
+    assert!(GenomeMutator::mutate_add_node(&mut neat, &mut genome0));
+    assert!(in_bounds(&genome0), "add node's connections");
+
+    //-1 * -1 is out of bounds once the node is removed
+    let hidden_node = *genome0.nodes.keys().find(|key| genome0.nodes[*key].get_x() == 0.5).unwrap();
+    for con in genome0.connections.values_mut() {
+        if con.from.get_innovation_number() == hidden_node || con.to.get_innovation_number() == hidden_node {
+            con.weight = -1.0;
+        }
+    }
+    assert!(GenomeMutator::mutate_remove_node(&mut neat, &mut genome0));
+    assert!(in_bounds(&genome0), "remove node's connections");
+}
+
+#[test]
+fn test_mutate_aggregation() {
+    let mut neat = get_neat_for_tests();
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let genome1 = get_basic_genome_for_test_using_neat(&mut neat);
+
+    //every node is already sum
+    neat.aggregation_options = vec![Aggregation::Sum];
+    assert!(!GenomeMutator::mutate_aggregation(&mut neat, &mut genome0));
+
+    neat.aggregation_options = vec![Aggregation::Product];
+    assert!(GenomeMutator::mutate_aggregation(&mut neat, &mut genome0));
+
+    let changed: Vec<usize> = genome0.nodes.values().filter(|node| node.get_aggregation() == Aggregation::Product).map(|node| node.get_innovation_number()).collect();
+    assert_eq!(changed.len(), 1);
+    assert!(genome0.nodes[&changed[0]].get_x() > 0.1, "inputs don't aggregate anything");
+    assert_eq!(neat.node_bank[&changed[0]].get_aggregation(), Aggregation::Sum, "only this genome's node changes");
+    assert_eq!(genome0.validate(), vec![]);
+
+    //new connections use the genome's node, not the node bank's
+    for _ in 0..10 {
+        GenomeMutator::mutate_add_node(&mut neat, &mut genome0);
+        GenomeMutator::mutate_add_connection(&mut neat, &mut genome0);
+        assert_eq!(genome0.validate(), vec![]);
+    }
+    assert_eq!(genome0.nodes[&changed[0]].get_aggregation(), Aggregation::Product);
+
+    //the node comes from either parent, connections have to agree with it
+    for _ in 0..10 {
+        let breeded = GenomeNeatMethods::breed(&genome0, &genome1);
+        assert_eq!(breeded.validate(), vec![]);
+    }
+}
+
+#[test]
+fn test_mutate_hebbian() {
+    let mut neat = get_neat_for_tests();
+    neat.weight_shift_distribution = WeightDistribution::Uniform {max: 1.0};
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let weights: HashMap<usize, f64> = genome0.connections.iter().map(|(key, con)| (*key, con.weight)).collect();
+
+    for _ in 0..50 {
+        assert!(GenomeMutator::mutate_hebbian(&mut neat, &mut genome0));
+    }
+
+    assert!(genome0.connections.values().any(|con| con.hebbian != HebbianRule::default()));
+    for con in genome0.connections.values() {
+        assert!(con.hebbian.learning_rate >= 0.0);
+        assert_eq!(con.weight, weights[&con.get_innovation_number()], "only the rules change");
+        if !con.enabled {
+            assert_eq!(con.hebbian, HebbianRule::default(), "disabled connections aren't mutated");
+        }
+    }
+
+    //the rules are inherited like the rest of the connection
+    let genome1 = get_basic_genome_for_test_using_neat(&mut neat);
+    let breeded = GenomeNeatMethods::breed(&genome0, &genome1);
+    for con in breeded.connections.values() {
+        let parent_rules = [genome0.connections.get(&con.get_innovation_number()), genome1.connections.get(&con.get_innovation_number())];
+        assert!(parent_rules.iter().flatten().any(|parent| parent.hebbian == con.hebbian));
+    }
+}
+
+#[test]
+fn test_mutate_add_modulatory_node() {
+    let mut neat = get_neat_for_tests();
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let enabled_before: Vec<usize> = genome0.connections.values().filter(|con| con.enabled).map(|con| con.get_innovation_number()).collect();
+    let nodes_before = genome0.nodes.len();
+
+    assert!(GenomeMutator::mutate_add_modulatory_node(&mut neat, &mut genome0));
+    assert_eq!(genome0.validate(), vec![]);
+    assert_eq!(genome0.nodes.len(), nodes_before + 1);
+    for con_key in &enabled_before {
+        assert!(genome0.connections[con_key].enabled, "the connection next to the new node stays");
+    }
+
+    let modulatory: Vec<usize> = genome0.nodes.values().filter(|node| node.is_modulatory()).map(|node| node.get_innovation_number()).collect();
+    assert_eq!(modulatory.len(), 1);
+    assert!(!neat.node_bank[&modulatory[0]].is_modulatory(), "only this genome's node is modulatory");
+    let outgoing: Vec<&ConnectionGene> = genome0.connections.values().filter(|con| con.from.get_innovation_number() == modulatory[0]).collect();
+    assert_eq!(outgoing.len(), 1);
+    assert!(outgoing[0].from.is_modulatory(), "connections use the genome's node");
+
+    //removing it (it's the only hidden node) doesn't rewire anything, it didn't feed values
+    let connections_before = genome0.connections.len();
+    assert!(GenomeMutator::mutate_remove_node(&mut neat, &mut genome0));
+    assert_eq!(genome0.connections.len(), connections_before - 2);
+    assert_eq!(genome0.validate(), vec![]);
+}
+
+#[test]
+fn test_modulatory_nodes_have_their_own_innovation_numbers() {
+    let mut neat = get_neat_for_tests();
+
+    //both genomes only have the same one connection
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+    let kept: usize = *genome0.connections.keys().next().unwrap();
+    genome0.connections.retain(|key, _con| *key == kept);
+    let mut genome1 = genome0.clone();
+
+    assert!(GenomeMutator::mutate_add_node(&mut neat, &mut genome0));
+    assert!(GenomeMutator::mutate_add_modulatory_node(&mut neat, &mut genome1));
+
+    let new_nodes = |genome: &Genome| -> Vec<usize> { genome.nodes.values().filter(|node| node.get_x() == 0.5).map(|node| node.get_innovation_number()).collect() };
+    let new_connections = |genome: &Genome| -> Vec<usize> { genome.connections.keys().copied().filter(|key| *key != kept).collect() };
+    assert_eq!((new_nodes(&genome0).len(), new_nodes(&genome1).len()), (1, 1));
+    assert_ne!(new_nodes(&genome0), new_nodes(&genome1), "add node's node isn't the modulatory node");
+    assert!(new_connections(&genome1).iter().all(|key| !new_connections(&genome0).contains(key)));
+
+    //a genome can have both, the connection gets the same modulatory node as in genome1
+    let only_kept_enabled = |genome: &mut Genome| for con in genome.connections.values_mut() {
+        con.enabled = con.get_innovation_number() == kept;
+    };
+    let mut genome2 = genome0.clone();
+    only_kept_enabled(&mut genome2);
+    assert!(GenomeMutator::mutate_add_modulatory_node(&mut neat, &mut genome2));
+    let modulatory: Vec<usize> = genome2.nodes.values().filter(|node| node.is_modulatory()).map(|node| node.get_innovation_number()).collect();
+    assert_eq!(modulatory, new_nodes(&genome1));
+    assert_eq!(genome2.nodes.len(), genome0.nodes.len() + 1);
+    assert_eq!(genome2.validate(), vec![]);
+
+    only_kept_enabled(&mut genome2);
+    assert!(!GenomeMutator::mutate_add_modulatory_node(&mut neat, &mut genome2), "the connection has one already");
+}
+
+#[test]
+fn test_mutate_time_constant() {
+    let mut neat = get_neat_for_tests();
+    neat.weight_shift_distribution = WeightDistribution::Uniform {max: 5.0};
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+
+    for _ in 0..30 {
+        assert!(GenomeMutator::mutate_time_constant(&mut neat, &mut genome0));
+        assert_eq!(genome0.validate(), vec![], "time constants stay above the minimum");
+    }
+
+    let changed: Vec<usize> = genome0.nodes.values().filter(|node| node.get_time_constant() != 1.0).map(|node| node.get_innovation_number()).collect();
+    assert!(!changed.is_empty());
+    for node_key in &changed {
+        assert!(genome0.nodes[node_key].get_x() > 0.1, "inputs don't have a time constant");
+        assert_eq!(neat.node_bank[node_key].get_time_constant(), 1.0, "only this genome's node changes");
+    }
+
+    //inherited like aggregations
+    let genome1 = get_basic_genome_for_test_using_neat(&mut neat);
+    let breeded = GenomeNeatMethods::breed(&genome0, &genome1);
+    assert_eq!(breeded.validate(), vec![]);
+    for node in breeded.nodes.values() {
+        let parent_nodes = [genome0.nodes.get(&node.get_innovation_number()), genome1.nodes.get(&node.get_innovation_number())];
+        assert!(parent_nodes.iter().flatten().any(|parent| parent.get_time_constant() == node.get_time_constant()));
+    }
+}
+
+#[test]
+fn test_mutate_neuron() {
+    let mut neat = get_neat_for_tests();
+    neat.weight_shift_distribution = WeightDistribution::Uniform {max: 20.0};
+    let mut genome0 = get_basic_genome_for_test_using_neat(&mut neat);
+
+    for _ in 0..50 {
+        assert!(GenomeMutator::mutate_neuron(&mut neat, &mut genome0));
+        assert_eq!(genome0.validate(), vec![], "parameters stay runnable");
+    }
+
+    let changed: Vec<usize> = genome0.nodes.values().filter(|node| node.get_neuron() != NeuronParameters::default())
+        .map(|node| node.get_innovation_number()).collect();
+    assert!(!changed.is_empty());
+    for node_key in &changed {
+        assert!(genome0.nodes[node_key].get_x() > 0.1, "inputs don't have neurons");
+        assert_eq!(neat.node_bank[node_key].get_neuron(), NeuronParameters::default(), "only this genome's node changes");
+    }
+}
+
+//only the given mutations can happen, with probability 1
+fn get_neat_with_mutations(mutations: &[Mutation], mode: MutationMode, rounds: u64) -> Neat {
+    let mut neat = get_neat_for_tests();
+    for mutation in Mutation::ALL {
+        let probability = match mutation {
+            Mutation::AddNode => &mut neat.mutate_probability_add_node,
+            Mutation::AddConnection => &mut neat.mutate_probability_add_connection,
+            Mutation::AddModulatoryNode => &mut neat.mutate_probability_add_modulatory_node,
+            Mutation::RandomWeight => &mut neat.mutate_probability_random_weight,
+            Mutation::WeightShift => &mut neat.mutate_probability_weight_shift,
+            Mutation::ToggleConnection => &mut neat.mutate_probability_toggle_connection,
+            Mutation::Aggregation => &mut neat.mutate_probability_aggregation,
+            Mutation::Hebbian => &mut neat.mutate_probability_hebbian,
+            Mutation::TimeConstant => &mut neat.mutate_probability_time_constant,
+            Mutation::Neuron => &mut neat.mutate_probability_neuron,
+            Mutation::RemoveConnection => &mut neat.mutate_probability_remove_connection,
+            Mutation::RemoveNode => &mut neat.mutate_probability_remove_node,
+        };
+        *probability = if mutations.contains(&mutation) { 1.0 } else { 0.0 };
+    }
+    neat.mutation_mode = mode;
+    neat.mutation_rounds = rounds;
+    neat
+}
+
+#[test]
+fn test_mutation_modes() {
+    //(a weight changed, a connection was toggled)
+    let mutate = |neat: &mut Neat| {
+        let genome = get_basic_genome_for_test_using_neat(neat);
+        let mut mutated = genome.clone();
+        GenomeMutator::mutate_random(neat, &mut mutated);
+        let weight_changed = genome.connections.iter().any(|(key, connection)| connection.weight != mutated.connections[key].weight);
+        let toggled = genome.connections.iter().any(|(key, connection)| connection.enabled != mutated.connections[key].enabled);
+        (weight_changed, toggled)
+    };
+
+    let mut neat = get_neat_with_mutations(&[Mutation::RandomWeight, Mutation::ToggleConnection], MutationMode::Independent, 1);
+    assert_eq!(mutate(&mut neat), (true, true));
+
+    let mut neat = get_neat_with_mutations(&[Mutation::RandomWeight, Mutation::ToggleConnection], MutationMode::ExactlyOne, 1);
+    let outcomes: Vec<(bool, bool)> = (0..50).map(|_| mutate(&mut neat)).collect();
+    assert!(outcomes.iter().all(|(weight_changed, toggled)| weight_changed != toggled), "one mutation at a time");
+    assert!(outcomes.contains(&(true, false)) && outcomes.contains(&(false, true)));
+
+    let mut neat = get_neat_with_mutations(&[], MutationMode::ExactlyOne, 1);
+    assert_eq!(mutate(&mut neat), (false, false), "nothing to pick from");
+}
+
+#[test]
+fn test_mutation_rounds() {
+    let mut neat = get_neat_with_mutations(&[Mutation::ToggleConnection], MutationMode::Independent, 3);
+    let mut genome = get_basic_genome_for_test_using_neat(&mut neat);
+    let first_key = *genome.connections.keys().min().unwrap();
+    genome.connections.retain(|key, _connection| *key == first_key);
+
+    GenomeMutator::mutate_random(&mut neat, &mut genome);
+    assert!(!genome.connections[&first_key].enabled, "toggled 3 times");
+
+    neat.mutation_rounds = 2;
+    GenomeMutator::mutate_random(&mut neat, &mut genome);
+    assert!(!genome.connections[&first_key].enabled, "toggled back and forth");
+}
@@ -0,0 +1,91 @@
+use super::Neat;
+use super::population::{ClientId, SpeciesId};
+use crate::feed_forward::genome::Genome;
+use crate::feed_forward::network::Network;
+use std::cell::RefCell;
+use std::cmp::Ordering::Equal;
+use std::rc::Rc;
+
+/* - Hall of fame -
+The best genomes seen so far, kept across generations (and checkpoints) so they aren't lost to mutation
+    hall_of_fame_size - how many are kept, 0 (off) unless set through a NeatConfig
+update_clients records the scored clients before it changes them, a genome that's already in the hall only
+keeps its best fitness
+    get_hall_of_fame() - best first
+    get_hall_of_fame_networks() - eg. as opponents when scoring
+    reseed_from_hall_of_fame(count) - puts copies of the best genomes back into the population
+ */
+
+#[derive(Clone)]
+pub struct HallOfFameEntry {
+    pub genome: Genome,
+    pub fitness: f64,
+    pub generation: u64, //generation it was scored in
+    pub species: Option<SpeciesId>, //species it was in then, species names don't survive checkpoints
+}
+
+impl Neat {
+    pub fn get_hall_of_fame(&self) -> &[HallOfFameEntry] {
+        &self.hall_of_fame
+    }
+
+    //same bias/activation/decoder as get_client_network
+    pub fn get_hall_of_fame_networks(&self) -> Vec<Network> {
+        self.hall_of_fame.iter().map(|entry| match self.make_network(&entry.genome) {
+            Err(message) => panic!("can't make a network from a hall of fame genome: {}", message),
+            Ok(network) => network,
+        }).collect()
+    }
+
+    //replaces the genomes of the lowest scoring clients with copies of the best hall of fame genomes,
+    //they get the entry's fitness as their score, so use it after scoring and before update_clients
+    //returns the replaced clients
+    pub fn reseed_from_hall_of_fame(&mut self, count: usize) -> Vec<ClientId> {
+        let mut worst: Vec<(ClientId, f64)> = self.clients().map(|client| (client.id(), client.score())).collect();
+        worst.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Equal));
+
+        let mut replaced = Vec::new();
+        for ((client_id, _score), entry) in worst.into_iter().zip(self.hall_of_fame.iter()).take(count) {
+            let mut client = self.clients[&*client_id].borrow_mut();
+            client.set_genome(Rc::new(RefCell::new(entry.genome.clone())));
+            client.set_score(entry.fitness);
+            client.generate_calculator(self.activation_function);
+            replaced.push(client_id);
+        }
+
+        replaced
+    }
+
+    pub(super) fn record_hall_of_fame(&mut self) {
+        if self.hall_of_fame_size == 0 {
+            return;
+        }
+
+        let mut scored: Vec<HallOfFameEntry> = self.clients().map(|client| HallOfFameEntry {
+            genome: client.genome(),
+            fitness: client.score(),
+            generation: self.generation,
+            species: client.species_id(),
+        }).collect();
+        scored.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap_or(Equal));
+        scored.truncate(self.hall_of_fame_size);
+
+        for entry in scored {
+            self.add_to_hall_of_fame(entry);
+        }
+    }
+
+    pub(super) fn add_to_hall_of_fame(&mut self, entry: HallOfFameEntry) {
+        match self.hall_of_fame.iter_mut().find(|existing| existing.genome == entry.genome) {
+            Some(existing) => {
+                if entry.fitness > existing.fitness {
+                    *existing = entry;
+                }
+            },
+            None => self.hall_of_fame.push(entry),
+        }
+
+        self.hall_of_fame.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap_or(Equal));
+        self.hall_of_fame.truncate(self.hall_of_fame_size);
+    }
+}
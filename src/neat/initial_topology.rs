@@ -0,0 +1,116 @@
+use crate::feed_forward::gene::Gene;
+use crate::feed_forward::node_gene::{NodeGene, NodeRole, INPUT_X, OUTPUT_X};
+use super::Neat;
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/* - Initial topology -
+What the genome of a new client (without a seed genome) is connected like, the bias counts as an input
+    full - every input to every output
+    full_hidden(n) - every input to each of n hidden nodes and each of those to every output, no input to output
+    sparse(fraction) - that fraction (rounded) of the input to output connections, picked at random for every client
+    unconnected - no connections, the mutations have to add them
+the hidden nodes are shared by every client, the i-th (from 1) is the hidden node in the node bank at (0.5, i / (n + 1))
+that isn't some connection's replacement (or modulatory) node, so a loaded checkpoint finds them again
+then mutate_full is run on the genome unless initial_mutate_full is off (it applies to seeded clients too)
+written like `full_hidden(3)` or `sparse(0.5)`
+ */
+
+const HIDDEN_X: f64 = (INPUT_X + OUTPUT_X) / 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum InitialTopology {
+    #[default]
+    FullyConnected,
+    FullyConnectedHidden(usize),
+    Sparse(f64),
+    Unconnected,
+}
+
+impl fmt::Display for InitialTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitialTopology::FullyConnected => write!(f, "full"),
+            InitialTopology::FullyConnectedHidden(hidden_nodes) => write!(f, "full_hidden({})", hidden_nodes),
+            InitialTopology::Sparse(fraction) => write!(f, "sparse({})", fraction),
+            InitialTopology::Unconnected => write!(f, "unconnected"),
+        }
+    }
+}
+
+impl FromStr for InitialTopology {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bad_format = || format!("expected full, full_hidden(n), sparse(fraction) or unconnected, got '{}'", text.trim());
+
+        match text.trim() {
+            "full" => return Ok(InitialTopology::FullyConnected),
+            "unconnected" => return Ok(InitialTopology::Unconnected),
+            _ => {},
+        }
+
+        let (name, parameter) = text.trim().strip_suffix(')')
+            .and_then(|text| text.split_once('('))
+            .ok_or_else(bad_format)?;
+
+        match name.trim() {
+            "full_hidden" => {
+                let hidden_nodes: usize = parameter.trim().parse().map_err(|_| bad_format())?;
+                if hidden_nodes == 0 {
+                    return Err(String::from("full_hidden needs at least 1 hidden node, use full for none"));
+                }
+                Ok(InitialTopology::FullyConnectedHidden(hidden_nodes))
+            },
+            "sparse" => {
+                let fraction: f64 = parameter.trim().parse().map_err(|_| bad_format())?;
+                if !(0.0..=1.0).contains(&fraction) {
+                    return Err(format!("sparse's fraction has to be between 0 and 1, got {}", fraction));
+                }
+                Ok(InitialTopology::Sparse(fraction))
+            },
+            _ => Err(bad_format()),
+        }
+    }
+}
+
+impl Neat {
+    //the index-th of hidden_nodes initial hidden nodes, from the node bank if it's there already
+    pub(super) fn get_initial_hidden_node(&mut self, index: usize, hidden_nodes: usize) -> Rc<NodeGene> {
+        let y = (index + 1) as f64 / (hidden_nodes + 1) as f64;
+        let replacement_nodes: HashSet<usize> = self.connection_to_replacement_node_map.values()
+            .chain(self.connection_to_modulatory_node_map.values())
+            .copied().collect();
+
+        let existing = self.node_bank.values()
+            .filter(|node| node.get_role() == NodeRole::Hidden && node.get_x() == HIDDEN_X && node.get_y() == y)
+            .filter(|node| !replacement_nodes.contains(&node.get_innovation_number()))
+            .min_by_key(|node| node.get_innovation_number());
+
+        match existing {
+            Some(node) => Rc::clone(node),
+            None => self.get_new_node_from_xy(HIDDEN_X, y, NodeRole::Hidden),
+        }
+    }
+
+    pub fn get_initial_topology(&self) -> InitialTopology {
+        self.initial_topology
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_what_it_writes() {
+        for topology in [InitialTopology::FullyConnected, InitialTopology::FullyConnectedHidden(3), InitialTopology::Sparse(0.25), InitialTopology::Unconnected] {
+            assert_eq!(topology.to_string().parse::<InitialTopology>(), Ok(topology));
+        }
+        assert!("full_hidden(0)".parse::<InitialTopology>().is_err());
+        assert!("sparse(1.5)".parse::<InitialTopology>().is_err());
+        assert!("sparse".parse::<InitialTopology>().is_err());
+    }
+}
@@ -2,7 +2,7 @@ use crate::neat::species::Species;
 use std::cell::{RefCell};
 use crate::neat::client::Client;
 use std::rc::Rc;
-use crate::feed_forward::node_gene::NodeGene;
+use crate::feed_forward::node_gene::{NodeGene, NodeRole, INPUT_X, OUTPUT_X};
 use crate::feed_forward::connection_gene::ConnectionGene;
 use std::collections::HashMap;
 use crate::feed_forward::gene::Gene;
@@ -12,6 +12,23 @@ use rand::rngs::ThreadRng;
 use std::cmp::Ordering::Equal;
 use crate::random_hash_set::RandomHashSet;
 use rand::prelude::IteratorRandom;
+use crate::feed_forward::calculator::Calculator;
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::backprop::{Backprop, FineTuneSettings};
+use crate::feed_forward::network::Network;
+use crate::feed_forward::ctrnn::Ctrnn;
+use crate::feed_forward::spiking::{SpikingNetwork, NeuronModel, InputEncoding};
+use crate::neat::phased_search::{PhasedSearch, SearchPhase};
+use crate::neat::weight_distribution::WeightDistribution;
+use crate::neat::population::ClientId;
+use crate::neat::hall_of_fame::HallOfFameEntry;
+use crate::neat::adaptive_mutation::{MutationSchedule, MutationRateReport};
+use crate::neat::mutation::MutationMode;
+use crate::neat::initial_topology::InitialTopology;
+use crate::output_decoder::{OutputDecoder, DecodedOutput};
+use std::io;
+use std::path::Path;
 
 #[cfg(test)]
 mod tests;
@@ -21,6 +38,16 @@ mod genome_neat_tests;
 mod client;
 mod species;
 mod genome_neat;
+mod checkpoint;
+mod seed;
+pub mod config;
+pub mod phased_search;
+pub mod weight_distribution;
+pub mod population;
+pub mod hall_of_fame;
+pub mod adaptive_mutation;
+pub mod mutation;
+pub mod initial_topology;
 
 /* - Neat -
 everything
@@ -31,43 +58,73 @@ pub struct Neat {
     clients: HashMap<String, Rc<RefCell<Client>>>,
     default_species: Rc<RefCell<Species>>, //when a client doesn't have a species
 
-    activation_function: fn(f64) -> f64,
+    activation_function: Activation,
     species_distance_threshold: f64,
     proportion_to_kill: f64, //proportion to kill each run
 
-    //mutate chances - 1 in x chance of mutating
-    mutate_chance_add_node: u64,
-    mutate_chance_add_connection: u64,
-    mutate_chance_random_weight: u64,
-    mutate_chance_weight_shift: u64,
-    mutate_chance_toggle_connection: u64,
+    //mutation probabilities - 0 (never) to 1 (every time) (see neat::mutation)
+    mutate_probability_add_node: f64,
+    mutate_probability_add_connection: f64,
+    mutate_probability_random_weight: f64,
+    mutate_probability_weight_shift: f64,
+    mutate_probability_toggle_connection: f64,
+    mutate_probability_remove_connection: f64, //0 (never) unless set through a NeatConfig
+    mutate_probability_remove_node: f64, //0 (never) unless set through a NeatConfig
+    mutate_probability_add_modulatory_node: f64, //0 (never) unless set through a NeatConfig
+    mutate_probability_aggregation: f64, //0 (never) unless set through a NeatConfig
+    mutate_probability_hebbian: f64, //0 (never, clients aren't plastic) unless set through a NeatConfig
+    mutate_probability_time_constant: f64, //0 (never) unless set through a NeatConfig
+    mutate_probability_neuron: f64, //0 (never) unless set through a NeatConfig
+    aggregation_options: Vec<Aggregation>, //what mutate_aggregation can change a node's aggregation to
+    //how mutate_random uses the probabilities (see neat::mutation)
+    mutation_mode: MutationMode, //independent unless set through a NeatConfig
+    mutation_rounds: u64, //1 unless set through a NeatConfig
+    //adaptive mutation (see neat::adaptive_mutation)
+    mutation_rate_adaptation: f64, //0 (genomes don't carry their own probabilities) unless set through a NeatConfig
+    mutation_schedule: MutationSchedule, //fixed unless set through a NeatConfig
+    mutation_multiplier: f64, //from the schedule, for this generation
+    best_fitness: f64, //best score so far, for the stagnation schedule
+    generations_since_fitness_improved: u64,
+    last_mutation_rates: Option<MutationRateReport>,
     //distance constants
     C1: f64,
     C2: f64,
     C3: f64,
-    random_weight_max: f64,
-    random_weight_shift_max: f64,
+    random_weight_distribution: WeightDistribution, //new weights, uniform(random_weight_max) from Neat::new
+    weight_shift_distribution: WeightDistribution, //shifts, uniform(random_weight_shift_max) from Neat::new
+    weight_mutation_rate: f64, //0 mutates one connection per mutation, otherwise the chance each enabled connection is mutated
+    weight_bounds: (f64, f64), //(min, max), every weight the Neat makes or changes is clamped to this
     max_mutation_attempts: u64,
 
     node_bank: HashMap<usize, Rc<NodeGene>>, //for making sure nodes with same inv_num always refers to same node
     nodes_to_connection_map: HashMap<(usize, usize), usize>, //(node0_inv_num, node1_inv_num) -> connection_inv_num
     connection_to_replacement_node_map: HashMap<usize, usize>, //conection_inv_num -> node_inv_number
+    connection_to_modulatory_node_map: HashMap<usize, usize>, //conection_inv_num -> node_inv_number, see mutate_add_modulatory_node
 
     pub(crate) num_of_input_nodes: usize,
     pub(crate) num_of_output_nodes: usize,
 
+    generation: u64, //number of times update_clients has run
+    phased_search: Option<PhasedSearch>, //None mutates without phases, set through a NeatConfig
+    hall_of_fame_size: usize, //0 (off) unless set through a NeatConfig
+    hall_of_fame: Vec<HallOfFameEntry>, //best first
+    seed_genome: Option<Genome>, //new clients are mutated copies of this when it's set, already renumbered for this Neat
+    initial_topology: InitialTopology, //fully connected unless set through a NeatConfig
+    initial_mutate_full: bool, //whether new clients get mutate_full, true unless set through a NeatConfig
+    output_decoder: OutputDecoder, //for use_client_decoded and the networks this hands out, raw unless set
+
     cached_rng: ThreadRng,
 }
 
 impl Neat {
     pub fn new(input_size: usize, output_size: usize,
-               activation_function: fn(f64) -> f64,
+               activation_function: Activation,
                species_distance_threshold: f64,
-               mutate_chance_add_node: u64,
-               mutate_chance_add_connection: u64,
-               mutate_chance_random_weight: u64,
-               mutate_chance_weight_shift: u64,
-               mutate_chance_toggle_connection: u64,
+               mutate_probability_add_node: f64,
+               mutate_probability_add_connection: f64,
+               mutate_probability_random_weight: f64,
+               mutate_probability_weight_shift: f64,
+               mutate_probability_toggle_connection: f64,
                proportion_to_kill: f64, C1: f64,
                C2: f64, C3: f64,
                random_weight_max: f64,
@@ -83,60 +140,77 @@ impl Neat {
             activation_function,
             species_distance_threshold,
             proportion_to_kill,
-            mutate_chance_add_node,
-            mutate_chance_add_connection,
-            mutate_chance_random_weight,
-            mutate_chance_weight_shift,
-            mutate_chance_toggle_connection,
+            mutate_probability_add_node,
+            mutate_probability_add_connection,
+            mutate_probability_random_weight,
+            mutate_probability_weight_shift,
+            mutate_probability_toggle_connection,
+            mutate_probability_remove_connection: 0.0,
+            mutate_probability_remove_node: 0.0,
+            mutate_probability_add_modulatory_node: 0.0,
+            mutate_probability_aggregation: 0.0,
+            mutate_probability_hebbian: 0.0,
+            mutate_probability_time_constant: 0.0,
+            mutate_probability_neuron: 0.0,
+            aggregation_options: Aggregation::ALL.to_vec(),
+            mutation_mode: MutationMode::Independent,
+            mutation_rounds: 1,
+            mutation_rate_adaptation: 0.0,
+            mutation_schedule: MutationSchedule::Fixed,
+            mutation_multiplier: 1.0,
+            best_fitness: f64::NEG_INFINITY,
+            generations_since_fitness_improved: 0,
+            last_mutation_rates: None,
 
             C1,
             C2,
             C3,
-            random_weight_max,
-            random_weight_shift_max,
+            random_weight_distribution: WeightDistribution::Uniform {max: random_weight_max},
+            weight_shift_distribution: WeightDistribution::Uniform {max: random_weight_shift_max},
+            weight_mutation_rate: 0.0,
+            weight_bounds: (f64::NEG_INFINITY, f64::INFINITY),
             max_mutation_attempts,
 
             node_bank: Default::default(),
             nodes_to_connection_map: Default::default(),
             connection_to_replacement_node_map: Default::default(),
+            connection_to_modulatory_node_map: Default::default(),
 
             num_of_input_nodes: input_size,
             num_of_output_nodes: output_size,
+            generation: 0,
+            phased_search: None,
+            hall_of_fame_size: 0,
+            hall_of_fame: Vec::new(),
+            seed_genome: None,
+            initial_topology: InitialTopology::FullyConnected,
+            initial_mutate_full: true,
+            output_decoder: OutputDecoder::Raw,
             cached_rng: rand::thread_rng(),
         };
 
         //we add a node (node0) as the bias
         for i in 0..input_size {
-            neat.get_new_node_from_xy(0.1, (i / input_size) as f64);
+            neat.get_new_node_from_xy(INPUT_X, (i / input_size) as f64, NodeRole::Input);
         }
         for i in 0..output_size {
-            neat.get_new_node_from_xy(0.9, (i / output_size) as f64);
+            neat.get_new_node_from_xy(OUTPUT_X, (i / output_size) as f64, NodeRole::Output);
         }
 
         neat
     }
 
     //creates new client, adds to default species
-    //returns client's id/name
+    //returns client's id, it derefs to the name
     //client is ready-to-run on creation (has calculator)
-    pub fn new_client(&mut self) -> String {
-        //get a basic client
-        let mut client = Client::new(Rc::new(RefCell::new(self.get_default_genome())),
-                                 self.get_default_species(),
-                                 );
-        //update calculator
-        client.generate_calculator(self.activation_function);
-
-        let name = String::clone(client.get_name());
-
-        let client_ref = Rc::new(RefCell::new(client));
-
-        self.get_default_species().borrow_mut().force_put(Rc::clone(&client_ref), self.get_default_species());
-
-        self.clients.insert(String::clone(&name), client_ref);
-
-
-        name
+    //starts from the seed genome when there is one (see neat::seed)
+    //a self-adaptive Neat gives it the Neat's mutation probabilities, unless the seed genome has its own
+    pub fn new_client(&mut self) -> ClientId {
+        let mut genome = self.get_starting_genome();
+        if self.mutation_rate_adaptation > 0.0 && genome.mutation_rates.is_none() {
+            genome.mutation_rates = Some(self.get_base_mutation_rates());
+        }
+        self.add_new_client(genome)
     }
 
     //run the client's calculator
@@ -152,6 +226,116 @@ impl Neat {
         }
     }
 
+    //use_client with the outputs put through the output decoder
+    pub fn use_client_decoded(&self, client_name: &String, inputs: &Vec<f64>) -> DecodedOutput {
+        self.output_decoder.decode(&self.use_client(client_name, inputs))
+    }
+
+    pub fn get_output_decoder(&self) -> &OutputDecoder {
+        &self.output_decoder
+    }
+
+    pub fn set_output_decoder(&mut self, decoder: OutputDecoder) -> Result<(), String> {
+        decoder.check(self.num_of_output_nodes)?;
+        self.output_decoder = decoder;
+        Ok(())
+    }
+
+    //puts the client's plastic connections back to their genetic weights (see feed_forward::plasticity)
+    pub fn reset_client_state(&self, client_name: &String) {
+        match self.clients.get(client_name) {
+            None => panic!("Whoa, client with name {} doesn't exist", client_name),
+            Some(client_ref) => client_ref.borrow().reset_calculator_state(),
+        }
+    }
+
+    //run a genome that isn't a client (eg. a saved champion), same bias/activation as use_client
+    pub fn use_genome(&self, genome: &Genome, inputs: &Vec<f64>) -> Vec<f64> {
+        assert_eq!(inputs.len(), self.num_of_input_nodes-1);
+        let mut inputs_with_bias: Vec<f64> = vec![1.0];
+        inputs_with_bias.extend(inputs);
+        Calculator::new(genome, self.activation_function.function()).run(&inputs_with_bias)
+    }
+
+    //gradient descent on a copy of the client's weights, dataset inputs don't include the bias (same as use_client)
+    //the tuned weights are clamped to the weight bounds, write_back puts them into the client's genome (lamarckian evolution),
+    //otherwise the genome is left alone and the returned network can be used to score the client instead
+    pub fn fine_tune_client(&self, client_name: &String, dataset: &[(Vec<f64>, Vec<f64>)], settings: &FineTuneSettings,
+                            write_back: bool) -> Backprop {
+        let client_ref = match self.clients.get(client_name) {
+            None => panic!("Whoa, client with name {} doesn't exist", client_name),
+            Some(client_ref) => client_ref,
+        };
+
+        let genome_ref = client_ref.borrow().get_genome();
+        let mut backprop = match Backprop::new(&genome_ref.borrow(), self.activation_function, true) {
+            Err(message) => panic!("can't fine tune client {}: {}", client_name, message),
+            Ok(backprop) => backprop,
+        };
+        backprop.train(dataset, settings);
+        backprop.clamp_weights(self.weight_bounds.0, self.weight_bounds.1);
+
+        if write_back {
+            backprop.write_weights(&mut genome_ref.borrow_mut());
+            client_ref.borrow_mut().generate_calculator(self.activation_function);
+        }
+
+        backprop
+    }
+
+    //a standalone copy of the client's network that can outlive this Neat, it takes inputs without the bias like use_client
+    //and has this Neat's output decoder
+    pub fn get_client_network(&self, client_name: &String) -> Network {
+        let client_ref = match self.clients.get(client_name) {
+            None => panic!("Whoa, client with name {} doesn't exist", client_name),
+            Some(client_ref) => client_ref,
+        };
+
+        let genome_ref = client_ref.borrow().get_genome();
+        let network = self.make_network(&genome_ref.borrow());
+        match network {
+            Err(message) => panic!("can't make a network from client {}: {}", client_name, message),
+            Ok(network) => network,
+        }
+    }
+
+    //the client's genome as a CTRNN (see feed_forward::ctrnn), it takes inputs without the bias like use_client
+    pub fn get_client_ctrnn(&self, client_name: &String) -> Ctrnn {
+        let client_ref = match self.clients.get(client_name) {
+            None => panic!("Whoa, client with name {} doesn't exist", client_name),
+            Some(client_ref) => client_ref,
+        };
+
+        let genome_ref = client_ref.borrow().get_genome();
+        let ctrnn = Ctrnn::new(&genome_ref.borrow(), self.activation_function, true);
+        match ctrnn {
+            Err(message) => panic!("can't make a ctrnn from client {}: {}", client_name, message),
+            Ok(ctrnn) => ctrnn,
+        }
+    }
+
+    //the client's genome as spiking neurons (see feed_forward::spiking), it takes inputs without the bias like use_client
+    pub fn get_client_spiking_network(&self, client_name: &String, model: NeuronModel, encoding: InputEncoding) -> SpikingNetwork {
+        let client_ref = match self.clients.get(client_name) {
+            None => panic!("Whoa, client with name {} doesn't exist", client_name),
+            Some(client_ref) => client_ref,
+        };
+
+        let genome_ref = client_ref.borrow().get_genome();
+        let network = SpikingNetwork::new(&genome_ref.borrow(), model, encoding, true);
+        match network {
+            Err(message) => panic!("can't make a spiking network from client {}: {}", client_name, message),
+            Ok(network) => network,
+        }
+    }
+
+    //same bias/activation/decoder as the clients
+    fn make_network(&self, genome: &Genome) -> Result<Network, String> {
+        let mut network = Network::new(genome, self.activation_function, true)?;
+        network.set_decoder(self.output_decoder.clone())?;
+        Ok(network)
+    }
+
     pub fn score_client(&self, client_name: &String, score: f64) {
         match self.clients.get(client_name) {
             None => panic!("Whoa, client with name {} doesn't exist", client_name),
@@ -164,6 +348,14 @@ impl Neat {
     pub fn update_clients(&mut self) {
         // println!("starting client updates...");
 
+        let best_score = self.clients.values().map(|client_ref| client_ref.borrow().get_score()).fold(f64::NEG_INFINITY, f64::max);
+        if let Some(phased_search) = &mut self.phased_search {
+            phased_search.record_fitness(best_score);
+        }
+        self.update_mutation_schedule(best_score);
+
+        self.record_hall_of_fame();
+
         /*
         evaluate species
         kill low species
@@ -274,12 +466,21 @@ impl Neat {
 
             self.species.clear();
         }
+        self.record_mutation_rates();
 
         /*
         sort clients into species
         */
         self.sort_clients_into_species();
 
+        //decide next generation's phase
+        let mean_complexity = self.get_mean_complexity();
+        if let Some(phased_search) = &mut self.phased_search {
+            phased_search.record_complexity(mean_complexity);
+        }
+
+        self.generation += 1;
+
         // println!("finished client updates {:?}", self.species.iter().map(|(a,b)| format!("{} {}", a, b.borrow().size())).collect::<Vec<String>>());
     }
 
@@ -320,7 +521,7 @@ impl Neat {
         }
     }
 
-    //generate the base genome (in nodes, out nodes, 
+    //generate the base genome (in nodes, out nodes, connected like initial_topology says, see neat::initial_topology)
     fn get_default_genome(&mut self) -> Genome {
         let mut genome = Genome::new();
 
@@ -328,22 +529,51 @@ impl Neat {
             match self.get_node_by_inv_num(i) {
                 None => break,
                 Some(node) => {
-                    if node.get_x() != 0.1 && node.get_x() != 0.9 { break; }
+                    if node.get_role() == NodeRole::Hidden { break; }
 
                     genome.add_node(node);
                 }
             }
         }
 
-        //add connections from each in node to every out node
-        for in_node_inv in 0..self.num_of_input_nodes {
-            for out_node_inv in self.num_of_input_nodes..self.num_of_input_nodes+self.num_of_output_nodes {
-                let con_num = self.get_connection_number_from_nodes(in_node_inv, out_node_inv);
-                genome.add_connection(self.new_connection(con_num, in_node_inv, out_node_inv));
-            }
+        let in_nodes: Vec<usize> = (0..self.num_of_input_nodes).collect();
+        let out_nodes: Vec<usize> = (self.num_of_input_nodes..self.num_of_input_nodes+self.num_of_output_nodes).collect();
+        let every_pair = |from: &[usize], to: &[usize]| -> Vec<(usize, usize)> {
+            from.iter().flat_map(|from_inv| to.iter().map(move |to_inv| (*from_inv, *to_inv))).collect()
+        };
+
+        //(from, to) of every connection
+        let mut connections: Vec<(usize, usize)> = match self.initial_topology {
+            InitialTopology::FullyConnected => every_pair(&in_nodes, &out_nodes),
+            InitialTopology::FullyConnectedHidden(hidden_nodes) => {
+                let mut hidden_invs = Vec::new();
+                for i in 0..hidden_nodes {
+                    let node = self.get_initial_hidden_node(i, hidden_nodes);
+                    hidden_invs.push(node.get_innovation_number());
+                    genome.add_node(node);
+                }
+
+                let mut connections = every_pair(&in_nodes, &hidden_invs);
+                connections.extend(every_pair(&hidden_invs, &out_nodes));
+                connections
+            },
+            InitialTopology::Sparse(fraction) => {
+                let all_connections = every_pair(&in_nodes, &out_nodes);
+                let number = (fraction * all_connections.len() as f64).round() as usize;
+                all_connections.into_iter().choose_multiple(&mut self.cached_rng, number)
+            },
+            InitialTopology::Unconnected => Vec::new(),
+        };
+        connections.sort_unstable(); //new innovation numbers in a steady order
+
+        for (from_inv, to_inv) in connections {
+            let con_num = self.get_connection_number_from_nodes(from_inv, to_inv);
+            genome.add_connection(self.new_connection(con_num, from_inv, to_inv));
         }
 
-        GenomeMutator::mutate_full(self, &mut genome);
+        if self.initial_mutate_full {
+            GenomeMutator::mutate_full(self, &mut genome);
+        }
         genome
     }
 
@@ -361,7 +591,9 @@ impl Neat {
             assert!(node0.get_x() < node1.get_x());
 
             self.nodes_to_connection_map.insert((node0_num, node1_num), connection_num);
-            return ConnectionGene::new(connection_num, Rc::clone(&node0), Rc::clone(&node1));
+            let mut connection = ConnectionGene::new(connection_num, Rc::clone(&node0), Rc::clone(&node1));
+            connection.weight = self.clamp_weight(connection.weight); //the default weight can be out of bounds too
+            return connection;
         }
 
         panic!("new_connection's nodes do not exist");
@@ -375,8 +607,8 @@ impl Neat {
         }
     }
 
-    fn get_new_node_from_xy(&mut self, x: f64, y: f64) -> Rc<NodeGene> {
-        let new_node = Rc::new(NodeGene::new(self.node_bank.len(), x, y));
+    fn get_new_node_from_xy(&mut self, x: f64, y: f64, role: NodeRole) -> Rc<NodeGene> {
+        let new_node = Rc::new(NodeGene::new_with_role(self.node_bank.len(), x, y, role));
         self.node_bank.insert(new_node.get_innovation_number(), Rc::clone(&new_node));
 
         new_node
@@ -385,15 +617,39 @@ impl Neat {
     fn get_replacement_for_connection(&mut self, connection: &ConnectionGene) -> (ConnectionGene, ConnectionGene, Rc<NodeGene>) {
         let replacement_node: Rc<NodeGene> = match self.connection_to_replacement_node_map.get(&connection.get_innovation_number()) {
             None => { //get new node
-                let x = ( connection.to.get_x() + connection.from.get_x() ) / 2.0;
-                let y = ( connection.to.get_y() + connection.from.get_y() ) / 2.0;
-                let con = self.get_new_node_from_xy(x, y);
+                let con = self.get_new_node_for_connection(connection);
                 self.connection_to_replacement_node_map.insert(connection.get_innovation_number(), con.get_innovation_number());
                 con
             },
             Some(node_num) => Rc::clone(self.node_bank.get(node_num).unwrap())
         };
 
+        self.get_connections_through_node(connection, replacement_node)
+    }
+
+    //like get_replacement_for_connection but with its own node (and so connections), the node bank's node isn't modulatory
+    fn get_modulatory_node_for_connection(&mut self, connection: &ConnectionGene) -> (ConnectionGene, ConnectionGene, Rc<NodeGene>) {
+        let modulatory_node: Rc<NodeGene> = match self.connection_to_modulatory_node_map.get(&connection.get_innovation_number()) {
+            None => {
+                let node = self.get_new_node_for_connection(connection);
+                self.connection_to_modulatory_node_map.insert(connection.get_innovation_number(), node.get_innovation_number());
+                node
+            },
+            Some(node_num) => Rc::clone(&self.node_bank[node_num]),
+        };
+
+        self.get_connections_through_node(connection, modulatory_node)
+    }
+
+    //halfway along the connection
+    fn get_new_node_for_connection(&mut self, connection: &ConnectionGene) -> Rc<NodeGene> {
+        let x = ( connection.to.get_x() + connection.from.get_x() ) / 2.0;
+        let y = ( connection.to.get_y() + connection.from.get_y() ) / 2.0;
+        self.get_new_node_from_xy(x, y, NodeRole::Hidden)
+    }
+
+    //from -> node and node -> to
+    fn get_connections_through_node(&mut self, connection: &ConnectionGene, replacement_node: Rc<NodeGene>) -> (ConnectionGene, ConnectionGene, Rc<NodeGene>) {
         let con0_num = self.get_connection_number_from_nodes(connection.from.get_innovation_number(), replacement_node.get_innovation_number());
         let con1_num = self.get_connection_number_from_nodes(replacement_node.get_innovation_number(), connection.to.get_innovation_number());
 
@@ -427,12 +683,23 @@ impl Neat {
         self.species_distance_threshold
     }
 
-    fn get_random_weight_max(&self) -> f64 {
-        self.random_weight_max
+    //a weight for a new connection or mutate_random_weight
+    fn get_new_random_weight(&mut self) -> f64 {
+        let weight = self.random_weight_distribution.sample(&mut self.cached_rng);
+        self.clamp_weight(weight)
+    }
+
+    fn get_shifted_weight(&mut self, weight: f64) -> f64 {
+        let weight = weight + self.weight_shift_distribution.sample(&mut self.cached_rng);
+        self.clamp_weight(weight)
     }
 
-    fn get_random_weight_shift_max(&self) -> f64 {
-        self.random_weight_shift_max
+    fn clamp_weight(&self, weight: f64) -> f64 {
+        weight.max(self.weight_bounds.0).min(self.weight_bounds.1)
+    }
+
+    fn get_weight_mutation_rate(&self) -> f64 {
+        self.weight_mutation_rate
     }
 
     fn get_max_mutation_attempts(&self) -> u64 {
@@ -447,6 +714,49 @@ impl Neat {
         self.clients.len()
     }
 
+    pub fn get_generation(&self) -> u64 {
+        self.generation
+    }
+
+    //None when not using phased search
+    pub fn get_search_phase(&self) -> Option<SearchPhase> {
+        self.phased_search.as_ref().map(|phased_search| phased_search.phase)
+    }
+
+    //mean number of nodes + connections over all the clients' genomes
+    pub fn get_mean_complexity(&self) -> f64 {
+        if self.clients.is_empty() {
+            return 0.0;
+        }
+
+        let total: usize = self.clients.values().map(|client_ref| {
+            let genome_ref = client_ref.borrow().get_genome();
+            let genome = genome_ref.borrow();
+            genome.nodes.len() + genome.connections.len()
+        }).sum();
+
+        total as f64 / self.clients.len() as f64
+    }
+
+    fn allows_additions(&self) -> bool {
+        self.phased_search.as_ref().is_none_or(|phased_search| phased_search.allows_additions())
+    }
+
+    fn allows_deletions(&self) -> bool {
+        self.phased_search.as_ref().is_none_or(|phased_search| phased_search.allows_deletions())
+    }
+
+    pub fn get_client_names(&self) -> Vec<String> {
+        self.clients.keys().map(String::clone).collect()
+    }
+
+    pub fn save_client_genome<P: AsRef<Path>>(&self, client_name: &String, path: P) -> io::Result<()> {
+        match self.clients.get(client_name) {
+            None => Err(io::Error::new(io::ErrorKind::NotFound, format!("client with name {} doesn't exist", client_name))),
+            Some(client_ref) => client_ref.borrow().get_genome().borrow().save(path),
+        }
+    }
+
     pub fn display_genome(&self, client_name: &String) {
         if let Some(client_ref) = self.clients.get(client_name) {
             let client = client_ref.borrow();
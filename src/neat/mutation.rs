@@ -0,0 +1,157 @@
+use crate::feed_forward::mutation_rates::MutationRates;
+use super::Neat;
+use std::fmt;
+use std::str::FromStr;
+
+/* - Mutations -
+Every mutation mutate_random can do, with the probability (0 to 1) of it happening, set through a NeatConfig as
+mutate_probability_<name> = p (an older config's mutate_chance_<name> = x, 1 in x, is read as 1/x, 0 being never)
+the five main ones can also be the genome's own/scheduled (see neat::adaptive_mutation)
+how they're picked (mutation_mode)
+    independent - each mutation happens with its own probability, a genome can get all of them at once
+    exactly_one - one mutation is picked, more probable mutations more often (the probabilities don't need to add up to 1),
+        nothing happens if every probability is 0
+mutate_random does this mutation_rounds times (1 unless set through a NeatConfig)
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    AddNode,
+    AddConnection,
+    AddModulatoryNode,
+    RandomWeight,
+    WeightShift,
+    ToggleConnection,
+    Aggregation,
+    Hebbian,
+    TimeConstant,
+    Neuron,
+    RemoveConnection,
+    RemoveNode,
+}
+
+impl Mutation {
+    //the order mutate_random tries them in
+    pub const ALL: [Mutation; 12] = [
+        Mutation::AddNode, Mutation::AddConnection, Mutation::AddModulatoryNode,
+        Mutation::RandomWeight, Mutation::WeightShift, Mutation::ToggleConnection,
+        Mutation::Aggregation, Mutation::Hebbian, Mutation::TimeConstant, Mutation::Neuron,
+        Mutation::RemoveConnection, Mutation::RemoveNode,
+    ];
+
+    //same as the mutate_probability_<name> config keys
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutation::AddNode => "add_node",
+            Mutation::AddConnection => "add_connection",
+            Mutation::AddModulatoryNode => "add_modulatory_node",
+            Mutation::RandomWeight => "random_weight",
+            Mutation::WeightShift => "weight_shift",
+            Mutation::ToggleConnection => "toggle_connection",
+            Mutation::Aggregation => "aggregation",
+            Mutation::Hebbian => "hebbian",
+            Mutation::TimeConstant => "time_constant",
+            Mutation::Neuron => "neuron",
+            Mutation::RemoveConnection => "remove_connection",
+            Mutation::RemoveNode => "remove_node",
+        }
+    }
+
+    //phased search only allows one of these at a time
+    pub fn adds_structure(&self) -> bool {
+        matches!(self, Mutation::AddNode | Mutation::AddConnection | Mutation::AddModulatoryNode)
+    }
+
+    pub fn removes_structure(&self) -> bool {
+        matches!(self, Mutation::RemoveConnection | Mutation::RemoveNode)
+    }
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Mutation {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Mutation::ALL.iter().copied().find(|mutation| mutation.name() == text.trim())
+            .ok_or_else(|| format!("unknown mutation '{}'", text.trim()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MutationMode {
+    #[default]
+    Independent,
+    ExactlyOne,
+}
+
+impl fmt::Display for MutationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationMode::Independent => write!(f, "independent"),
+            MutationMode::ExactlyOne => write!(f, "exactly_one"),
+        }
+    }
+}
+
+impl FromStr for MutationMode {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            "independent" => Ok(MutationMode::Independent),
+            "exactly_one" => Ok(MutationMode::ExactlyOne),
+            _ => Err(format!("unknown mutation mode '{}', expected independent or exactly_one", text.trim())),
+        }
+    }
+}
+
+impl Neat {
+    //the probability of the mutation, before adaptive mutation (see above)
+    pub fn get_mutation_probability(&self, mutation: Mutation) -> f64 {
+        match mutation {
+            Mutation::AddNode => self.mutate_probability_add_node,
+            Mutation::AddConnection => self.mutate_probability_add_connection,
+            Mutation::AddModulatoryNode => self.mutate_probability_add_modulatory_node,
+            Mutation::RandomWeight => self.mutate_probability_random_weight,
+            Mutation::WeightShift => self.mutate_probability_weight_shift,
+            Mutation::ToggleConnection => self.mutate_probability_toggle_connection,
+            Mutation::Aggregation => self.mutate_probability_aggregation,
+            Mutation::Hebbian => self.mutate_probability_hebbian,
+            Mutation::TimeConstant => self.mutate_probability_time_constant,
+            Mutation::Neuron => self.mutate_probability_neuron,
+            Mutation::RemoveConnection => self.mutate_probability_remove_connection,
+            Mutation::RemoveNode => self.mutate_probability_remove_node,
+        }
+    }
+
+    //what mutate_random can do this round with the genome's (adapted) main probabilities, phased search leaves some out
+    pub(super) fn get_allowed_mutation_probabilities(&self, rates: &MutationRates) -> Vec<(Mutation, f64)> {
+        let (allows_additions, allows_deletions) = (self.allows_additions(), self.allows_deletions());
+        let main_probabilities = rates.to_array();
+
+        Mutation::ALL.iter().copied()
+            .filter(|mutation| (allows_additions || !mutation.adds_structure()) && (allows_deletions || !mutation.removes_structure()))
+            .map(|mutation| {
+                //the main mutations have the same names as the rates
+                let probability = match MutationRates::NAMES.iter().position(|name| *name == mutation.name()) {
+                    Some(index) => main_probabilities[index],
+                    None => self.get_mutation_probability(mutation),
+                };
+                (mutation, probability)
+            })
+            .collect()
+    }
+
+    pub fn get_mutation_mode(&self) -> MutationMode {
+        self.mutation_mode
+    }
+
+    pub fn get_mutation_rounds(&self) -> u64 {
+        self.mutation_rounds
+    }
+}
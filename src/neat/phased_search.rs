@@ -0,0 +1,167 @@
+use std::fmt;
+use std::str::FromStr;
+
+/* - Phased search -
+Green's phased pruning, the population alternates between
+    complexifying - mutations only add structure (no deletions)
+    simplifying - mutations only delete structure (no additions)
+weight and toggle mutations happen in both phases
+complexity is the mean number of nodes + connections in the population's genomes
+    complexifying -> simplifying when mean complexity goes over the ceiling (last floor + complexity_threshold)
+        or when the best score hasn't improved for fitness_plateau_generations (0 turns this off)
+    simplifying -> complexifying when mean complexity hasn't gone down for complexity_plateau_generations
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchPhase {
+    Complexifying,
+    Simplifying,
+}
+
+impl fmt::Display for SearchPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchPhase::Complexifying => write!(f, "complexifying"),
+            SearchPhase::Simplifying => write!(f, "simplifying"),
+        }
+    }
+}
+
+impl FromStr for SearchPhase {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "complexifying" => Ok(SearchPhase::Complexifying),
+            "simplifying" => Ok(SearchPhase::Simplifying),
+            _ => Err(format!("unknown search phase '{}'", text)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PhasedSearch {
+    //settings
+    pub(crate) complexity_threshold: f64,
+    pub(crate) fitness_plateau_generations: u64,
+    pub(crate) complexity_plateau_generations: u64,
+
+    //state
+    pub(crate) phase: SearchPhase,
+    pub(crate) complexity_ceiling: Option<f64>, //None until the first generation is measured
+    pub(crate) best_fitness: f64,
+    pub(crate) generations_since_fitness_improved: u64,
+    pub(crate) lowest_complexity: f64,
+    pub(crate) generations_since_complexity_dropped: u64,
+}
+
+impl PhasedSearch {
+    pub(crate) fn new(complexity_threshold: f64, fitness_plateau_generations: u64, complexity_plateau_generations: u64) -> Self {
+        PhasedSearch {
+            complexity_threshold,
+            fitness_plateau_generations,
+            complexity_plateau_generations,
+            phase: SearchPhase::Complexifying,
+            complexity_ceiling: None,
+            best_fitness: f64::NEG_INFINITY,
+            generations_since_fitness_improved: 0,
+            lowest_complexity: f64::INFINITY,
+            generations_since_complexity_dropped: 0,
+        }
+    }
+
+    //call with the generation's best score, before the population changes
+    pub(crate) fn record_fitness(&mut self, best_fitness: f64) {
+        if best_fitness > self.best_fitness {
+            self.best_fitness = best_fitness;
+            self.generations_since_fitness_improved = 0;
+        } else {
+            self.generations_since_fitness_improved += 1;
+        }
+    }
+
+    //call with the new population's mean complexity, decides the phase for the next generation
+    pub(crate) fn record_complexity(&mut self, mean_complexity: f64) {
+        match self.phase {
+            SearchPhase::Complexifying => {
+                let ceiling = *self.complexity_ceiling.get_or_insert(mean_complexity + self.complexity_threshold);
+                let fitness_plateaued = self.fitness_plateau_generations > 0
+                    && self.generations_since_fitness_improved >= self.fitness_plateau_generations;
+
+                if mean_complexity > ceiling || fitness_plateaued {
+                    self.phase = SearchPhase::Simplifying;
+                    self.lowest_complexity = mean_complexity;
+                    self.generations_since_complexity_dropped = 0;
+                }
+            },
+            SearchPhase::Simplifying => {
+                if mean_complexity < self.lowest_complexity {
+                    self.lowest_complexity = mean_complexity;
+                    self.generations_since_complexity_dropped = 0;
+                } else {
+                    self.generations_since_complexity_dropped += 1;
+                }
+
+                if self.generations_since_complexity_dropped >= self.complexity_plateau_generations {
+                    self.phase = SearchPhase::Complexifying;
+                    self.complexity_ceiling = Some(self.lowest_complexity + self.complexity_threshold);
+                    self.generations_since_fitness_improved = 0;
+                }
+            },
+        }
+    }
+
+    pub(crate) fn allows_additions(&self) -> bool {
+        self.phase == SearchPhase::Complexifying
+    }
+
+    pub(crate) fn allows_deletions(&self) -> bool {
+        self.phase == SearchPhase::Simplifying
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_to_simplifying_over_the_ceiling() {
+        let mut phased_search = PhasedSearch::new(10.0, 0, 2);
+
+        phased_search.record_complexity(20.0); //sets ceiling to 30
+        assert_eq!(phased_search.phase, SearchPhase::Complexifying);
+        phased_search.record_complexity(30.0);
+        assert_eq!(phased_search.phase, SearchPhase::Complexifying);
+        phased_search.record_complexity(30.5);
+        assert_eq!(phased_search.phase, SearchPhase::Simplifying);
+    }
+
+    #[test]
+    fn switches_back_when_complexity_stops_dropping() {
+        let mut phased_search = PhasedSearch::new(10.0, 0, 2);
+        phased_search.record_complexity(20.0);
+        phased_search.record_complexity(31.0);
+
+        phased_search.record_complexity(25.0);
+        phased_search.record_complexity(24.0);
+        phased_search.record_complexity(24.0);
+        assert_eq!(phased_search.phase, SearchPhase::Simplifying);
+        phased_search.record_complexity(24.5);
+        assert_eq!(phased_search.phase, SearchPhase::Complexifying);
+        assert_eq!(phased_search.complexity_ceiling, Some(34.0), "new ceiling is the floor plus the threshold");
+    }
+
+    #[test]
+    fn switches_to_simplifying_on_fitness_plateau() {
+        let mut phased_search = PhasedSearch::new(100.0, 2, 2);
+
+        phased_search.record_fitness(1.0);
+        phased_search.record_complexity(10.0);
+        phased_search.record_fitness(1.0);
+        phased_search.record_complexity(10.0);
+        assert_eq!(phased_search.phase, SearchPhase::Complexifying);
+        phased_search.record_fitness(0.5);
+        phased_search.record_complexity(10.0);
+        assert_eq!(phased_search.phase, SearchPhase::Simplifying);
+    }
+}
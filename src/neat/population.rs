@@ -0,0 +1,172 @@
+use super::Neat;
+use super::client::Client;
+use super::species::Species;
+use crate::feed_forward::genome::Genome;
+use crate::feed_forward::network::Network;
+use std::cell::RefCell;
+use std::cmp::Ordering::Equal;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+/* - Population views -
+Read-only access to the clients and species of a Neat
+    ClientId/SpeciesId - handles for a client/species, they deref to the name so they work with use_client, score_client etc.
+    neat.clients() / neat.species() - iterators over ClientView/SpeciesView, in no particular order
+    neat.get_client(&name) - the view of one client
+    neat.best_client() - highest scoring client
+    neat.clients_by_species() - the clients of every species
+clients that haven't been put in a species yet (from new_client, until the next update_clients) have no species id
+ */
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClientId(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpeciesId(String);
+
+impl ClientId {
+    pub(super) fn new(name: &String) -> Self {
+        ClientId(String::clone(name))
+    }
+}
+
+impl SpeciesId {
+    pub(super) fn new(name: &String) -> Self {
+        SpeciesId(String::clone(name))
+    }
+}
+
+impl Deref for ClientId {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl Deref for SpeciesId {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for SpeciesId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub struct ClientView<'a> {
+    neat: &'a Neat,
+    client: &'a Rc<RefCell<Client>>,
+}
+
+impl<'a> ClientView<'a> {
+    pub fn id(&self) -> ClientId {
+        ClientId::new(self.client.borrow().get_name())
+    }
+
+    pub fn score(&self) -> f64 {
+        self.client.borrow().get_score()
+    }
+
+    //None until the client has been sorted into a species
+    pub fn species_id(&self) -> Option<SpeciesId> {
+        let species_ref = self.client.borrow().get_species();
+        if Rc::ptr_eq(&species_ref, &self.neat.default_species) {
+            return None;
+        }
+        let species_id = SpeciesId::new(species_ref.borrow().get_name());
+        Some(species_id)
+    }
+
+    pub fn number_of_nodes(&self) -> usize {
+        self.client.borrow().get_genome().borrow().nodes.len()
+    }
+
+    pub fn number_of_connections(&self) -> usize {
+        self.client.borrow().get_genome().borrow().connections.len()
+    }
+
+    pub fn number_of_enabled_connections(&self) -> usize {
+        self.client.borrow().get_genome().borrow().connections.values().filter(|connection| connection.enabled).count()
+    }
+
+    //a copy, changing it doesn't change the client
+    pub fn genome(&self) -> Genome {
+        self.client.borrow().get_genome().borrow().clone()
+    }
+
+    pub fn network(&self) -> Network {
+        self.neat.get_client_network(self.client.borrow().get_name())
+    }
+}
+
+pub struct SpeciesView<'a> {
+    neat: &'a Neat,
+    species: &'a Rc<RefCell<Species>>,
+}
+
+impl<'a> SpeciesView<'a> {
+    pub fn id(&self) -> SpeciesId {
+        SpeciesId::new(self.species.borrow().get_name())
+    }
+
+    //mean client score from the last update_clients
+    pub fn score(&self) -> f64 {
+        self.species.borrow().get_score()
+    }
+
+    pub fn size(&self) -> usize {
+        self.species.borrow().size()
+    }
+
+    pub fn client_ids(&self) -> Vec<ClientId> {
+        self.species.borrow().get_clients().get_data().iter().map(|client_ref| ClientId::new(client_ref.borrow().get_name())).collect()
+    }
+
+    pub fn clients(&self) -> Vec<ClientView<'a>> {
+        let neat = self.neat;
+        self.species.borrow().get_clients().get_data().iter()
+            .map(|client_ref| neat.get_client(client_ref.borrow().get_name()).expect("species has a client that isn't in the neat"))
+            .collect()
+    }
+}
+
+impl Neat {
+    pub fn clients(&self) -> impl Iterator<Item=ClientView<'_>> {
+        self.clients.values().map(move |client| ClientView {neat: self, client})
+    }
+
+    pub fn species(&self) -> impl Iterator<Item=SpeciesView<'_>> {
+        self.species.values().map(move |species| SpeciesView {neat: self, species})
+    }
+
+    pub fn get_client(&self, client_name: &String) -> Option<ClientView<'_>> {
+        self.clients.get(client_name).map(|client| ClientView {neat: self, client})
+    }
+
+    pub fn get_species(&self, species_name: &String) -> Option<SpeciesView<'_>> {
+        self.species.get(species_name).map(|species| SpeciesView {neat: self, species})
+    }
+
+    //None when there are no clients, ties go to any of the tied clients
+    pub fn best_client(&self) -> Option<ClientView<'_>> {
+        self.clients().max_by(|a, b| a.score().partial_cmp(&b.score()).unwrap_or(Equal))
+    }
+
+    //clients without a species yet aren't included
+    pub fn clients_by_species(&self) -> HashMap<SpeciesId, Vec<ClientId>> {
+        self.species().map(|species| (species.id(), species.client_ids())).collect()
+    }
+}
@@ -0,0 +1,139 @@
+use super::Neat;
+use super::client::Client;
+use super::genome_neat::GenomeMutator;
+use super::population::ClientId;
+use crate::feed_forward::builder::GenomeBuilder;
+use crate::feed_forward::gene::Gene;
+use crate::feed_forward::genome::Genome;
+use crate::feed_forward::node_gene::{NodeGene, NodeRole};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/* - Seeding -
+Starting clients from an existing genome (eg. a champion from an earlier experiment) instead of the default one
+    new_client_from_genome(&genome) - a client with an exact copy of the genome
+    set_seed_genome(&genome) - seed population mode, new_client gives mutated copies of the genome
+        (set through a NeatConfig with seed_genome = <path>)
+the genome is renumbered to fit this Neat first (reconcile_genome, build_genome does it for a GenomeBuilder's genome)
+    input/output nodes - matched up in innovation number order, the bias is the first input, the counts have to match
+    hidden nodes - use a node bank node at the same x/y (the one with the same innovation number if it's there),
+        otherwise get a new one
+    connections - get the innovation number this Neat uses for their (from, to) nodes, or a new one
+ */
+
+impl Neat {
+    pub fn new_client_from_genome(&mut self, genome: &Genome) -> Result<ClientId, String> {
+        let genome = self.reconcile_genome(genome)?;
+        Ok(self.add_new_client(genome))
+    }
+
+    pub fn set_seed_genome(&mut self, genome: &Genome) -> Result<(), String> {
+        self.seed_genome = Some(self.reconcile_genome(genome)?);
+        Ok(())
+    }
+
+    //the genome new_client starts from, a mutated copy of the seed genome if there is one (an exact one without initial_mutate_full)
+    pub(super) fn get_starting_genome(&mut self) -> Genome {
+        match &self.seed_genome {
+            None => self.get_default_genome(),
+            Some(seed_genome) => {
+                let mut genome = seed_genome.clone();
+                if self.initial_mutate_full {
+                    GenomeMutator::mutate_full(self, &mut genome);
+                }
+                genome
+            },
+        }
+    }
+
+    pub(super) fn add_new_client(&mut self, genome: Genome) -> ClientId {
+        let mut client = Client::new(Rc::new(RefCell::new(genome)), self.get_default_species());
+        client.generate_calculator(self.activation_function);
+
+        let id = ClientId::new(client.get_name());
+        let client_ref = Rc::new(RefCell::new(client));
+        self.get_default_species().borrow_mut().force_put(Rc::clone(&client_ref), self.get_default_species());
+        self.clients.insert(String::clone(&id), client_ref);
+
+        id
+    }
+
+    //the builder's genome numbered with this Neat's node bank and innovation tables, new nodes/connections are added to them
+    pub fn build_genome(&mut self, builder: &GenomeBuilder) -> Result<Genome, String> {
+        self.reconcile_genome(&builder.build()?)
+    }
+
+    //a copy of the genome using this Neat's node bank and innovation numbers, see above
+    pub fn reconcile_genome(&mut self, genome: &Genome) -> Result<Genome, String> {
+        if let Some(violation) = genome.validate().first() {
+            return Err(format!("invalid genome: {}", violation));
+        }
+
+        let mut nodes: Vec<&Rc<NodeGene>> = genome.nodes.values().collect();
+        nodes.sort_unstable_by_key(|node| node.get_innovation_number());
+
+        let inputs: Vec<&Rc<NodeGene>> = nodes.iter().copied().filter(|node| node.get_role() == NodeRole::Input).collect();
+        let outputs: Vec<&Rc<NodeGene>> = nodes.iter().copied().filter(|node| node.get_role() == NodeRole::Output).collect();
+        if inputs.len() != self.num_of_input_nodes || outputs.len() != self.num_of_output_nodes {
+            return Err(format!("genome has {} input and {} output nodes (bias included), expected {} and {}",
+                               inputs.len(), outputs.len(), self.num_of_input_nodes, self.num_of_output_nodes));
+        }
+
+        //old innovation number -> node bank innovation number
+        let mut node_map: HashMap<usize, usize> = HashMap::new();
+        for (position, node) in inputs.iter().chain(outputs.iter()).enumerate() {
+            node_map.insert(node.get_innovation_number(), position);
+        }
+
+        let mut used: HashSet<usize> = node_map.values().copied().collect();
+        for node in nodes.iter().filter(|node| node.get_role() == NodeRole::Hidden) {
+            let inv_num = node.get_innovation_number();
+            let is_same = |bank_node: &NodeGene| !used.contains(&bank_node.get_innovation_number())
+                && bank_node.get_role() == NodeRole::Hidden && bank_node.get_x() == node.get_x() && bank_node.get_y() == node.get_y();
+
+            //the node with the same innovation number first, then the lowest numbered one at the same x/y
+            let mut same_nodes: Vec<usize> = self.node_bank.values().filter(|bank_node| is_same(bank_node))
+                .map(|bank_node| bank_node.get_innovation_number()).collect();
+            same_nodes.sort_unstable_by_key(|bank_inv_num| (*bank_inv_num != inv_num, *bank_inv_num));
+
+            let new_inv_num = match same_nodes.first() {
+                Some(bank_inv_num) => *bank_inv_num,
+                None => self.get_new_node_from_xy(node.get_x(), node.get_y(), NodeRole::Hidden).get_innovation_number(),
+            };
+            used.insert(new_inv_num);
+            node_map.insert(inv_num, new_inv_num);
+        }
+
+        let mut new_genome = Genome::new();
+        for node in &nodes {
+            let bank_node = &self.node_bank[&node_map[&node.get_innovation_number()]];
+            if bank_node.with_settings_of(node) == **bank_node {
+                new_genome.add_node(Rc::clone(bank_node));
+            } else {
+                new_genome.add_node(Rc::new(bank_node.with_settings_of(node)));
+            }
+        }
+
+        let mut connections: Vec<_> = genome.connections.values().collect();
+        connections.sort_unstable_by_key(|connection| connection.get_innovation_number());
+        for connection in connections {
+            let (from, to) = (node_map[&connection.from.get_innovation_number()], node_map[&connection.to.get_innovation_number()]);
+            if self.node_bank[&from].get_x() >= self.node_bank[&to].get_x() {
+                return Err(format!("connection {} goes backwards once the input/output nodes are matched up", connection.get_innovation_number()));
+            }
+
+            let con_num = self.get_connection_number_from_nodes(from, to);
+            let mut new_connection = self.new_connection(con_num, from, to);
+            new_connection.weight = self.clamp_weight(connection.weight);
+            new_connection.enabled = connection.enabled;
+            new_connection.hebbian = connection.hebbian;
+            new_genome.add_connection(new_connection);
+        }
+
+        new_genome.mutation_rates = genome.mutation_rates;
+
+        new_genome.debug_validate("reconciling a genome");
+        Ok(new_genome)
+    }
+}
@@ -0,0 +1,645 @@
+use super::Neat;
+use super::config::NeatConfig;
+use super::phased_search::SearchPhase;
+use super::weight_distribution::WeightDistribution;
+use super::population::ClientId;
+use crate::feed_forward::gene::Gene;
+use crate::feed_forward::genome::Genome;
+use crate::feed_forward::network::Network;
+use crate::feed_forward::builder::GenomeBuilder;
+use super::genome_neat::GenomeMutator;
+use crate::activation_functions::Activation;
+use crate::aggregation_functions::Aggregation;
+use crate::feed_forward::backprop::{FineTuneSettings, Loss};
+use crate::output_decoder::OutputDecoder;
+use crate::feed_forward::plasticity::HebbianRule;
+use crate::feed_forward::spiking::{NeuronModel, InputEncoding};
+use super::adaptive_mutation::MutationSchedule;
+use super::mutation::{Mutation, MutationMode};
+use super::initial_topology::InitialTopology;
+
+#[test]
+fn test_config_parse() {
+    let text = "# xor\ninput_size = 2\noutput_size=1\n\nactivation_function = tanh # comment\nc3 = 0.7\n";
+    let config = NeatConfig::parse(text).unwrap();
+
+    assert_eq!(config.input_size, 2);
+    assert_eq!(config.activation_function, Activation::Tanh);
+    assert_eq!(config.c3, 0.7);
+    assert_eq!(config.c1, NeatConfig::default().c1, "missing keys keep their default");
+
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config, "to_text should parse back to the same config");
+
+    assert_eq!(NeatConfig::parse("c1 = 1\nnot_a_key = 3").err().unwrap().line, 2);
+    assert!(NeatConfig::parse("activation_function = not_a_function").is_err());
+    assert!(NeatConfig::parse("input_size = -1").is_err());
+
+    let weights = NeatConfig::parse("weight_shift_distribution = cauchy(0.2)\nweight_mutation_rate = 0.8\nweight_max = 5").unwrap();
+    assert_eq!(weights.weight_shift_distribution, Some(WeightDistribution::Cauchy {scale: 0.2}));
+    assert_eq!(weights.random_weight_distribution, None);
+    assert_eq!(NeatConfig::parse(&weights.to_text()).unwrap(), weights);
+    assert!(NeatConfig::parse("weight_mutation_rate = 1.5").is_err());
+    assert!(Neat::from_config(&NeatConfig {weight_min: 1.0, weight_max: -1.0, ..NeatConfig::default()}).is_err());
+
+    let aggregations = NeatConfig::parse("mutate_probability_aggregation = 0.2\naggregation_options = sum, max_abs,product").unwrap();
+    assert_eq!(aggregations.aggregation_options, vec![Aggregation::Sum, Aggregation::MaxAbs, Aggregation::Product]);
+    assert_eq!(NeatConfig::parse(&aggregations.to_text()).unwrap(), aggregations);
+    assert!(NeatConfig::parse("aggregation_options = sum, average").is_err());
+}
+
+#[test]
+fn test_checkpoint_round_trip() {
+    let config = NeatConfig::default();
+    let mut neat = Neat::from_config(&config).unwrap();
+
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    neat.update_clients();
+
+    let text = neat.checkpoint_to_text();
+    let loaded = Neat::checkpoint_from_text(&config, &text).unwrap();
+
+    assert_eq!(loaded.get_generation(), 1);
+    assert_eq!(loaded.get_number_of_clients(), 10);
+    assert_eq!(loaded.node_bank.len(), neat.node_bank.len());
+    assert_eq!(loaded.nodes_to_connection_map, neat.nodes_to_connection_map);
+    assert_eq!(loaded.checkpoint_to_text(), text, "saving a loaded checkpoint gives the same checkpoint");
+
+    //connections are summed in hashmap order, so allow for rounding
+    for name in neat.get_client_names() {
+        let (loaded_output, output) = (loaded.use_client(&name, &vec![0.3, 0.6]), neat.use_client(&name, &vec![0.3, 0.6]));
+        assert!((loaded_output[0] - output[0]).abs() < 1e-12, "clients should give the same outputs");
+    }
+}
+
+#[test]
+fn test_checkpoint_keeps_aggregations() {
+    let config = NeatConfig {mutate_probability_aggregation: 1.0, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    for _ in 0..3 {
+        neat.update_clients();
+    }
+
+    let text = neat.checkpoint_to_text();
+    assert!(text.contains(" aggregation="), "every mutation changes an aggregation:\n{}", text);
+
+    let loaded = Neat::checkpoint_from_text(&config, &text).unwrap();
+    assert_eq!(loaded.checkpoint_to_text(), text);
+    for name in neat.get_client_names() {
+        let (loaded_output, output) = (loaded.use_client(&name, &vec![0.3, 0.6]), neat.use_client(&name, &vec![0.3, 0.6]));
+        assert!((loaded_output[0] - output[0]).abs() < 1e-12, "clients should give the same outputs");
+    }
+}
+
+#[test]
+fn test_checkpoint_keeps_modulatory_nodes() {
+    let config = NeatConfig {mutate_probability_add_modulatory_node: 1.0, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    neat.update_clients();
+
+    let text = neat.checkpoint_to_text();
+    assert!(text.contains("\nmodulatory "), "every client got a modulatory node:\n{}", text);
+
+    let loaded = Neat::checkpoint_from_text(&config, &text).unwrap();
+    assert_eq!(loaded.connection_to_modulatory_node_map, neat.connection_to_modulatory_node_map);
+    assert_eq!(loaded.checkpoint_to_text(), text);
+}
+
+#[test]
+fn test_checkpoint_needs_matching_config() {
+    let neat = Neat::from_config(&NeatConfig::default()).unwrap();
+    let text = neat.checkpoint_to_text();
+
+    let bigger_config = NeatConfig {output_size: 4, ..NeatConfig::default()};
+
+    assert!(Neat::checkpoint_from_text(&bigger_config, &text).is_err());
+}
+
+#[test]
+fn test_checkpoint_keeps_phased_search_state() {
+    let config = NeatConfig {phased_search: true, phase_complexity_threshold: 0.5, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    neat.update_clients();
+    neat.update_clients();
+
+    let loaded = Neat::checkpoint_from_text(&config, &neat.checkpoint_to_text()).unwrap();
+
+    assert!(loaded.phased_search.is_some());
+    assert_eq!(loaded.phased_search, neat.phased_search);
+}
+
+#[test]
+fn test_phased_search_blocks_mutations() {
+    let config = NeatConfig {
+        phased_search: true,
+        mutate_probability_add_node: 1.0,
+        mutate_probability_add_connection: 1.0,
+        mutate_probability_remove_connection: 1.0,
+        mutate_probability_remove_node: 1.0,
+        proportion_to_kill: 0.0, //no breeding, only mutation changes the genomes
+        ..NeatConfig::default()
+    };
+    let mut neat = Neat::from_config(&config).unwrap();
+    assert_eq!(neat.get_search_phase(), Some(SearchPhase::Complexifying));
+
+    for _ in 0..10 {
+        neat.new_client();
+    }
+
+    //complexifying never removes anything, so complexity can't go down
+    let before = neat.get_mean_complexity();
+    neat.update_clients();
+    assert!(neat.get_mean_complexity() >= before);
+
+    neat.phased_search.as_mut().unwrap().phase = SearchPhase::Simplifying;
+
+    let before = neat.get_mean_complexity();
+    neat.update_clients();
+    assert!(neat.get_mean_complexity() <= before, "simplifying never adds anything");
+}
+
+#[test]
+fn test_fine_tune_client() {
+    let config = NeatConfig {activation_function: Activation::Tanh, weight_min: -3.0, weight_max: 3.0, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    let name = neat.new_client();
+    //every client starts empty, give it connections to tune
+    let genome_ref = neat.clients[name.as_str()].borrow().get_genome();
+    for _ in 0..20 {
+        GenomeMutator::mutate_add_connection(&mut neat, &mut genome_ref.borrow_mut());
+    }
+    neat.clients[name.as_str()].borrow_mut().generate_calculator(neat.activation_function);
+
+    let dataset = vec![(vec![0.0, 0.0], vec![-0.5]), (vec![0.0, 1.0], vec![0.5]), (vec![1.0, 0.0], vec![0.5]), (vec![1.0, 1.0], vec![-0.5])];
+    let settings = FineTuneSettings {epochs: 200, ..FineTuneSettings::default()};
+    let outputs_before: Vec<Vec<f64>> = dataset.iter().map(|(inputs, _targets)| neat.use_client(&name, inputs)).collect();
+
+    //without write back the client is untouched, the returned network has the tuned weights
+    let tuned = neat.fine_tune_client(&name, &dataset, &settings, false);
+    for ((inputs, _targets), before) in dataset.iter().zip(&outputs_before) {
+        assert_eq!(&neat.use_client(&name, inputs), before);
+    }
+    let before_loss: f64 = dataset.iter().zip(&outputs_before).map(|((_inputs, targets), outputs)| Loss::MeanSquaredError.value(outputs, targets)).sum::<f64>() / 4.0;
+    assert!(tuned.get_loss(&dataset, Loss::MeanSquaredError) <= before_loss);
+
+    //lamarckian, the client's genome gets the weights
+    let tuned = neat.fine_tune_client(&name, &dataset, &settings, true);
+    for (inputs, _targets) in &dataset {
+        let (client_output, tuned_output) = (neat.use_client(&name, inputs), tuned.run(inputs));
+        assert!((client_output[0] - tuned_output[0]).abs() < 1e-12, "client gives {:?}, tuned network gives {:?}", client_output, tuned_output);
+    }
+    assert!(tuned.get_weights().values().all(|weight| (-3.0..=3.0).contains(weight)), "weights stay in the bounds");
+}
+
+#[test]
+fn test_client_network() {
+    let mut neat = Neat::from_config(&NeatConfig::default()).unwrap();
+    for _ in 0..5 {
+        neat.new_client();
+    }
+    neat.update_clients();
+
+    let name = neat.get_client_names()[0].clone();
+    let network = neat.get_client_network(&name);
+    let expected = neat.use_client(&name, &vec![0.3, 0.6]);
+
+    //the network doesn't need the Neat anymore
+    drop(neat);
+    assert_eq!(network.get_number_of_inputs(), 2);
+    let output = network.run(&[0.3, 0.6]);
+    assert!((output[0] - expected[0]).abs() < 1e-12, "network gave {}, client gave {}", output[0], expected[0]);
+}
+
+#[test]
+fn test_population_views() {
+    let mut neat = Neat::from_config(&NeatConfig::default()).unwrap();
+    let ids: Vec<ClientId> = (0..10).map(|_| neat.new_client()).collect();
+
+    //not sorted into species yet
+    assert!(neat.clients().all(|client| client.species_id().is_none()));
+    assert!(neat.clients_by_species().is_empty());
+
+    for (i, id) in ids.iter().enumerate() {
+        neat.score_client(id, i as f64);
+    }
+    let best = neat.best_client().unwrap();
+    assert_eq!(best.id(), ids[9]);
+    assert_eq!(best.score(), 9.0);
+    let genome = best.genome();
+    assert_eq!((genome.nodes.len(), genome.connections.len()), (best.number_of_nodes(), best.number_of_connections()));
+    assert!(best.number_of_enabled_connections() <= best.number_of_connections());
+
+    neat.update_clients();
+
+    let mut listed: Vec<ClientId> = neat.clients().map(|client| client.id()).collect();
+    listed.sort();
+    let mut expected = ids.clone();
+    expected.sort();
+    assert_eq!(listed, expected, "update_clients keeps the same clients");
+
+    let by_species = neat.clients_by_species();
+    assert_eq!(by_species.len(), neat.get_number_of_species());
+    assert_eq!(by_species.values().map(Vec::len).sum::<usize>(), 10, "every client is in a species after update_clients");
+    for (species_id, client_ids) in &by_species {
+        let species = neat.get_species(species_id).unwrap();
+        assert_eq!(species.size(), client_ids.len());
+        for client_id in client_ids {
+            assert_eq!(neat.get_client(client_id).unwrap().species_id().as_ref(), Some(species_id));
+        }
+    }
+
+    assert!(neat.get_client(&String::from("client_missing")).is_none());
+}
+
+#[test]
+fn test_hall_of_fame() {
+    let config = NeatConfig {hall_of_fame_size: 3, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    let ids: Vec<ClientId> = (0..10).map(|_| neat.new_client()).collect();
+
+    for (i, id) in ids.iter().enumerate() {
+        neat.score_client(id, i as f64);
+    }
+    let best_genome = neat.best_client().unwrap().genome().to_text();
+    neat.update_clients();
+
+    let hall_of_fame = neat.get_hall_of_fame();
+    assert_eq!(hall_of_fame.iter().map(|entry| entry.fitness).collect::<Vec<f64>>(), vec![9.0, 8.0, 7.0]);
+    assert_eq!(hall_of_fame[0].generation, 0);
+    assert_eq!(hall_of_fame[0].genome.to_text(), best_genome);
+    assert!(hall_of_fame[0].species.is_none(), "clients weren't in a species in the first generation");
+
+    //worse generations don't push the best genomes out
+    for generation in 1..4 {
+        for id in neat.get_client_names() {
+            neat.score_client(&id, generation as f64);
+        }
+        neat.update_clients();
+    }
+    let hall_of_fame = neat.get_hall_of_fame();
+    assert_eq!(hall_of_fame.iter().map(|entry| entry.fitness).collect::<Vec<f64>>(), vec![9.0, 8.0, 7.0]);
+    assert_eq!(neat.get_hall_of_fame_networks().len(), 3);
+
+    let text = neat.checkpoint_to_text();
+    let loaded = Neat::checkpoint_from_text(&config, &text).unwrap();
+    assert_eq!(loaded.checkpoint_to_text(), text, "the hall of fame is kept in checkpoints");
+    let without = Neat::checkpoint_from_text(&NeatConfig::default(), &text).unwrap();
+    assert!(without.get_hall_of_fame().is_empty());
+
+    let replaced = neat.reseed_from_hall_of_fame(2);
+    assert_eq!(replaced.len(), 2);
+    for (id, entry) in replaced.iter().zip(neat.get_hall_of_fame()) {
+        let client = neat.get_client(id).unwrap();
+        assert_eq!(client.genome().to_text(), entry.genome.to_text());
+        assert_eq!(client.score(), entry.fitness);
+    }
+}
+
+#[test]
+fn test_new_client_from_genome() {
+    let config = NeatConfig {mutate_probability_add_node: 0.5, ..NeatConfig::default()};
+    let mut earlier = Neat::from_config(&config).unwrap();
+    for _ in 0..10 {
+        earlier.new_client();
+    }
+    for _ in 0..3 {
+        earlier.update_clients();
+    }
+    let champion = earlier.best_client().unwrap().id();
+    let genome = earlier.get_client(&champion).unwrap().genome();
+
+    //a new run has never seen the champion's hidden nodes or connections
+    let mut neat = Neat::from_config(&config).unwrap();
+    let id = neat.new_client_from_genome(&genome).unwrap();
+    let copy = neat.get_client(&id).unwrap().genome();
+    assert_eq!((copy.nodes.len(), copy.connections.len()), (genome.nodes.len(), genome.connections.len()));
+    assert!(copy.nodes.keys().all(|inv_num| neat.node_bank.contains_key(inv_num)));
+    assert!(copy.connections.values().all(|connection| {
+        neat.nodes_to_connection_map.get(&(connection.from.get_innovation_number(), connection.to.get_innovation_number())) == Some(&connection.get_innovation_number())
+    }));
+    for inputs in [vec![0.0, 0.0], vec![0.3, 0.6], vec![1.0, -1.0]].iter() {
+        let (output, expected) = (neat.use_client(&id, inputs), earlier.use_client(&champion, inputs));
+        assert!((output[0] - expected[0]).abs() < 1e-12, "copy gave {}, original gave {}", output[0], expected[0]);
+    }
+
+    //the same genome again doesn't make new innovations
+    let bank_size = neat.node_bank.len();
+    neat.new_client_from_genome(&genome).unwrap();
+    assert_eq!(neat.node_bank.len(), bank_size);
+
+    //numbered some other way, input/output nodes are matched up in innovation number order
+    let foreign = Genome::from_text("node 10 x=0.1 y=0\nnode 11 x=0.1 y=0\nnode 12 x=0.1 y=0\nnode 20 x=0.9 y=0\nnode 15 x=0.5 y=0 aggregation=max\n\
+                                     connection 3 from=11 to=15 weight=0.5\nconnection 4 from=12 to=15 weight=-1.5\nconnection 7 from=15 to=20 weight=2\n\
+                                     connection 8 from=10 to=20 weight=0.25\n").unwrap();
+    let id = neat.new_client_from_genome(&foreign).unwrap();
+    let network = Network::new(&foreign, Activation::Sigmoid, true).unwrap();
+    assert_eq!(neat.use_client(&id, &vec![0.3, 0.6]), network.run(&[0.3, 0.6]));
+
+    let wrong_size = Genome::from_text("node 0 x=0.1 y=0\nnode 1 x=0.9 y=0\n").unwrap();
+    assert!(neat.new_client_from_genome(&wrong_size).is_err());
+}
+
+#[test]
+fn test_seed_population() {
+    let seed = Genome::from_text("node 0 x=0.1 y=0\nnode 1 x=0.1 y=0\nnode 2 x=0.1 y=0\nnode 3 x=0.9 y=0\nnode 4 x=0.5 y=0\n\
+                                  connection 0 from=1 to=4 weight=0.5\nconnection 1 from=4 to=3 weight=2\nconnection 2 from=2 to=3 weight=-1\n").unwrap();
+    let path = std::env::temp_dir().join(format!("rust_machine_learning_library_seed_{}.genome", std::process::id()));
+    seed.save(&path).unwrap();
+
+    let config = NeatConfig {seed_genome: Some(path.display().to_string()), ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    //mutations only add to the seed, so every client still has its connections
+    for _ in 0..10 {
+        let id = neat.new_client();
+        let genome = neat.get_client(&id).unwrap().genome();
+        for (from, to) in [(1, 4), (4, 3), (2, 3)].iter() {
+            assert!(genome.connections.values().any(|connection| (connection.from.get_innovation_number(), connection.to.get_innovation_number()) == (*from, *to)));
+        }
+    }
+    neat.update_clients();
+
+    let missing = NeatConfig {seed_genome: Some(String::from("not_a_file.genome")), ..NeatConfig::default()};
+    assert!(Neat::from_config(&missing).is_err());
+}
+
+#[test]
+fn test_checkpoint_renumbers_seed_genome() {
+    let seed = Genome::from_text("node 0 x=0.1 y=0\nnode 1 x=0.1 y=0\nnode 2 x=0.1 y=0\nnode 3 x=0.9 y=0\nnode 4 x=0.5 y=0.25\n\
+                                  connection 0 from=1 to=4 weight=0.5\nconnection 1 from=4 to=3 weight=2\nconnection 2 from=2 to=3 weight=-1\n").unwrap();
+    let path = std::env::temp_dir().join(format!("rust_machine_learning_library_checkpoint_seed_{}.genome", std::process::id()));
+    seed.save(&path).unwrap();
+
+    //the checkpoint's tables already have other nodes and connections when the seed gets its numbers
+    let config = NeatConfig {mutate_probability_add_node: 1.0, mutate_probability_add_connection: 1.0, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    neat.update_clients();
+    neat.update_clients();
+    neat.set_seed_genome(&seed).unwrap();
+
+    let seeded_config = NeatConfig {seed_genome: Some(path.display().to_string()), ..config};
+    let mut loaded = Neat::checkpoint_from_text(&seeded_config, &neat.checkpoint_to_text()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for _ in 0..10 {
+        let id = loaded.new_client();
+        let genome = loaded.get_client(&id).unwrap().genome();
+        assert!(genome.validate().is_empty());
+        for node in genome.nodes.values() {
+            assert!(loaded.node_bank[&node.get_innovation_number()].get_x() == node.get_x());
+        }
+        for connection in genome.connections.values() {
+            let nodes = (connection.from.get_innovation_number(), connection.to.get_innovation_number());
+            assert_eq!(loaded.nodes_to_connection_map[&nodes], connection.get_innovation_number());
+        }
+    }
+    loaded.update_clients();
+}
+
+#[test]
+fn test_genome_builder_for_neat() {
+    let mut neat = Neat::from_config(&NeatConfig::default()).unwrap();
+    let mut builder = GenomeBuilder::new();
+    builder.add_input("bias");
+    builder.add_input("a");
+    builder.add_input("b");
+    builder.add_hidden("h");
+    builder.add_output("out");
+    builder.connect("a", "h", 1.0).connect("b", "h", -1.0).connect("h", "out", 2.0).connect("bias", "out", 0.5);
+
+    let genome = neat.build_genome(&builder).unwrap();
+    assert!(genome.connections.values().all(|connection| {
+        neat.nodes_to_connection_map.get(&(connection.from.get_innovation_number(), connection.to.get_innovation_number())) == Some(&connection.get_innovation_number())
+    }));
+
+    let id = neat.new_client_from_genome(&genome).unwrap();
+    let network = Network::new(&builder.build().unwrap(), Activation::Sigmoid, true).unwrap();
+    assert_eq!(neat.use_client(&id, &vec![0.3, 0.6]), network.run(&[0.3, 0.6]));
+}
+
+#[test]
+fn test_neat_output_decoder() {
+    let config = NeatConfig::parse("input_size = 2\noutput_size = 3\noutput_decoder = scale(0:1, -1:1)").unwrap();
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    let name = neat.new_client();
+
+    let outputs = neat.use_client(&name, &vec![0.3, 0.6]);
+    let scaled = neat.use_client_decoded(&name, &vec![0.3, 0.6]);
+    for (output, value) in outputs.iter().zip(scaled.values().unwrap()) {
+        assert!((output * 2.0 - 1.0 - value).abs() < 1e-12);
+    }
+    assert_eq!(neat.get_client_network(&name).get_decoder(), neat.get_output_decoder(), "networks get the decoder");
+
+    neat.set_output_decoder(OutputDecoder::Argmax).unwrap();
+    let best = (0..3).fold(0, |best, i| if outputs[i] > outputs[best] { i } else { best });
+    assert_eq!(neat.use_client_decoded(&name, &vec![0.3, 0.6]).class(), Some(best));
+    assert!(neat.set_output_decoder(OutputDecoder::Scale {from: (0.0, 1.0), to: vec![(0.0, 1.0); 2]}).is_err(), "2 ranges for 3 outputs");
+
+    let bad_config = NeatConfig {output_decoder: OutputDecoder::Scale {from: (0.0, 1.0), to: vec![(0.0, 1.0); 2]}, ..config};
+    assert!(Neat::from_config(&bad_config).is_err());
+}
+
+#[test]
+fn test_plastic_clients() {
+    let config = NeatConfig {mutate_probability_hebbian: 1.0, activation_function: Activation::Identity, ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    let mut builder = GenomeBuilder::new();
+    builder.add_input("bias");
+    builder.add_input("x");
+    builder.add_input("y");
+    builder.add_output("out");
+    builder.connect("x", "out", 1.0);
+    let mut genome = neat.build_genome(&builder).unwrap();
+    for connection in genome.connections.values_mut() {
+        connection.hebbian = HebbianRule::new(0.0, 0.0, 0.0, 1.0, 0.5);
+    }
+
+    let name = neat.new_client_from_genome(&genome).unwrap();
+    assert_eq!(neat.use_client(&name, &vec![2.0, 0.0]), vec![2.0]);
+    assert_eq!(neat.use_client(&name, &vec![2.0, 0.0]), vec![3.0], "the weight goes up by 0.5 every run");
+    neat.reset_client_state(&name);
+    assert_eq!(neat.use_client(&name, &vec![2.0, 0.0]), vec![2.0]);
+}
+
+#[test]
+fn test_client_ctrnn() {
+    let config = NeatConfig {mutate_probability_time_constant: 1.0, ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    let name = neat.new_client();
+
+    //held inputs settle on what the client outputs
+    let mut ctrnn = neat.get_client_ctrnn(&name);
+    assert_eq!(ctrnn.get_number_of_inputs(), 2);
+    let settled = ctrnn.advance(&[0.3, 0.6], 0.1, 2000);
+    let expected = neat.use_client(&name, &vec![0.3, 0.6]);
+    assert!((settled[0] - expected[0]).abs() < 1e-6, "ctrnn settled on {}, client gave {}", settled[0], expected[0]);
+}
+
+#[test]
+fn test_client_spiking_network() {
+    let config = NeatConfig {mutate_probability_neuron: 1.0, output_size: 2, ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    for _ in 0..5 {
+        neat.update_clients();
+    }
+    let name = neat.get_client_names()[0].clone();
+
+    let mut network = neat.get_client_spiking_network(&name, NeuronModel::Izhikevich, InputEncoding::Rate {max_rate: 0.5});
+    assert_eq!(network.get_number_of_inputs(), 2);
+    assert_eq!(network.run(&[0.3, 0.6], 1.0, 50).len(), 2);
+}
+
+#[test]
+fn test_self_adaptive_mutation_rates() {
+    let config = NeatConfig {mutation_rate_adaptation: 0.5, ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    let base_rates = neat.get_base_mutation_rates();
+    assert!(neat.clients.values().all(|client_ref| client_ref.borrow().get_genome().borrow().get_mutation_rates() == Some(base_rates)));
+    assert!(neat.get_mutation_rate_report().is_none());
+
+    neat.update_clients();
+    assert!(neat.clients.values().any(|client_ref| client_ref.borrow().get_genome().borrow().get_mutation_rates() != Some(base_rates)),
+            "mutating a genome mutates its probabilities");
+    let report = neat.get_mutation_rate_report().unwrap();
+    assert_eq!(report.generation, 0);
+    assert_eq!(report.multiplier, 1.0);
+    assert!(report.mean.to_array().iter().all(|probability| (0.0..=1.0).contains(probability)));
+
+    //the probabilities are part of the genome text
+    let name = neat.get_client_names()[0].clone();
+    let genome_ref = neat.clients[&name].borrow().get_genome();
+    let text = genome_ref.borrow().to_text();
+    assert!(text.starts_with("mutation_rates add_node="));
+    assert!(Genome::from_text(&text).unwrap() == *genome_ref.borrow());
+    assert!(Genome::from_text(&text.replacen("add_node=", "add_node=-", 1)).is_err());
+
+    let loaded = Neat::checkpoint_from_text(&config, &neat.checkpoint_to_text()).unwrap();
+    assert_eq!(loaded.checkpoint_to_text(), neat.checkpoint_to_text());
+}
+
+#[test]
+fn test_mutation_schedule() {
+    let config = NeatConfig {mutation_schedule: MutationSchedule::Decay {rate: 0.5, min: 0.3}, ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    neat.update_clients();
+    neat.update_clients();
+    let report = neat.get_mutation_rate_report().unwrap();
+    assert_eq!((report.generation, report.multiplier), (1, 0.5));
+    assert!((report.mean.add_node - config.mutate_probability_add_node / 2.0).abs() < 1e-12, "half as likely");
+    neat.update_clients();
+    assert_eq!(neat.get_mutation_rate_report().unwrap().multiplier, 0.3);
+
+    //every score is 0, so the best score stops improving after the first generation
+    let config = NeatConfig {mutation_schedule: "stagnation(1, 2, 3)".parse().unwrap(), ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    for _ in 0..10 {
+        neat.new_client();
+    }
+    let mut multipliers = Vec::new();
+    for _ in 0..4 {
+        neat.update_clients();
+        multipliers.push(neat.get_mutation_rate_report().unwrap().multiplier);
+    }
+    assert_eq!(multipliers, vec![1.0, 2.0, 3.0, 3.0]);
+
+    let loaded = Neat::checkpoint_from_text(&config, &neat.checkpoint_to_text()).unwrap();
+    assert_eq!((loaded.best_fitness, loaded.generations_since_fitness_improved), (0.0, 3));
+}
+
+#[test]
+fn test_mutation_probability_config() {
+    let config = NeatConfig::parse("mutate_chance_add_connection = 4\nmutate_probability_add_node = 0.5\nmutate_probability_add_node = 0.35\nmutation_mode = exactly_one\nmutation_rounds = 3").unwrap();
+    assert_eq!(config.mutate_probability_add_node, 0.35, "the last one counts");
+    assert_eq!(config.mutate_probability_add_connection, 0.25, "1 in 4");
+    assert!(!config.to_text().contains("mutate_chance"));
+    assert_eq!(config.mutation_mode, MutationMode::ExactlyOne);
+    assert_eq!(config.mutation_rounds, 3);
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+
+    assert!(NeatConfig::parse("mutate_probability_add_node = 1.5").is_err());
+    assert!(NeatConfig::parse("mutate_probability_add_everything = 0.5").is_err());
+    assert!(NeatConfig::parse("mutation_mode = sometimes").is_err());
+
+    let neat = Neat::from_config(&config).unwrap();
+    assert_eq!(neat.get_mutation_probability(Mutation::AddNode), 0.35);
+    assert_eq!(neat.get_mutation_probability(Mutation::AddConnection), 0.25, "1 in 4");
+    assert_eq!(neat.get_mutation_probability(Mutation::RemoveNode), 0.0);
+    assert_eq!(neat.get_base_mutation_rates().add_node, 0.35);
+}
+
+#[test]
+fn test_initial_topologies() {
+    //(nodes, connections) of a new client's genome
+    let new_genome_size = |neat: &mut Neat| {
+        let name = neat.new_client();
+        let genome_ref = neat.clients[&*name].borrow().get_genome();
+        let genome = genome_ref.borrow();
+        (genome.nodes.len(), genome.connections.len())
+    };
+
+    let config = NeatConfig {initial_topology: InitialTopology::FullyConnectedHidden(2), initial_mutate_full: false, ..NeatConfig::default()};
+    assert_eq!(NeatConfig::parse(&config.to_text()).unwrap(), config);
+    let mut neat = Neat::from_config(&config).unwrap();
+    assert_eq!(new_genome_size(&mut neat), (3 + 1 + 2, 3 * 2 + 2));
+    let bank_size = neat.node_bank.len();
+    new_genome_size(&mut neat);
+    assert_eq!(neat.node_bank.len(), bank_size, "every client uses the same hidden nodes");
+
+    let mut loaded = Neat::checkpoint_from_text(&config, &neat.checkpoint_to_text()).unwrap();
+    new_genome_size(&mut loaded);
+    assert_eq!(loaded.node_bank.len(), bank_size, "the hidden nodes are found again after loading");
+
+    let config = NeatConfig {initial_topology: "sparse(0.5)".parse().unwrap(), initial_mutate_full: false, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    assert_eq!(new_genome_size(&mut neat), (4, 2), "half of 3 connections, rounded");
+
+    let config = NeatConfig {initial_topology: InitialTopology::Unconnected, initial_mutate_full: false, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    assert_eq!(new_genome_size(&mut neat), (4, 0));
+    let name = neat.get_client_names()[0].clone();
+    assert_eq!(neat.use_client(&name, &vec![0.3, 0.6]), vec![Activation::Sigmoid.apply(0.0)]);
+
+    //mutate_full can only add a connection to an unconnected genome
+    let config = NeatConfig {initial_topology: InitialTopology::Unconnected, ..NeatConfig::default()};
+    let mut neat = Neat::from_config(&config).unwrap();
+    assert_eq!(new_genome_size(&mut neat), (4, 1));
+    for _ in 0..9 {
+        neat.new_client();
+    }
+    for _ in 0..5 {
+        neat.update_clients();
+    }
+}
@@ -0,0 +1,114 @@
+use rand::Rng;
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/* - WeightDistribution -
+What mutate_random_weight draws a new weight from / what mutate_weight_shift adds to a weight
+all centred on 0
+    uniform(max) - anywhere in -max..max
+    gaussian(sigma) - normal distribution, mostly small changes
+    cauchy(scale) - like gaussian but with much heavier tails, the odd big jump
+written as `name(parameter)`, eg. `gaussian(0.5)`
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightDistribution {
+    Uniform {max: f64},
+    Gaussian {sigma: f64},
+    Cauchy {scale: f64},
+}
+
+impl WeightDistribution {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        match *self {
+            WeightDistribution::Uniform {max} => {
+                if max <= 0.0 { return 0.0; } //gen_range panics on an empty range
+                rng.gen_range(-max..max)
+            },
+            WeightDistribution::Gaussian {sigma} => {
+                //box-muller, 1-gen keeps the log away from 0
+                let u1: f64 = 1.0 - rng.gen::<f64>();
+                let u2: f64 = rng.gen::<f64>();
+                sigma * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+            },
+            WeightDistribution::Cauchy {scale} => {
+                scale * (PI * (rng.gen::<f64>() - 0.5)).tan()
+            },
+        }
+    }
+}
+
+impl fmt::Display for WeightDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeightDistribution::Uniform {max} => write!(f, "uniform({})", max),
+            WeightDistribution::Gaussian {sigma} => write!(f, "gaussian({})", sigma),
+            WeightDistribution::Cauchy {scale} => write!(f, "cauchy({})", scale),
+        }
+    }
+}
+
+impl FromStr for WeightDistribution {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bad_format = || format!("expected uniform(max), gaussian(sigma) or cauchy(scale), got '{}'", text);
+
+        let (name, parameter) = text.trim().strip_suffix(')')
+            .and_then(|text| text.split_once('('))
+            .ok_or_else(bad_format)?;
+        let parameter: f64 = parameter.trim().parse().map_err(|_| bad_format())?;
+
+        if !(parameter >= 0.0 && parameter.is_finite()) {
+            return Err(format!("weight distribution parameter has to be a positive number, got {}", parameter));
+        }
+
+        match name.trim() {
+            "uniform" => Ok(WeightDistribution::Uniform {max: parameter}),
+            "gaussian" => Ok(WeightDistribution::Gaussian {sigma: parameter}),
+            "cauchy" => Ok(WeightDistribution::Cauchy {scale: parameter}),
+            _ => Err(bad_format()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_displays() {
+        for text in ["uniform(2)", "gaussian(0.5)", "cauchy(0.1)"].iter() {
+            let distribution: WeightDistribution = text.parse().unwrap();
+            assert_eq!(distribution.to_string(), *text);
+        }
+
+        assert_eq!("gaussian( 0.25 )".parse(), Ok(WeightDistribution::Gaussian {sigma: 0.25}));
+        assert!("gaussian".parse::<WeightDistribution>().is_err());
+        assert!("poisson(1)".parse::<WeightDistribution>().is_err());
+        assert!("uniform(-1)".parse::<WeightDistribution>().is_err());
+    }
+
+    #[test]
+    fn samples_have_the_right_spread() {
+        let mut rng = rand::thread_rng();
+
+        let uniform = WeightDistribution::Uniform {max: 3.0};
+        assert!((0..1000).map(|_| uniform.sample(&mut rng)).all(|sample| (-3.0..3.0).contains(&sample)));
+        assert_eq!(WeightDistribution::Uniform {max: 0.0}.sample(&mut rng), 0.0);
+
+        //mean and standard deviation of 10000 samples should be close to 0 and sigma
+        let gaussian = WeightDistribution::Gaussian {sigma: 2.0};
+        let samples: Vec<f64> = (0..10000).map(|_| gaussian.sample(&mut rng)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let deviation = (samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / samples.len() as f64).sqrt();
+        assert!(mean.abs() < 0.1, "mean {}", mean);
+        assert!((deviation - 2.0).abs() < 0.1, "standard deviation {}", deviation);
+
+        //half of a cauchy's samples are within one scale of 0
+        let cauchy = WeightDistribution::Cauchy {scale: 1.0};
+        let within = (0..10000).filter(|_| cauchy.sample(&mut rng).abs() < 1.0).count();
+        assert!((within as i64 - 5000).abs() < 300, "{} within the scale", within);
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::str::FromStr;
+
+/* - OutputDecoder -
+Turns a network's raw outputs into something a task can use directly, attached to a Neat (use_client_decoded) or
+a Network (run_decoded)
+    raw - the outputs as they are
+    softmax - probabilities that add up to 1
+    argmax - index of the biggest output (the first one on a tie)
+    threshold(t) - a label for each output, on when the output is >= t
+    scale(from, to...) - each output mapped from the from range into a to range (clamped to it), one to range for
+        every output or one per output, ranges are written min:max
+written like `threshold(0.5)` or `scale(0:1,-2:2,0:10)`
+ */
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum OutputDecoder {
+    #[default]
+    Raw,
+    Softmax,
+    Argmax,
+    Threshold(f64),
+    Scale {from: (f64, f64), to: Vec<(f64, f64)>},
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedOutput {
+    Values(Vec<f64>), //raw, softmax and scale
+    Class(usize), //argmax
+    Labels(Vec<bool>), //threshold
+}
+
+impl DecodedOutput {
+    pub fn values(&self) -> Option<&[f64]> {
+        match self {
+            DecodedOutput::Values(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn class(&self) -> Option<usize> {
+        match self {
+            DecodedOutput::Class(class) => Some(*class),
+            _ => None,
+        }
+    }
+
+    pub fn labels(&self) -> Option<&[bool]> {
+        match self {
+            DecodedOutput::Labels(labels) => Some(labels),
+            _ => None,
+        }
+    }
+}
+
+impl OutputDecoder {
+    //panics if the decoder doesn't fit the outputs (see check)
+    pub fn decode(&self, outputs: &[f64]) -> DecodedOutput {
+        if let Err(message) = self.check(outputs.len()) {
+            panic!("can't decode {} outputs: {}", outputs.len(), message);
+        }
+
+        match self {
+            OutputDecoder::Raw => DecodedOutput::Values(outputs.to_vec()),
+            OutputDecoder::Softmax => DecodedOutput::Values(softmax(outputs)),
+            OutputDecoder::Argmax => {
+                let mut best = 0;
+                for (i, output) in outputs.iter().enumerate().skip(1) {
+                    if *output > outputs[best] {
+                        best = i;
+                    }
+                }
+                DecodedOutput::Class(best)
+            },
+            OutputDecoder::Threshold(threshold) => DecodedOutput::Labels(outputs.iter().map(|output| output >= threshold).collect()),
+            OutputDecoder::Scale {from, to} => DecodedOutput::Values(outputs.iter().enumerate().map(|(i, output)| {
+                let (to_min, to_max) = if to.len() == 1 { to[0] } else { to[i] };
+                let proportion = (output - from.0) / (from.1 - from.0);
+                (to_min + proportion * (to_max - to_min)).max(to_min).min(to_max)
+            }).collect()),
+        }
+    }
+
+    //whether the decoder can decode this many outputs
+    pub fn check(&self, number_of_outputs: usize) -> Result<(), String> {
+        match self {
+            OutputDecoder::Argmax if number_of_outputs == 0 => Err(String::from("argmax needs at least one output")),
+            OutputDecoder::Scale {from, to} => {
+                if from.0 == from.1 {
+                    return Err(format!("can't scale from the empty range {}:{}", from.0, from.1));
+                }
+                if to.len() != 1 && to.len() != number_of_outputs {
+                    return Err(format!("scale has {} to ranges, expected 1 or {} (one per output)", to.len(), number_of_outputs));
+                }
+                Ok(())
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+//exp of each value over the sum of them, the max is taken off first so exp doesn't overflow
+fn softmax(values: &[f64]) -> Vec<f64> {
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = values.iter().map(|value| (value - max).exp()).collect();
+    let total: f64 = exps.iter().sum();
+    exps.iter().map(|value| value / total).collect()
+}
+
+impl fmt::Display for OutputDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputDecoder::Raw => write!(f, "raw"),
+            OutputDecoder::Softmax => write!(f, "softmax"),
+            OutputDecoder::Argmax => write!(f, "argmax"),
+            OutputDecoder::Threshold(threshold) => write!(f, "threshold({})", threshold),
+            OutputDecoder::Scale {from, to} => {
+                let ranges: Vec<String> = [*from].iter().chain(to.iter()).map(|(min, max)| format!("{}:{}", min, max)).collect();
+                write!(f, "scale({})", ranges.join(","))
+            },
+        }
+    }
+}
+
+impl FromStr for OutputDecoder {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let bad_format = || format!("expected raw, softmax, argmax, threshold(t) or scale(from, to...), got '{}'", text.trim());
+
+        match text.trim() {
+            "raw" => return Ok(OutputDecoder::Raw),
+            "softmax" => return Ok(OutputDecoder::Softmax),
+            "argmax" => return Ok(OutputDecoder::Argmax),
+            _ => {},
+        }
+
+        let (name, parameters) = text.trim().strip_suffix(')')
+            .and_then(|text| text.split_once('('))
+            .ok_or_else(bad_format)?;
+
+        match name.trim() {
+            "threshold" => parameters.trim().parse().map(OutputDecoder::Threshold).map_err(|_| bad_format()),
+            "scale" => {
+                let ranges = parameters.split(',').map(|range| {
+                    let (min, max) = range.split_once(':').ok_or_else(bad_format)?;
+                    match (min.trim().parse::<f64>(), max.trim().parse::<f64>()) {
+                        (Ok(min), Ok(max)) if min <= max => Ok((min, max)),
+                        _ => Err(format!("bad range '{}', expected min:max", range.trim())),
+                    }
+                }).collect::<Result<Vec<(f64, f64)>, String>>()?;
+
+                match ranges.split_first() {
+                    Some((from, to)) if !to.is_empty() => Ok(OutputDecoder::Scale {from: *from, to: to.to_vec()}),
+                    _ => Err(bad_format()),
+                }
+            },
+            _ => Err(bad_format()),
+        }
+    }
+}
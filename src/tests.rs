@@ -1,4 +1,7 @@
 use crate::random_hash_set::*;
+use crate::activation_functions::{Activation, ActivationFunctions, DerivativeFunctions};
+use crate::aggregation_functions::Aggregation;
+use crate::output_decoder::{OutputDecoder, DecodedOutput};
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -80,4 +83,108 @@ fn test_ref_cell_in_random_hash_set() {
     }
 
     assert_eq!(*ref_2.borrow(), "changed it a second time uwu");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_derivative_functions() {
+    for activation in Activation::ALL.iter() {
+        //away from 0 and ±1 so no kinks (relu, abs, clamped...) are crossed
+        for input in [-2.5, -0.7, 0.3, 1.9].iter() {
+            let numerical = (activation.apply(input + 1e-6) - activation.apply(input - 1e-6)) / 2e-6;
+            assert!((activation.derivative_at(*input) - numerical).abs() < 1e-5, "{} at {}: {} vs finite differences {}",
+                    activation, input, activation.derivative_at(*input), numerical);
+        }
+    }
+
+    //the free functions are still there and match
+    assert_eq!(DerivativeFunctions::from_name("tanh").unwrap()(0.4), DerivativeFunctions::tanh(0.4));
+    assert_eq!(ActivationFunctions::from_name("ReLU").unwrap()(-1.0), 0.0);
+    assert!(DerivativeFunctions::from_name("not_a_function").is_none());
+}
+
+#[test]
+fn test_activation_names() {
+    for activation in Activation::ALL.iter() {
+        assert_eq!(activation.to_string().parse::<Activation>(), Ok(*activation));
+        assert_eq!(Activation::from_name(activation.name()), Some(*activation));
+    }
+
+    assert_eq!(Activation::from_name("scuffed_sigmoid"), Some(Activation::Sigmoid), "old names still work");
+    assert_eq!(" leaky_relu ".parse::<Activation>(), Ok(Activation::LeakyRelu));
+    assert!("not_a_function".parse::<Activation>().is_err());
+
+    assert_eq!(Activation::Inverse.apply(0.0), 0.0, "inverse stays finite");
+    assert_eq!(Activation::Clamped.apply(-3.0), -1.0);
+    assert_eq!(Activation::SteepenedSigmoid.apply(0.0), 0.5);
+}
+
+#[test]
+fn test_aggregation_functions() {
+    let values = [3.0, -4.0, 1.0, 0.5];
+    let expected = [
+        (Aggregation::Sum, 0.5),
+        (Aggregation::Product, -6.0),
+        (Aggregation::Mean, 0.125),
+        (Aggregation::Max, 3.0),
+        (Aggregation::Min, -4.0),
+        (Aggregation::Median, 0.75),
+        (Aggregation::MaxAbs, -4.0),
+    ];
+
+    for (aggregation, value) in expected.iter() {
+        assert_eq!(aggregation.apply(&values), *value, "{}", aggregation);
+        assert_eq!(aggregation.apply(&[]), 0.0, "{} of nothing", aggregation);
+        assert_eq!(aggregation.apply(&[-2.5]), -2.5, "{} of one value", aggregation);
+
+        //no ties, so every gradient is defined
+        for i in 0..values.len() {
+            let shifted = |shift: f64| {
+                let mut shifted = values;
+                shifted[i] += shift;
+                aggregation.apply(&shifted)
+            };
+            let numerical = (shifted(1e-6) - shifted(-1e-6)) / 2e-6;
+            assert!((aggregation.gradient(&values)[i] - numerical).abs() < 1e-6, "{} value {}: {} vs finite differences {}",
+                    aggregation, i, aggregation.gradient(&values)[i], numerical);
+        }
+
+        assert_eq!(aggregation.to_string().parse::<Aggregation>(), Ok(*aggregation));
+    }
+
+    assert_eq!(Aggregation::Median.apply(&[5.0, -1.0, 2.0]), 2.0);
+    assert_eq!(Aggregation::MaxAbs.apply(&[-2.0, 2.0]), 2.0, "the positive one on a tie");
+    assert_eq!(Aggregation::Product.gradient(&[0.0, 3.0, 2.0]), vec![6.0, 0.0, 0.0]);
+    assert_eq!(Aggregation::default(), Aggregation::Sum);
+    assert!("average".parse::<Aggregation>().is_err());
+}
+
+#[test]
+fn test_output_decoders() {
+    let outputs = [0.2, 0.9, -0.5, 0.9];
+
+    assert_eq!(OutputDecoder::Raw.decode(&outputs), DecodedOutput::Values(outputs.to_vec()));
+    assert_eq!(OutputDecoder::Argmax.decode(&outputs).class(), Some(1), "the first one on a tie");
+    assert_eq!(OutputDecoder::Threshold(0.5).decode(&outputs).labels(), Some(&[false, true, false, true][..]));
+
+    let probabilities = OutputDecoder::Softmax.decode(&outputs).values().unwrap().to_vec();
+    assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    assert!(probabilities[1] > probabilities[0] && probabilities[0] > probabilities[2]);
+    let big = OutputDecoder::Softmax.decode(&[1000.0, 1000.0]);
+    assert_eq!(big.values(), Some(&[0.5, 0.5][..]), "big outputs shouldn't overflow");
+
+    let scale = OutputDecoder::Scale {from: (0.0, 1.0), to: vec![(-2.0, 2.0)]};
+    assert_eq!(scale.decode(&[0.0, 0.25, 1.0, 3.0]).values(), Some(&[-2.0, -1.0, 2.0, 2.0][..]), "clamped to the to range");
+    let per_output = OutputDecoder::Scale {from: (-1.0, 1.0), to: vec![(0.0, 10.0), (5.0, 6.0)]};
+    assert_eq!(per_output.decode(&[0.0, 1.0]).values(), Some(&[5.0, 6.0][..]));
+    assert!(per_output.check(3).is_err(), "needs 1 or 3 to ranges");
+    assert!(OutputDecoder::Argmax.check(0).is_err());
+
+    for decoder in [OutputDecoder::Raw, OutputDecoder::Softmax, OutputDecoder::Argmax, OutputDecoder::Threshold(0.25), per_output].iter() {
+        assert_eq!(decoder.to_string().parse::<OutputDecoder>().as_ref(), Ok(decoder));
+    }
+    assert_eq!(" scale(0:1, -1.5:1.5) ".parse(), Ok(OutputDecoder::Scale {from: (0.0, 1.0), to: vec![(-1.5, 1.5)]}));
+    assert!("scale(0:1)".parse::<OutputDecoder>().is_err(), "no to range");
+    assert!("scale(1:0,0:1)".parse::<OutputDecoder>().is_err(), "min bigger than max");
+    assert!("threshold(x)".parse::<OutputDecoder>().is_err());
+    assert!("max".parse::<OutputDecoder>().is_err());
+}